@@ -0,0 +1,62 @@
+/*
+ * Copyright (C) 2026 The Android Open Source Project
+ *
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *
+ *      http://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+package com.android.server.uwb.data;
+
+import java.util.Objects;
+
+/**
+ * Stores the UWBS timestamp reported by the UCI CORE_QUERY_UWBS_TIMESTAMP CMD, along with its
+ * resolution.
+ */
+public class UwbTimestamp {
+    /* Units of the UWBS timestamp */
+    public static final int UNIT_MICROS = 0;
+    public static final int UNIT_NANOS = 1;
+    public static final int UNIT_TICKS = 2;
+
+    public long mTimestamp;
+    public int mUnit;
+    public long mResolutionNanos;
+
+    public UwbTimestamp(long timestamp, int unit, long resolutionNanos) {
+        this.mTimestamp = timestamp;
+        this.mUnit = unit;
+        this.mResolutionNanos = resolutionNanos;
+    }
+
+    @Override
+    public boolean equals(Object o) {
+        if (this == o) return true;
+        if (!(o instanceof UwbTimestamp)) return false;
+        UwbTimestamp that = (UwbTimestamp) o;
+        return mTimestamp == that.mTimestamp && mUnit == that.mUnit
+                && mResolutionNanos == that.mResolutionNanos;
+    }
+
+    @Override
+    public int hashCode() {
+        return Objects.hash(mTimestamp, mUnit, mResolutionNanos);
+    }
+
+    @Override
+    public String toString() {
+        return "UwbTimestamp{"
+                + "mTimestamp=" + mTimestamp
+                + ", mUnit=" + mUnit
+                + ", mResolutionNanos=" + mResolutionNanos
+                + '}';
+    }
+}
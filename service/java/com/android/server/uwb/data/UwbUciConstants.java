@@ -249,6 +249,10 @@ public class UwbUciConstants {
     public static final int STATUS_CODE_CCC_LIFECYCLE = STATUS_ERROR_CCC_LIFECYCLE;
     public static final int STATUS_CODE_ANDROID_REGULATION_UWB_OFF = STATUS_REGULATION_UWB_OFF;
 
+    /* Status code of the UWB JNI when the chip of the request is not registered, reserved for
+     * future use in the generic status codes so that no UWBS reports it. */
+    public static final int STATUS_CODE_JNI_NO_SUCH_CHIP = 0x10;
+
     /**
      * Table 28: Status codes in the DATA_TRANSFER_STATUS_NTF.
      */
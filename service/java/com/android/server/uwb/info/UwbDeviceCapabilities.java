@@ -0,0 +1,92 @@
+/*
+ * Copyright (C) 2026 The Android Open Source Project
+ *
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *
+ *      http://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+package com.android.server.uwb.info;
+
+import java.util.Arrays;
+
+/**
+ * Capabilities of the UWB subsystem commonly looked up by the framework, decoded from the
+ * capability TLVs reported by the UCI CORE_GET_CAPS_INFO CMD.
+ */
+public class UwbDeviceCapabilities {
+    private static final String TAG = UwbDeviceCapabilities.class.getSimpleName();
+
+    /**
+     * Max number of concurrent ranging sessions, -1 if not advertised.
+     */
+    private int mMaxRangingSessions;
+
+    /**
+     * RANGING_ROUND_USAGE values supported by the UWB subsystem.
+     */
+    private int[] mRoundUsages;
+
+    /**
+     * UWB channels supported by the UWB subsystem.
+     */
+    private int[] mChannels;
+
+    /**
+     * Capability TLVs not decoded above, in their UCI encoding.
+     */
+    private byte[] mRawRemaining;
+
+    public UwbDeviceCapabilities(int maxRangingSessions, int[] roundUsages, int[] channels,
+            byte[] rawRemaining) {
+        mMaxRangingSessions = maxRangingSessions;
+        mRoundUsages = roundUsages;
+        mChannels = channels;
+        mRawRemaining = rawRemaining;
+    }
+
+    /**
+     * get max number of concurrent ranging sessions, -1 if not advertised
+     */
+    public int getMaxRangingSessions() {
+        return mMaxRangingSessions;
+    }
+
+    /**
+     * get supported ranging round usages
+     */
+    public int[] getRoundUsages() {
+        return mRoundUsages;
+    }
+
+    /**
+     * get supported channels
+     */
+    public int[] getChannels() {
+        return mChannels;
+    }
+
+    /**
+     * get capability TLVs not decoded, in their UCI encoding
+     */
+    public byte[] getRawRemaining() {
+        return mRawRemaining;
+    }
+
+    @Override
+    public String toString() {
+        StringBuilder sb = new StringBuilder();
+        sb.append("UwbDeviceCapabilities: max_ranging_sessions=").append(mMaxRangingSessions)
+                .append(" round_usages=").append(Arrays.toString(mRoundUsages))
+                .append(" channels=").append(Arrays.toString(mChannels))
+                .append(" raw_remaining=").append(Arrays.toString(mRawRemaining));
+        return sb.toString();
+    }
+}
@@ -96,6 +96,15 @@ public interface INativeUwbManager {
          */
         void onDataTransferPhaseConfigNotificationReceived(long sessionId,
                 int dataTransferPhaseConfigStatus);
+
+        /**
+         * Interface for receiving the data credit notification
+         *
+         * @param sessionId          : Session ID
+         * @param creditAvailability : CREDIT_AVAILABILITY in the DATA_CREDIT_NTF packet, 0 when the
+         *                             credit is exhausted and 1 when it is available
+         */
+        default void onDataCreditNotificationReceived(long sessionId, int creditAvailability) {}
     }
 
     interface DeviceNotification {
@@ -115,6 +124,14 @@ public interface INativeUwbManager {
          * @param chipId : identifier of UWB chip for multi-HAL devices
          */
         void onCoreGenericErrorNotificationReceived(int status, String chipId);
+
+        /**
+         * Interface for receiving the unexpected disconnection of the HAL. The sessions of the chip
+         * are lost, and the HAL has to be reopened.
+         *
+         * @param chipId : identifier of UWB chip for multi-HAL devices
+         */
+        default void onHalDisconnected(String chipId) {}
     }
 
     interface VendorNotification {
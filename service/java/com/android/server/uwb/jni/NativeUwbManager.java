@@ -28,10 +28,14 @@ import com.android.server.uwb.data.UwbDeviceInfoResponse;
 import com.android.server.uwb.data.UwbMulticastListUpdateStatus;
 import com.android.server.uwb.data.UwbRadarData;
 import com.android.server.uwb.data.UwbRangingData;
+import com.android.server.uwb.data.UwbTimestamp;
 import com.android.server.uwb.data.UwbTlvData;
+import com.android.server.uwb.data.UwbTwoWayMeasurement;
 import com.android.server.uwb.data.UwbUciConstants;
 import com.android.server.uwb.data.UwbVendorUciResponse;
+import com.android.server.uwb.info.UwbDeviceCapabilities;
 import com.android.server.uwb.info.UwbPowerStats;
+import com.android.server.uwb.info.UwbPowerStatsLong;
 import com.android.server.uwb.multchip.UwbMultichipData;
 
 import java.util.Arrays;
@@ -94,6 +98,14 @@ public class NativeUwbManager {
         mDeviceListener.onCoreGenericErrorNotificationReceived(status, chipId);
     }
 
+    /**
+     * HAL disconnection callback invoked via the JNI, once per unexpected disconnection of the HAL
+     */
+    public void onHalDisconnected(String chipId) {
+        Log.e(TAG, "onHalDisconnected(" + chipId + ")");
+        mDeviceListener.onHalDisconnected(chipId);
+    }
+
     public void onSessionStatusNotificationReceived(long id, int state, int reasonCode) {
         Log.d(TAG, "onSessionStatusNotificationReceived(" + id + ", " + state + ", " + reasonCode
                 + ")");
@@ -171,14 +183,15 @@ public class NativeUwbManager {
     }
 
     /**
-     * Gets the timestamp resolution in nanosecond
+     * Gets the timestamp resolution in nanosecond of the default UWB chip, measured from its
+     * timestamps the first time it is queried
      *
-     * @return : timestamp resolution in nanosecond
+     * @return : timestamp resolution in nanosecond, 0 if failed
      */
     public long getTimestampResolutionNanos() {
-        return 0L;
-        /* TODO: Not Implemented in native stack
-        return nativeGetTimestampResolutionNanos(); */
+        synchronized (mNativeLock) {
+            return nativeGetTimestampResolutionNanos();
+        }
     }
 
     /**
@@ -356,7 +369,9 @@ public class NativeUwbManager {
      *                          0x02 - Adding with 16 bits sub-session key
      *                          0x03 - Adding with 32 bits sub-session key
      * @param noOfControlee     : The number(n) of Controlees
-     * @param addresses         : address list of Controlees
+     * @param addresses         : address list of Controlees, 2 bytes each, or 8 bytes each with
+     *                          the short address in the 2 LSB if the session is in an extended
+     *                          MAC address mode
      * @param subSessionIds     : Specific sub-session ID list of Controlees
      * @param subSessionKeyList : Sub-session key list of Controlees
      * @return : refer to SESSION_SET_APP_CONFIG_RSP
@@ -371,6 +386,44 @@ public class NativeUwbManager {
         }
     }
 
+    /**
+     * Adds controlees to the multicast list of the UWB session, only sending the ones not already
+     * in the list.
+     *
+     * @param sessionId     : Session ID to which the controlees are added
+     * @param noOfControlee : The number(n) of Controlees
+     * @param addresses     : address list of Controlees
+     * @param subSessionIds : Specific sub-session ID list of Controlees
+     * @param chipId        : Identifier of UWB chip for multi-HAL devices
+     * @return : Byte representing the status of the operation
+     */
+    public byte addControlees(int sessionId, int noOfControlee, byte[] addresses,
+            int[] subSessionIds, String chipId) {
+        synchronized (mNativeLock) {
+            return nativeAddControlees(sessionId, (byte) noOfControlee, addresses, subSessionIds,
+                    chipId);
+        }
+    }
+
+    /**
+     * Removes controlees from the multicast list of the UWB session, only sending the ones in the
+     * list.
+     *
+     * @param sessionId     : Session ID from which the controlees are removed
+     * @param noOfControlee : The number(n) of Controlees
+     * @param addresses     : address list of Controlees
+     * @param subSessionIds : Specific sub-session ID list of Controlees
+     * @param chipId        : Identifier of UWB chip for multi-HAL devices
+     * @return : Byte representing the status of the operation
+     */
+    public byte removeControlees(int sessionId, int noOfControlee, byte[] addresses,
+            int[] subSessionIds, String chipId) {
+        synchronized (mNativeLock) {
+            return nativeRemoveControlees(sessionId, (byte) noOfControlee, addresses,
+                    subSessionIds, chipId);
+        }
+    }
+
     /**
      * Set country code.
      *
@@ -438,6 +491,14 @@ public class NativeUwbManager {
         mSessionListener.onDataSendStatus(sessionId, dataTransferStatus, sequenceNum, txCount);
     }
 
+    /**
+     * Receive the data credit notification for a session.
+     */
+    public void onDataCreditNotificationReceived(long sessionId, int creditAvailability) {
+        Log.d(TAG, "onDataCreditNotificationReceived ");
+        mSessionListener.onDataCreditNotificationReceived(sessionId, creditAvailability);
+    }
+
     /**
      * Set Data transfer phase configuration
      */
@@ -533,72 +594,1570 @@ public class NativeUwbManager {
         }
     }
 
-    private native byte nativeSendData(int sessionId, byte[] address,
-            short sequenceNum, byte[] appData, String chipId);
-
-    private native byte nativeSessionDataTransferPhaseConfig(int sessionId, byte dtpcmRepetition,
-            byte dataTransferControl, byte dtpmlSize, byte[] macAddress, byte[] slotBitmap,
-            String chipId);
+    /**
+     * Sets the number of transmissions per ranging round of the UWB session, the number of frames
+     * the advertiser of an OWR for AoA session transmits in each ranging round.
+     *
+     * @param sessionId : Session ID of the UWB session
+     * @param count : Number of transmissions per ranging round
+     * @param chipId : Identifier of UWB chip for multi-HAL devices
+     * @return : Byte representing the status of the operation
+     */
+    public byte setTxPerRound(int sessionId, int count, String chipId) {
+        synchronized (mNativeLock) {
+            return nativeSetTxPerRound(sessionId, count, chipId);
+        }
+    }
 
-    private native long nativeDispatcherNew(Object[] chipIds);
+    /**
+     * Gets the number of transmissions per ranging round of the UWB session.
+     *
+     * @param sessionId : Session ID of the UWB session
+     * @param chipId : Identifier of UWB chip for multi-HAL devices
+     * @return : Number of transmissions per ranging round, or -1 if failed.
+     */
+    public int getTxPerRound(int sessionId, String chipId) {
+        synchronized (mNativeLock) {
+            return nativeGetTxPerRound(sessionId, chipId);
+        }
+    }
 
-    private native void nativeDispatcherDestroy();
+    /**
+     * Sets the slot duration of the UWB session.
+     *
+     * @param sessionId : Session ID of the UWB session
+     * @param slotDurationRstu : Slot duration in RSTU
+     * @param chipId : Identifier of UWB chip for multi-HAL devices
+     * @return : Byte representing the status of the operation
+     */
+    public byte setSlotDuration(int sessionId, int slotDurationRstu, String chipId) {
+        synchronized (mNativeLock) {
+            return nativeSetSlotDuration(sessionId, slotDurationRstu, chipId);
+        }
+    }
 
-    private native boolean nativeInit();
+    /**
+     * Gets the slot duration of the UWB session.
+     *
+     * @param sessionId : Session ID of the UWB session
+     * @param chipId : Identifier of UWB chip for multi-HAL devices
+     * @return : Slot duration in RSTU, or -1 if failed.
+     */
+    public int getSlotDuration(int sessionId, String chipId) {
+        synchronized (mNativeLock) {
+            return nativeGetSlotDuration(sessionId, chipId);
+        }
+    }
 
-    private native UwbDeviceInfoResponse nativeDoInitialize(String chipIds);
+    /**
+     * Gets the base unit of the ranging interval of the UWB session.
+     *
+     * @param sessionId : Session ID of the UWB session
+     * @param chipId : Identifier of UWB chip for multi-HAL devices
+     * @return : Base unit of the ranging interval in RSTU, or -1 if failed.
+     */
+    public int getBlockTimingBase(int sessionId, String chipId) {
+        synchronized (mNativeLock) {
+            return nativeGetBlockTimingBase(sessionId, chipId);
+        }
+    }
 
-    private native boolean nativeDoDeinitialize(String chipId);
+    /**
+     * Checks whether the UWBS supports provisioned STS.
+     *
+     * @param chipId : Identifier of UWB chip for multi-HAL devices
+     * @return : true if provisioned STS is supported, false otherwise or if failed.
+     */
+    public boolean isProvisionedStsSupported(String chipId) {
+        synchronized (mNativeLock) {
+            return nativeIsProvisionedStsSupported(chipId);
+        }
+    }
 
-    private native long nativeGetTimestampResolutionNanos();
+    /**
+     * Sets the responder initiator time offset of the UWB session.
+     *
+     * @param sessionId : Session ID of the UWB session
+     * @param offset : Time offset in ms
+     * @param chipId : Identifier of UWB chip for multi-HAL devices
+     * @return : Byte representing the status of the operation
+     */
+    public byte setRangingTimeOffset(int sessionId, int offset, String chipId) {
+        synchronized (mNativeLock) {
+            return nativeSetRangingTimeOffset(sessionId, offset, chipId);
+        }
+    }
 
-    private native UwbPowerStats nativeGetPowerStats(String chipId);
+    /**
+     * Gets the responder initiator time offset of the UWB session.
+     *
+     * @param sessionId : Session ID of the UWB session
+     * @param chipId : Identifier of UWB chip for multi-HAL devices
+     * @return : Time offset in ms, or -1 if failed.
+     */
+    public int getRangingTimeOffset(int sessionId, String chipId) {
+        synchronized (mNativeLock) {
+            return nativeGetRangingTimeOffset(sessionId, chipId);
+        }
+    }
 
-    private native byte nativeDeviceReset(byte resetConfig, String chipId);
+    /**
+     * Gets the number of ranging rounds completed by the UWB session.
+     *
+     * @param sessionId : Session ID of the UWB session
+     * @param chipId : Identifier of UWB chip for multi-HAL devices
+     * @return : Number of completed ranging rounds, or -1 if failed.
+     */
+    public long getCompletedRoundCount(int sessionId, String chipId) {
+        synchronized (mNativeLock) {
+            return nativeGetCompletedRoundCount(sessionId, chipId);
+        }
+    }
 
-    private native byte nativeSessionInit(int sessionId, byte sessionType, String chipId);
+    /**
+     * Sets the maximum number of slots per ranging round of the UWB session.
+     *
+     * @param sessionId : Session ID of the UWB session
+     * @param slots : Number of slots per ranging round
+     * @param chipId : Identifier of UWB chip for multi-HAL devices
+     * @return : Byte representing the status of the operation
+     */
+    public byte setSlotsPerRound(int sessionId, int slots, String chipId) {
+        synchronized (mNativeLock) {
+            return nativeSetSlotsPerRound(sessionId, slots, chipId);
+        }
+    }
 
-    private native byte nativeSessionDeInit(int sessionId, String chipId);
+    /**
+     * Gets the maximum number of slots per ranging round of the UWB session.
+     *
+     * @param sessionId : Session ID of the UWB session
+     * @param chipId : Identifier of UWB chip for multi-HAL devices
+     * @return : Number of slots per ranging round, or -1 if failed.
+     */
+    public int getSlotsPerRound(int sessionId, String chipId) {
+        synchronized (mNativeLock) {
+            return nativeGetSlotsPerRound(sessionId, chipId);
+        }
+    }
 
-    private native byte nativeGetSessionCount(String chipId);
+    /**
+     * Sets the application defined sub-session id of the UWB session.
+     *
+     * @param sessionId : Session ID of the UWB session
+     * @param subSessionId : Sub-session id of the session
+     * @param chipId : Identifier of UWB chip for multi-HAL devices
+     * @return : Byte representing the status of the operation
+     */
+    public byte setSubSessionId(int sessionId, int subSessionId, String chipId) {
+        synchronized (mNativeLock) {
+            return nativeSetSubSessionId(sessionId, subSessionId, chipId);
+        }
+    }
 
-    private native byte nativeRangingStart(int sessionId, String chipId);
+    /**
+     * Gets the application defined sub-session id of the UWB session.
+     *
+     * @param sessionId : Session ID of the UWB session
+     * @param chipId : Identifier of UWB chip for multi-HAL devices
+     * @return : Sub-session id of the session, or -1 if failed.
+     */
+    public long getSubSessionId(int sessionId, String chipId) {
+        synchronized (mNativeLock) {
+            return nativeGetSubSessionId(sessionId, chipId);
+        }
+    }
 
-    private native byte nativeRangingStop(int sessionId, String chipId);
+    /**
+     * Gets the range of FiRa PHY versions supported by the UWBS.
+     *
+     * @param chipId : Identifier of UWB chip for multi-HAL devices
+     * @return : Major and minor numbers of the min version followed by those of the max version,
+     *           or null if failed.
+     */
+    public int[] getSupportedFiraPhyVersions(String chipId) {
+        synchronized (mNativeLock) {
+            return nativeGetSupportedFiraPhyVersions(chipId);
+        }
+    }
 
-    private native byte nativeGetSessionState(int sessionId, String chipId);
+    /**
+     * Enables or disables the ranging result report phase of the UWB session.
+     *
+     * @param sessionId : Session ID of the UWB session
+     * @param enable : true to enable the result report phase
+     * @param chipId : Identifier of UWB chip for multi-HAL devices
+     * @return : Byte representing the status of the operation
+     */
+    public byte setResultReportPhase(int sessionId, boolean enable, String chipId) {
+        synchronized (mNativeLock) {
+            return nativeSetResultReportPhase(sessionId, enable, chipId);
+        }
+    }
 
-    private native UwbConfigStatusData nativeSetAppConfigurations(int sessionId, int noOfParams,
-            int appConfigParamLen, byte[] appConfigParams, String chipId);
+    /**
+     * Gets whether the ranging result report phase of the UWB session is enabled.
+     *
+     * @param sessionId : Session ID of the UWB session
+     * @param chipId : Identifier of UWB chip for multi-HAL devices
+     * @return : 1 if enabled, 0 if disabled and -1 if failed.
+     */
+    public int getResultReportPhase(int sessionId, String chipId) {
+        synchronized (mNativeLock) {
+            return nativeGetResultReportPhase(sessionId, chipId);
+        }
+    }
 
-    private native UwbTlvData nativeGetAppConfigurations(int sessionId, int noOfParams,
-            int appConfigParamLen, byte[] appConfigParams, String chipId);
+    /**
+     * Sets the minimum interval between two range data notifications of the session. The
+     * notifications received in between are dropped.
+     *
+     * @param sessionId : Session ID of the UWB session
+     * @param intervalMs : Interval in ms, 0 to disable the throttling
+     * @param chipId : Identifier of UWB chip for multi-HAL devices
+     * @return : Byte representing the status of the operation
+     */
+    public byte setMinFrameReportInterval(int sessionId, int intervalMs, String chipId) {
+        synchronized (mNativeLock) {
+            return nativeSetMinFrameReportInterval(sessionId, intervalMs, chipId);
+        }
+    }
 
-    private native UwbConfigStatusData nativeSetRadarAppConfigurations(int sessionId,
-            int noOfParams, int appConfigParamLen, byte[] appConfigParams, String chipId);
+    /**
+     * Gets the minimum interval between two range data notifications of the session.
+     *
+     * @param sessionId : Session ID of the UWB session
+     * @param chipId : Identifier of UWB chip for multi-HAL devices
+     * @return : Interval in ms, 0 if not throttled, -1 if failed
+     */
+    public int getMinFrameReportInterval(int sessionId, String chipId) {
+        synchronized (mNativeLock) {
+            return nativeGetMinFrameReportInterval(sessionId, chipId);
+        }
+    }
 
-    private native UwbTlvData nativeGetCapsInfo(String chipId);
+    /**
+     * Checks whether the UWBS can run ranging and radar sessions at the same time.
+     *
+     * @param chipId : Identifier of UWB chip for multi-HAL devices
+     * @return : true if supported, false otherwise or if failed.
+     */
+    public boolean isConcurrentRangingRadarSupported(String chipId) {
+        synchronized (mNativeLock) {
+            return nativeIsConcurrentRangingRadarSupported(chipId);
+        }
+    }
 
-    private native byte nativeControllerMulticastListUpdate(int sessionId, byte action,
-            byte noOfControlee, byte[] address, int[] subSessionId, byte[] subSessionKeyList,
-            String chipId);
+    /**
+     * Gets the most recent UCI commands sent to the UWBS, oldest first. Each command is encoded as
+     * the 2 bytes opcode, the 1 byte status and the 8 bytes timestamp in ms, in little endian.
+     *
+     * @param chipId : Identifier of UWB chip for multi-HAL devices
+     * @return : Encoded commands, or null if failed.
+     */
+    public byte[] getRecentCommands(String chipId) {
+        synchronized (mNativeLock) {
+            return nativeGetRecentCommands(chipId);
+        }
+    }
 
-    private native byte nativeSetCountryCode(byte[] countryCode, String chipId);
+    /**
+     * Sets the RFRAME configuration of the UWB session.
+     *
+     * @param sessionId : Session ID of the UWB session
+     * @param config : RFRAME configuration, 0 for SP0, 1 for SP1 and 3 for SP3
+     * @param chipId : Identifier of UWB chip for multi-HAL devices
+     * @return : Byte representing the status of the operation
+     */
+    public byte setRframeConfig(int sessionId, int config, String chipId) {
+        synchronized (mNativeLock) {
+            return nativeSetRframeConfig(sessionId, config, chipId);
+        }
+    }
 
-    private native boolean nativeSetLogMode(String logMode);
+    /**
+     * Gets the RFRAME configuration of the UWB session.
+     *
+     * @param sessionId : Session ID of the UWB session
+     * @param chipId : Identifier of UWB chip for multi-HAL devices
+     * @return : RFRAME configuration, or -1 if failed.
+     */
+    public int getRframeConfig(int sessionId, String chipId) {
+        synchronized (mNativeLock) {
+            return nativeGetRframeConfig(sessionId, chipId);
+        }
+    }
 
-    private native UwbVendorUciResponse nativeSendRawVendorCmd(int mt, int gid, int oid,
-            byte[] payload, String chipId);
+    /**
+     * Gets the data stats of the UWB session since they were last reset.
+     *
+     * @param sessionId : Session ID of the UWB session
+     * @param chipId : Identifier of UWB chip for multi-HAL devices
+     * @return : Bytes sent, bytes received and throughput in bits per second, or null if failed.
+     */
+    public long[] getDataThroughputStats(int sessionId, String chipId) {
+        synchronized (mNativeLock) {
+            return nativeGetDataThroughputStats(sessionId, chipId);
+        }
+    }
 
-    private native DtTagUpdateRangingRoundsStatus nativeSessionUpdateDtTagRangingRounds(
-            int sessionId, int noOfActiveRangingRounds, byte[] rangingRoundIndexes, String chipId);
+    /**
+     * Resets the data stats of the UWB session.
+     *
+     * @param sessionId : Session ID of the UWB session
+     * @param chipId : Identifier of UWB chip for multi-HAL devices
+     * @return : Byte representing the status of the operation
+     */
+    public byte resetDataThroughputStats(int sessionId, String chipId) {
+        synchronized (mNativeLock) {
+            return nativeResetDataThroughputStats(sessionId, chipId);
+        }
+    }
 
-    private native short nativeQueryDataSize(int sessionId, String chipId);
+    /**
+     * Sets the preamble duration of the UWB session.
+     *
+     * @param sessionId : Session ID of the UWB session
+     * @param durationSymbols : Preamble duration, either 32 or 64 symbols
+     * @param chipId : Identifier of UWB chip for multi-HAL devices
+     * @return : Byte representing the status of the operation
+     */
+    public byte setPreambleDuration(int sessionId, int durationSymbols, String chipId) {
+        synchronized (mNativeLock) {
+            return nativeSetPreambleDuration(sessionId, durationSymbols, chipId);
+        }
+    }
 
-    private native long nativeQueryUwbTimestamp(String chipId);
+    /**
+     * Gets the preamble duration of the UWB session.
+     *
+     * @param sessionId : Session ID of the UWB session
+     * @param chipId : Identifier of UWB chip for multi-HAL devices
+     * @return : Preamble duration in symbols, or -1 if failed.
+     */
+    public int getPreambleDuration(int sessionId, String chipId) {
+        synchronized (mNativeLock) {
+            return nativeGetPreambleDuration(sessionId, chipId);
+        }
+    }
 
-    private native int nativeGetSessionToken(int sessionId, String chipId);
+    /**
+     * Gets the block duration of the UWB session, as adjusted by the UWBS once ranging started.
+     *
+     * @param sessionId : Session ID of the UWB session
+     * @param chipId : Identifier of UWB chip for multi-HAL devices
+     * @return : Block duration in ms, or -1 if the session is not active or if failed.
+     */
+    public int getEffectiveBlockDuration(int sessionId, String chipId) {
+        synchronized (mNativeLock) {
+            return nativeGetEffectiveBlockDuration(sessionId, chipId);
+        }
+    }
 
-    private native byte nativeSetHybridSessionConfigurations(int sessionId, int noOfPhases,
-            byte[] updateTime, byte[] phaseList, String chipId);
+    /**
+     * Sets the PSDU data rate of the UWB session.
+     *
+     * @param sessionId : Session ID of the UWB session
+     * @param rate : PSDU data rate, 0 for 6.81 Mbps, 1 for 7.80 Mbps, 2 for 27.2 Mbps, 3 for
+     *             31.2 Mbps and 4 for 850 kbps
+     * @param chipId : Identifier of UWB chip for multi-HAL devices
+     * @return : Byte representing the status of the operation
+     */
+    public byte setPsduDataRate(int sessionId, int rate, String chipId) {
+        synchronized (mNativeLock) {
+            return nativeSetPsduDataRate(sessionId, rate, chipId);
+        }
+    }
+
+    /**
+     * Gets the PSDU data rate of the UWB session.
+     *
+     * @param sessionId : Session ID of the UWB session
+     * @param chipId : Identifier of UWB chip for multi-HAL devices
+     * @return : PSDU data rate, or -1 if failed.
+     */
+    public int getPsduDataRate(int sessionId, String chipId) {
+        synchronized (mNativeLock) {
+            return nativeGetPsduDataRate(sessionId, chipId);
+        }
+    }
+
+    /**
+     * Gets the channel bandwidths supported by the UWBS, e.g. 499200 kHz for the 499.2 MHz
+     * channels.
+     *
+     * @param chipId : Identifier of UWB chip for multi-HAL devices
+     * @return : Supported bandwidths in kHz, empty if not advertised, or null if failed.
+     */
+    public int[] getSupportedBandwidths(String chipId) {
+        synchronized (mNativeLock) {
+            return nativeGetSupportedBandwidths(chipId);
+        }
+    }
+
+    /**
+     * Sets the hopping sequence of the controller of the UWB session.
+     *
+     * @param sessionId : Session ID of the UWB session
+     * @param sequence : Hopping sequence, 0 to disable hopping, 1 for the FiRa hopping sequence
+     *                 and 0xA0 to 0xAF for vendor specific sequences
+     * @param chipId : Identifier of UWB chip for multi-HAL devices
+     * @return : Byte representing the status of the operation
+     */
+    public byte setHoppingSequence(int sessionId, int sequence, String chipId) {
+        synchronized (mNativeLock) {
+            return nativeSetHoppingSequence(sessionId, sequence, chipId);
+        }
+    }
+
+    /**
+     * Gets the hopping sequence of the controller of the UWB session.
+     *
+     * @param sessionId : Session ID of the UWB session
+     * @param chipId : Identifier of UWB chip for multi-HAL devices
+     * @return : Hopping sequence, or -1 if failed.
+     */
+    public int getHoppingSequence(int sessionId, String chipId) {
+        synchronized (mNativeLock) {
+            return nativeGetHoppingSequence(sessionId, chipId);
+        }
+    }
+
+    /**
+     * Sets the number of ranging measurements of the UWB session aggregated before a single range
+     * data notification is delivered.
+     *
+     * @param sessionId : Session ID of the UWB session
+     * @param count : Number of measurements per notification, 1 to disable the batching
+     * @param chipId : Identifier of UWB chip for multi-HAL devices
+     * @return : Byte representing the status of the operation
+     */
+    public byte setBatchNotificationSize(int sessionId, int count, String chipId) {
+        synchronized (mNativeLock) {
+            return nativeSetBatchNotificationSize(sessionId, count, chipId);
+        }
+    }
+
+    /**
+     * Gets the state of the UWB radar session.
+     *
+     * @param sessionId : Session ID of the UWB session
+     * @param chipId : Identifier of UWB chip for multi-HAL devices
+     * @return : Session state, or -1 if the session is not a radar session or if failed.
+     */
+    public byte getRadarSessionState(int sessionId, String chipId) {
+        synchronized (mNativeLock) {
+            return nativeGetRadarSessionState(sessionId, chipId);
+        }
+    }
+
+    /**
+     * Checks whether a UCI log mode is supported by the native stack.
+     *
+     * @param logModeStr : UCI log mode, as passed to setLogMode
+     * @return : true if the log mode is supported, false otherwise.
+     */
+    public boolean isLogModeSupported(String logModeStr) {
+        synchronized (mNativeLock) {
+            return nativeIsLogModeSupported(logModeStr);
+        }
+    }
+
+    /**
+     * Sets the number of ranging rounds after which the STS key of the UWB session is refreshed.
+     *
+     * @param sessionId : Session ID of the UWB session
+     * @param rounds : Number of ranging rounds, a power of 2 up to 2^15 or 0 to never refresh the
+     *                 key
+     * @param chipId : Identifier of UWB chip for multi-HAL devices
+     * @return : Byte representing the status of the operation
+     */
+    public byte setStsKeyRefreshRounds(int sessionId, int rounds, String chipId) {
+        synchronized (mNativeLock) {
+            return nativeSetStsKeyRefreshRounds(sessionId, rounds, chipId);
+        }
+    }
+
+    /**
+     * Gets the number of ranging rounds after which the STS key of the UWB session is refreshed.
+     *
+     * @param sessionId : Session ID of the UWB session
+     * @param chipId : Identifier of UWB chip for multi-HAL devices
+     * @return : Number of ranging rounds, 0 if never refreshed, or -1 if failed.
+     */
+    public int getStsKeyRefreshRounds(int sessionId, String chipId) {
+        synchronized (mNativeLock) {
+            return nativeGetStsKeyRefreshRounds(sessionId, chipId);
+        }
+    }
+
+    /**
+     * Gets the STS mode of the UWB session.
+     *
+     * @param sessionId : Session ID of the UWB session
+     * @param chipId : Identifier of UWB chip for multi-HAL devices
+     * @return : 0 for static, 1 for dynamic and 2 for provisioned STS, or -1 if unset or if failed.
+     */
+    public int getStsMode(int sessionId, String chipId) {
+        synchronized (mNativeLock) {
+            return nativeGetStsMode(sessionId, chipId);
+        }
+    }
+
+    /**
+     * Sets the ranging error policy of the UWB session.
+     *
+     * @param sessionId : Session ID of the UWB session
+     * @param policy : Number of consecutive failed ranging rounds after which the session is
+     *                 stopped by the UWBS, or 0 to keep ranging
+     * @param chipId : Identifier of UWB chip for multi-HAL devices
+     * @return : Byte representing the status of the operation
+     */
+    public byte setRangingErrorPolicy(int sessionId, int policy, String chipId) {
+        synchronized (mNativeLock) {
+            return nativeSetRangingErrorPolicy(sessionId, policy, chipId);
+        }
+    }
+
+    /**
+     * Gets the device info reported by the UWBS when the HAL was last opened, without opening it
+     * again.
+     *
+     * @param chipId : Identifier of UWB chip for multi-HAL devices
+     * @return : Device info, or null if not cached yet or if failed.
+     */
+    public UwbDeviceInfoResponse getCachedDeviceInfo(String chipId) {
+        synchronized (mNativeLock) {
+            return nativeGetCachedDeviceInfo(chipId);
+        }
+    }
+
+    /**
+     * Sets the multiplier of the block duration of the session in the hybrid scheduling, as its
+     * block stride length: the session ranges in one of every {@code multiplier} blocks.
+     *
+     * @param sessionId : Session ID of the UWB session
+     * @param multiplier : Multiplier of the block duration, from 1 to 256
+     * @param chipId : Identifier of UWB chip for multi-HAL devices
+     * @return : Byte representing the status of the operation
+     */
+    public byte setBlockDurationMultiplier(int sessionId, int multiplier, String chipId) {
+        synchronized (mNativeLock) {
+            return nativeSetBlockDurationMultiplier(sessionId, multiplier, chipId);
+        }
+    }
+
+    /**
+     * Gets the multiplier of the block duration of the session in the hybrid scheduling.
+     *
+     * @param sessionId : Session ID of the UWB session
+     * @param chipId : Identifier of UWB chip for multi-HAL devices
+     * @return : Multiplier of the block duration, or -1 if failed.
+     */
+    public int getBlockDurationMultiplier(int sessionId, String chipId) {
+        synchronized (mNativeLock) {
+            return nativeGetBlockDurationMultiplier(sessionId, chipId);
+        }
+    }
+
+    /**
+     * Gets whether the data transferred in the UWB session is encrypted, which depends on its STS
+     * configuration.
+     *
+     * @param sessionId : Session ID of the UWB session
+     * @param chipId : Identifier of UWB chip for multi-HAL devices
+     * @return : 1 if encrypted, 0 if not and -1 if failed.
+     */
+    public int isDataEncrypted(int sessionId, String chipId) {
+        synchronized (mNativeLock) {
+            return nativeIsDataEncrypted(sessionId, chipId);
+        }
+    }
+
+    /**
+     * Gets the radar app configurations of the UWB session, as last accepted by the UWBS.
+     *
+     * @param sessionId : Session ID of the UWB session
+     * @param radarConfigParams : Ids of the radar app configurations to read back
+     * @param chipId : Identifier of UWB chip for multi-HAL devices
+     * @return : Radar app configurations, or null if failed or if any of the configurations has not
+     *           been set.
+     */
+    public UwbTlvData getRadarAppConfigurations(int sessionId, byte[] radarConfigParams,
+            String chipId) {
+        synchronized (mNativeLock) {
+            return nativeGetRadarAppConfigurations(sessionId, radarConfigParams, chipId);
+        }
+    }
+
+    /**
+     * Sets the number of zero bytes allowed after the app configuration TLVs, for the vendor HALs
+     * which pad them. None are allowed by default.
+     *
+     * @param maxZeroPadding : Number of zero bytes allowed after the TLVs
+     * @param chipId : Identifier of UWB chip for multi-HAL devices
+     * @return : Byte representing the status of the operation
+     */
+    public byte setAppConfigMaxZeroPadding(int maxZeroPadding, String chipId) {
+        synchronized (mNativeLock) {
+            return nativeSetAppConfigMaxZeroPadding(maxZeroPadding, chipId);
+        }
+    }
+
+    /**
+     * Sets the number of times a data message of the session is retransmitted, as the data
+     * repetition count of the session.
+     *
+     * @param sessionId : Session ID of the UWB session
+     * @param count : Number of retransmissions, from 0 to 255
+     * @param chipId : Identifier of UWB chip for multi-HAL devices
+     * @return : Byte representing the status of the operation
+     */
+    public byte setDataRetransmissionCount(int sessionId, int count, String chipId) {
+        synchronized (mNativeLock) {
+            return nativeSetDataRetransmissionCount(sessionId, count, chipId);
+        }
+    }
+
+    /**
+     * Gets the number of times a data message of the session is retransmitted.
+     *
+     * @param sessionId : Session ID of the UWB session
+     * @param chipId : Identifier of UWB chip for multi-HAL devices
+     * @return : Number of retransmissions, or -1 if failed.
+     */
+    public int getDataRetransmissionCount(int sessionId, String chipId) {
+        synchronized (mNativeLock) {
+            return nativeGetDataRetransmissionCount(sessionId, chipId);
+        }
+    }
+
+    /**
+     * Gets the lengths, in bits, of the STS keys supported by the UWBS.
+     *
+     * @param chipId : Identifier of UWB chip for multi-HAL devices
+     * @return : Supported key lengths, empty if the UWBS does not support FiRa 2.0, or null if
+     *           failed.
+     */
+    public int[] getSupportedStsKeyLengths(String chipId) {
+        synchronized (mNativeLock) {
+            return nativeGetSupportedStsKeyLengths(chipId);
+        }
+    }
+
+    /**
+     * Starts ranging on the UWB session. If the command times out, the session state is polled
+     * until timeoutMs after the command was sent, for the session to become active. The call lasts
+     * at least the default command timeout.
+     *
+     * @param sessionId : Session ID of the UWB session
+     * @param timeoutMs : Time to wait for the session state, or 0 or less to keep the default
+     *                    command timeout
+     * @param chipId : Identifier of UWB chip for multi-HAL devices
+     * @return : Byte representing the status of the operation
+     */
+    public byte rangingStartWithTimeout(int sessionId, int timeoutMs, String chipId) {
+        // Not synchronized on mNativeLock: the native locks this object for the command and for
+        // each poll only, letting the other calls through while waiting.
+        return nativeRangingStartWithTimeout(sessionId, timeoutMs, chipId);
+    }
+
+    /**
+     * Stops ranging on the UWB session. If the command times out, the session state is polled until
+     * timeoutMs after the command was sent, for the session to become idle. The call lasts at least
+     * the default command timeout.
+     *
+     * @param sessionId : Session ID of the UWB session
+     * @param timeoutMs : Time to wait for the session state, or 0 or less to keep the default
+     *                    command timeout
+     * @param chipId : Identifier of UWB chip for multi-HAL devices
+     * @return : Byte representing the status of the operation
+     */
+    public byte rangingStopWithTimeout(int sessionId, int timeoutMs, String chipId) {
+        // Not synchronized on mNativeLock: the native locks this object for the command and for
+        // each poll only, letting the other calls through while waiting.
+        return nativeRangingStopWithTimeout(sessionId, timeoutMs, chipId);
+    }
+
+    /**
+     * Enables or disables the logging of the latency of each UCI command sent to the UWBS.
+     *
+     * @param enabled : true to log the latencies, false otherwise
+     * @param chipId : Identifier of UWB chip for multi-HAL devices
+     * @return : Byte representing the status of the operation
+     */
+    public byte setLatencyLoggingEnabled(boolean enabled, String chipId) {
+        synchronized (mNativeLock) {
+            return nativeSetLatencyLoggingEnabled(enabled, chipId);
+        }
+    }
+
+    /**
+     * Gets the current log mode of the UCI packets.
+     *
+     * @return : Log mode, or null if failed.
+     */
+    public String getLogMode() {
+        synchronized (mNativeLock) {
+            return nativeGetLogMode();
+        }
+    }
+
+    /**
+     * Sets the app configurations of several sessions at once. A failing session does not prevent
+     * the configuration of the next ones.
+     *
+     * @param sessionIds : Session IDs of the UWB sessions
+     * @param noOfParams : Number of TLVs of each session
+     * @param appConfigParams : TLVs of each session
+     * @param chipId : Identifier of UWB chip for multi-HAL devices
+     * @return : Config status of each session, or null if failed.
+     */
+    public UwbConfigStatusData[] setAppConfigurationsBatch(int[] sessionIds, int[] noOfParams,
+            byte[][] appConfigParams, String chipId) {
+        synchronized (mNativeLock) {
+            return nativeSetAppConfigurationsBatch(sessionIds, noOfParams, appConfigParams,
+                    chipId);
+        }
+    }
+
+    /**
+     * Gets the last two way ranging measurement of a controlee of the session.
+     *
+     * @param sessionId : Session ID of the UWB session
+     * @param address : MAC address of the controlee
+     * @param chipId : Identifier of UWB chip for multi-HAL devices
+     * @return : Last measurement of the controlee, or null if none or if failed.
+     */
+    public UwbTwoWayMeasurement getControleeRangingResult(int sessionId, byte[] address,
+            String chipId) {
+        synchronized (mNativeLock) {
+            return nativeGetControleeRangingResult(sessionId, address, chipId);
+        }
+    }
+
+    /**
+     * Gets the states of all the sessions. A session whose state can not be queried is left out.
+     *
+     * @param chipId : Identifier of UWB chip for multi-HAL devices
+     * @return : Array of the session IDs, in increasing order, followed by the array of their
+     *           states, or null if failed.
+     */
+    public int[][] getAllSessionStates(String chipId) {
+        synchronized (mNativeLock) {
+            return nativeGetAllSessionStates(chipId);
+        }
+    }
+
+    /**
+     * Sets the country code of the UWB device from its ISO 3166-1 alpha-2 string.
+     *
+     * @param countryCode : Two uppercase letters, e.g. "US", or "00" for the world mode
+     * @param chipId : Identifier of UWB chip for multi-HAL devices
+     * @return : Byte representing the status of the operation
+     */
+    public byte setCountryCodeStr(String countryCode, String chipId) {
+        synchronized (mNativeLock) {
+            return nativeSetCountryCodeStr(countryCode, chipId);
+        }
+    }
+
+    /**
+     * Sets the number of ranging measurements of each controlee of the session averaged to smooth
+     * the reported AoA.
+     *
+     * @param sessionId : Session ID of the UWB session
+     * @param window : Number of averaged measurements, 1 to disable the filter
+     * @param chipId : Identifier of UWB chip for multi-HAL devices
+     * @return : Byte representing the status of the operation
+     */
+    public byte setAoaFilterWindow(int sessionId, int window, String chipId) {
+        synchronized (mNativeLock) {
+            return nativeSetAoaFilterWindow(sessionId, window, chipId);
+        }
+    }
+
+    /**
+     * Sets how the ranging measurements of the session recover after a non-line-of-sight
+     * detection.
+     *
+     * @param sessionId : Session ID of the UWB session
+     * @param mode : 0 to report the NLOS measurements as is, 1 to hold the last measurement of the
+     *             controlee in line of sight until the line of sight is recovered
+     * @param chipId : Identifier of UWB chip for multi-HAL devices
+     * @return : Byte representing the status of the operation
+     */
+    public byte setNlosRecoveryMode(int sessionId, int mode, String chipId) {
+        synchronized (mNativeLock) {
+            return nativeSetNlosRecoveryMode(sessionId, mode, chipId);
+        }
+    }
+
+    /**
+     * Gets how the ranging measurements of the session recover after a non-line-of-sight
+     * detection.
+     *
+     * @param sessionId : Session ID of the UWB session
+     * @param chipId : Identifier of UWB chip for multi-HAL devices
+     * @return : NLOS recovery mode, or -1 if failed.
+     */
+    public int getNlosRecoveryMode(int sessionId, String chipId) {
+        synchronized (mNativeLock) {
+            return nativeGetNlosRecoveryMode(sessionId, chipId);
+        }
+    }
+
+    /**
+     * Sets the precision of the distances reported for the session, which are rounded to the
+     * nearest multiple of it.
+     *
+     * @param sessionId : Session ID of the UWB session
+     * @param precision : Precision in cm, 1 to report the distances as measured
+     * @param chipId : Identifier of UWB chip for multi-HAL devices
+     * @return : Byte representing the status of the operation
+     */
+    public byte setTofPrecision(int sessionId, int precision, String chipId) {
+        synchronized (mNativeLock) {
+            return nativeSetTofPrecision(sessionId, precision, chipId);
+        }
+    }
+
+    /**
+     * Gets the precision of the distances reported for the session.
+     *
+     * @param sessionId : Session ID of the UWB session
+     * @param chipId : Identifier of UWB chip for multi-HAL devices
+     * @return : Precision in cm, or -1 if failed.
+     */
+    public int getTofPrecision(int sessionId, String chipId) {
+        synchronized (mNativeLock) {
+            return nativeGetTofPrecision(sessionId, chipId);
+        }
+    }
+
+    /**
+     * Sets the interval between two one way ranging rounds of an OWR for AoA session, as its
+     * ranging duration.
+     *
+     * @param sessionId : Session ID of the UWB session
+     * @param intervalMs : Interval in ms, at least 1
+     * @param chipId : Identifier of UWB chip for multi-HAL devices
+     * @return : Byte representing the status of the operation
+     */
+    public byte setOwrAoaRangingInterval(int sessionId, int intervalMs, String chipId) {
+        synchronized (mNativeLock) {
+            return nativeSetOwrAoaRangingInterval(sessionId, intervalMs, chipId);
+        }
+    }
+
+    /**
+     * Gets the interval between two one way ranging rounds of an OWR for AoA session.
+     *
+     * @param sessionId : Session ID of the UWB session
+     * @param chipId : Identifier of UWB chip for multi-HAL devices
+     * @return : Interval in ms, or -1 if failed.
+     */
+    public int getOwrAoaRangingInterval(int sessionId, String chipId) {
+        synchronized (mNativeLock) {
+            return nativeGetOwrAoaRangingInterval(sessionId, chipId);
+        }
+    }
+
+    /**
+     * Gets the max size of the in-band data payload carried by a single packet of the session, its
+     * MTU size.
+     *
+     * @param sessionId : Session ID of the UWB session
+     * @param chipId : Identifier of UWB chip for multi-HAL devices
+     * @return : Max payload size in bytes, or -1 if unknown or if failed.
+     */
+    public int getMaxInbandPayload(int sessionId, String chipId) {
+        synchronized (mNativeLock) {
+            return nativeGetMaxInbandPayload(sessionId, chipId);
+        }
+    }
+
+    /**
+     * Sets the AoA bounds of the session, outside of which the range data is not notified when
+     * enabled by the range data notification config.
+     *
+     * @param sessionId : Session ID of the UWB session
+     * @param azimuthMin : Min azimuth, in degrees as a signed Q9.7 value
+     * @param azimuthMax : Max azimuth, in degrees as a signed Q9.7 value
+     * @param elevationMin : Min elevation, in degrees as a signed Q9.7 value
+     * @param elevationMax : Max elevation, in degrees as a signed Q9.7 value
+     * @param chipId : Identifier of UWB chip for multi-HAL devices
+     * @return : Byte representing the status of the operation
+     */
+    public byte setAoaBound(int sessionId, int azimuthMin, int azimuthMax, int elevationMin,
+            int elevationMax, String chipId) {
+        synchronized (mNativeLock) {
+            return nativeSetAoaBound(sessionId, azimuthMin, azimuthMax, elevationMin,
+                    elevationMax, chipId);
+        }
+    }
+
+    /**
+     * Gets the AoA bounds of the session.
+     *
+     * @param sessionId : Session ID of the UWB session
+     * @param chipId : Identifier of UWB chip for multi-HAL devices
+     * @return : Azimuth min and max followed by the elevation min and max, in degrees as signed
+     *           Q9.7 values, or null if failed.
+     */
+    public int[] getAoaBound(int sessionId, String chipId) {
+        synchronized (mNativeLock) {
+            return nativeGetAoaBound(sessionId, chipId);
+        }
+    }
+
+    /**
+     * Queries the max application data size of each of the sessions. The size of a session whose
+     * query failed is 0.
+     *
+     * @param chipId : Identifier of UWB chip for multi-HAL devices
+     * @return : Array of the session IDs, in increasing order, followed by the array of their
+     *           sizes, or null if failed.
+     */
+    public int[][] queryDataSizeAll(String chipId) {
+        synchronized (mNativeLock) {
+            return nativeQueryDataSizeAll(chipId);
+        }
+    }
+
+    /**
+     * Gets the ranging round usages, e.g. SS-TWR, DS-TWR or OWR for AoA, supported by the UWBS.
+     *
+     * @param chipId : Identifier of UWB chip for multi-HAL devices
+     * @return : Supported ranging round usages, empty if not advertised, or null if failed.
+     */
+    public int[] getSupportedRoundUsageModes(String chipId) {
+        synchronized (mNativeLock) {
+            return nativeGetSupportedRoundUsageModes(chipId);
+        }
+    }
+
+    /**
+     * Sends the application payload in fragments of the max data size of the session, with
+     * consecutive UCI sequence numbers. The transfer stops at the first fragment which fails to be
+     * sent.
+     *
+     * @param sessionId : Session ID of the UWB session
+     * @param address : Address of the remote device
+     * @param uciSequenceNumber : UCI sequence number of the first fragment
+     * @param appPayloadData : Application payload to send
+     * @param chipId : Identifier of UWB chip for multi-HAL devices
+     * @return : Number of fragments sent if the whole payload was sent, or -(1 + number of
+     *         fragments sent) if failed, so -1 if failed before sending any fragment.
+     */
+    public int sendDataFragmented(int sessionId, byte[] address, short uciSequenceNumber,
+            byte[] appPayloadData, String chipId) {
+        synchronized (mNativeLock) {
+            return nativeSendDataFragmented(sessionId, address, uciSequenceNumber, appPayloadData,
+                    chipId);
+        }
+    }
+
+    /**
+     * Sets the MAC address mode of the session.
+     *
+     * @param sessionId : Session ID of the UWB session
+     * @param mode : 0 for short addresses, 1 for extended addresses with a short MAC header and 2
+     *               for extended addresses
+     * @param chipId : Identifier of UWB chip for multi-HAL devices
+     * @return : Byte representing the status of the operation
+     */
+    public byte setMacAddressMode(int sessionId, int mode, String chipId) {
+        synchronized (mNativeLock) {
+            return nativeSetMacAddressMode(sessionId, mode, chipId);
+        }
+    }
+
+    /**
+     * Gets the MAC address mode of the session.
+     *
+     * @param sessionId : Session ID of the UWB session
+     * @param chipId : Identifier of UWB chip for multi-HAL devices
+     * @return : MAC address mode of the session, or -1 if failed.
+     */
+    public int getMacAddressMode(int sessionId, String chipId) {
+        synchronized (mNativeLock) {
+            return nativeGetMacAddressMode(sessionId, chipId);
+        }
+    }
+
+    /**
+     * Gets the role of the local device in the session, from its device type.
+     *
+     * @param sessionId : Session ID of the UWB session
+     * @param chipId : Identifier of UWB chip for multi-HAL devices
+     * @return : 0 for a controlee, 1 for a controller, or -1 if not set or if failed.
+     */
+    public int getSessionRole(int sessionId, String chipId) {
+        synchronized (mNativeLock) {
+            return nativeGetSessionRole(sessionId, chipId);
+        }
+    }
+
+    /**
+     * Initializes the session and gets the session token allocated to it by the UWBS.
+     *
+     * @param sessionId : Session ID of the UWB session
+     * @param sessionType : Type of the UWB session
+     * @param chipId : Identifier of UWB chip for multi-HAL devices
+     * @return : Session token of the session, or -1 if failed.
+     */
+    public long initSessionAndGetToken(int sessionId, byte sessionType, String chipId) {
+        synchronized (mNativeLock) {
+            return nativeSessionInitAndGetToken(sessionId, sessionType, chipId);
+        }
+    }
+
+    /**
+     * Sets whether the UWBS sends a data transfer status notification for each data message of the
+     * session.
+     *
+     * @param sessionId : Session ID of the UWB session
+     * @param enable : Whether the notifications are enabled
+     * @param chipId : Identifier of UWB chip for multi-HAL devices
+     * @return : Byte representing the status of the operation
+     */
+    public byte setDataTransferStatusNtf(int sessionId, boolean enable, String chipId) {
+        synchronized (mNativeLock) {
+            return nativeSetDataTransferStatusNtf(sessionId, enable, chipId);
+        }
+    }
+
+    /**
+     * Gets whether the UWBS sends a data transfer status notification for each data message of the
+     * session.
+     *
+     * @param sessionId : Session ID of the UWB session
+     * @param chipId : Identifier of UWB chip for multi-HAL devices
+     * @return : 1 if enabled, 0 if disabled, or -1 if failed.
+     */
+    public int getDataTransferStatusNtf(int sessionId, String chipId) {
+        synchronized (mNativeLock) {
+            return nativeGetDataTransferStatusNtf(sessionId, chipId);
+        }
+    }
+
+    /**
+     * Sets the policy of the retry of the UCI commands answered with UCI_STATUS_COMMAND_RETRY. The
+     * policy only applies to the CORE_DEVICE_RESET, CORE_GET_CAPS_INFO, SESSION_INIT,
+     * SESSION_DEINIT, SESSION_SET_APP_CONFIG, SESSION_GET_APP_CONFIG, SESSION_START and
+     * SESSION_STOP commands.
+     *
+     * @param maxAttempts : Number of times a command is sent at most, 1 to disable the retry
+     * @param backoffMs : Delay in milliseconds before each resend
+     * @param chipId : Identifier of UWB chip for multi-HAL devices
+     * @return : Byte representing the status of the operation
+     */
+    public byte setCommandRetryPolicy(int maxAttempts, int backoffMs, String chipId) {
+        synchronized (mNativeLock) {
+            return nativeSetCommandRetryPolicy(maxAttempts, backoffMs, chipId);
+        }
+    }
+
+    /**
+     * Restricts the raw vendor commands sent to the UWBS of all chips to the allowlist.
+     *
+     * @param gidOidPairs : (GID, OID) pairs of the allowed vendor commands, flattened, or null to
+     *                      allow all the commands
+     * @return : Byte representing the status of the operation
+     */
+    public byte setVendorCmdAllowlist(int[] gidOidPairs) {
+        synchronized (mNativeLock) {
+            return nativeSetVendorCmdAllowlist(gidOidPairs);
+        }
+    }
+
+    /**
+     * Retrieves power related stats, with 64-bit counters which do not overflow on long running
+     * devices.
+     *
+     * @param chipId : Identifier of UWB chip for multi-HAL devices
+     * @return : Power stats, or null if failed.
+     */
+    public UwbPowerStatsLong getPowerStatsLong(String chipId) {
+        synchronized (mNativeLock) {
+            return nativeGetPowerStatsLong(chipId);
+        }
+    }
+
+    /**
+     * Gets the most recent generic errors notified by the UWBS, from the oldest to the most recent
+     * one. Each error is encoded as the 1 byte status and the 8 bytes timestamp in ms, in little
+     * endian.
+     *
+     * @param chipId : Identifier of UWB chip for multi-HAL devices
+     * @return : Encoded errors, or null if failed.
+     */
+    public byte[] getErrorHistory(String chipId) {
+        synchronized (mNativeLock) {
+            return nativeGetErrorHistory(chipId);
+        }
+    }
+
+    /**
+     * Validates app configurations without sending them to the UWBS: the TLVs are parsed, and the
+     * size of the value of each FiRa app configuration is checked.
+     *
+     * @param noOfParams : Number of the app configurations
+     * @param appConfigParams : TLVs of the app configurations
+     * @return : Byte representing the status of the validation
+     */
+    public byte validateAppConfig(int noOfParams, byte[] appConfigParams) {
+        synchronized (mNativeLock) {
+            return nativeValidateAppConfig(noOfParams, appConfigParams);
+        }
+    }
+
+    /**
+     * Checks if the UWBS supports a sub-session key for each controlee, to be sent in the multicast
+     * list updates.
+     *
+     * @param chipId : Identifier of UWB chip for multi-HAL devices
+     * @return : true if supported, false if unknown or if failed.
+     */
+    public boolean isSubSessionKeySupported(String chipId) {
+        synchronized (mNativeLock) {
+            return nativeIsSubSessionKeySupported(chipId);
+        }
+    }
+
+    /**
+     * Gets the raw packets of the most recent UCI responses received from the UWBS, whichever
+     * command produced them, from the oldest to the most recent one. The responses are captured
+     * from the UCI log: none are captured while the log mode is Disabled, and they are captured as
+     * logged, possibly redacted, while it is Filtered.
+     *
+     * @param count : Max number of the responses returned
+     * @param chipId : Identifier of UWB chip for multi-HAL devices
+     * @return : Raw responses, or null if failed.
+     */
+    public byte[][] getRecentRawResponses(int count, String chipId) {
+        synchronized (mNativeLock) {
+            return nativeGetRecentRawResponses(count, chipId);
+        }
+    }
+
+    /**
+     * Sets the number of raw UCI responses kept for getRecentRawResponses, 16 by default. The
+     * oldest responses beyond it are dropped.
+     *
+     * @param capacity : Number of the responses kept, 0 to stop the capture
+     * @param chipId : Identifier of UWB chip for multi-HAL devices
+     * @return : Byte representing the status of the operation
+     */
+    public byte setRawResponseCapacity(int capacity, String chipId) {
+        synchronized (mNativeLock) {
+            return nativeSetRawResponseCapacity(capacity, chipId);
+        }
+    }
+
+    /**
+     * Sets the country code on all the UWB chips, carrying on with the next chips when one fails,
+     * so that the regulatory update reaches as many chips as possible.
+     *
+     * @param countryCode : ISO/IEC 3166-1 alpha-2 country code
+     * @return : Ids of the chips which failed, empty if all succeeded, or null if the country code
+     *           is invalid or if failed.
+     */
+    public String[] setCountryCodeAllChips(byte[] countryCode) {
+        synchronized (mNativeLock) {
+            return nativeSetCountryCodeAllChips(countryCode);
+        }
+    }
+
+    /**
+     * Gets the state of the HAL channel of the UWB chip.
+     *
+     * @param chipId : Identifier of UWB chip for multi-HAL devices
+     * @return : 0 for Closed, 1 for Opening, 2 for Open and 3 for Error, or -1 if failed.
+     */
+    public int getHalState(String chipId) {
+        synchronized (mNativeLock) {
+            return nativeGetHalState(chipId);
+        }
+    }
+
+    /**
+     * Deinitializes all the sessions of the UWB chip in a single sweep, carrying on with the next
+     * sessions when one fails.
+     *
+     * @param chipId : Identifier of UWB chip for multi-HAL devices
+     * @return : Array of the ids of the sessions, in increasing order, followed by an array of the
+     *           status of their deinitialization, or null if failed.
+     */
+    public int[][] deInitAllSessions(String chipId) {
+        synchronized (mNativeLock) {
+            return nativeSessionDeInitAll(chipId);
+        }
+    }
+
+    /**
+     * Gets the max number of sessions doing both ranging and data transfers the UWBS runs at the
+     * same time. Any ranging session may transfer data, so this is the max number of ranging
+     * sessions.
+     *
+     * @param chipId : Identifier of UWB chip for multi-HAL devices
+     * @return : Max number of sessions, or -1 if not advertised or if failed.
+     */
+    public int getMaxRangingDataSessions(String chipId) {
+        synchronized (mNativeLock) {
+            return nativeGetMaxRangingDataSessions(chipId);
+        }
+    }
+
+    /**
+     * Queries the UWBS timestamp along with its resolution
+     *
+     * @param chipId : Identifier of UWB chip for multi-HAL devices
+     * @return : UwbTimestamp holding the timestamp, its unit and its resolution in nanoseconds,
+     *           null if failed
+     */
+    public UwbTimestamp queryUwbTimestampWithResolution(String chipId) {
+        synchronized (mNativeLock) {
+            return nativeQueryUwbTimestampWithResolution(chipId);
+        }
+    }
+
+    /**
+     * Gets the capabilities of the UWB subsystem, decoded as UwbDeviceCapabilities
+     *
+     * @param chipId : Identifier of UWB chip for multi-HAL devices
+     * @return : UwbDeviceCapabilities, null if failed
+     */
+    public UwbDeviceCapabilities getCapabilitiesStructured(String chipId) {
+        synchronized (mNativeLock) {
+            return nativeGetCapabilitiesStructured(chipId);
+        }
+    }
+
+    /**
+     * Sets the priority of the UWB session, used to schedule the sessions running concurrently
+     *
+     * @param sessionId : Session ID of the UWB session
+     * @param priority : Priority of the session, from 1 to 100
+     * @param chipId : Identifier of UWB chip for multi-HAL devices
+     * @return : Byte representing the status of the operation
+     */
+    public byte setSessionPriority(int sessionId, int priority, String chipId) {
+        synchronized (mNativeLock) {
+            return nativeSessionSetPriority(sessionId, priority, chipId);
+        }
+    }
+
+    /**
+     * Gets the number of ranging measurements per second of the UWB session over the last 5 seconds
+     *
+     * @param sessionId : Session ID of the UWB session
+     * @param chipId : Identifier of UWB chip for multi-HAL devices
+     * @return : Measurements per second, 0 without recent measurement or if failed
+     */
+    public float getMeasurementRate(int sessionId, String chipId) {
+        synchronized (mNativeLock) {
+            return nativeGetMeasurementRate(sessionId, chipId);
+        }
+    }
+
+    /**
+     * Sets the MAC mode of the UWB session, as its ranging round usage
+     *
+     * @param sessionId : Session ID of the UWB session
+     * @param mode : 0 for two way ranging, 1 for one way ranging
+     * @param chipId : Identifier of UWB chip for multi-HAL devices
+     * @return : Byte representing the status of the operation
+     */
+    public byte setMacMode(int sessionId, int mode, String chipId) {
+        synchronized (mNativeLock) {
+            return nativeSetMacMode(sessionId, mode, chipId);
+        }
+    }
+
+    /**
+     * Gets the MAC mode of the UWB session, decoded from its ranging round usage
+     *
+     * @param sessionId : Session ID of the UWB session
+     * @param chipId : Identifier of UWB chip for multi-HAL devices
+     * @return : 0 for two way ranging, 1 for one way ranging, -1 if unknown or if failed
+     */
+    public int getMacMode(int sessionId, String chipId) {
+        synchronized (mNativeLock) {
+            return nativeGetMacMode(sessionId, chipId);
+        }
+    }
+
+    private native byte nativeSendData(int sessionId, byte[] address,
+            short sequenceNum, byte[] appData, String chipId);
+
+    private native byte nativeSessionDataTransferPhaseConfig(int sessionId, byte dtpcmRepetition,
+            byte dataTransferControl, byte dtpmlSize, byte[] macAddress, byte[] slotBitmap,
+            String chipId);
+
+    private native long nativeDispatcherNew(Object[] chipIds);
+
+    private native void nativeDispatcherDestroy();
+
+    private native boolean nativeInit();
+
+    private native UwbDeviceInfoResponse nativeDoInitialize(String chipIds);
+
+    private native boolean nativeDoDeinitialize(String chipId);
+
+    private native long nativeGetTimestampResolutionNanos();
+
+    private native UwbPowerStats nativeGetPowerStats(String chipId);
+
+    private native byte nativeDeviceReset(byte resetConfig, String chipId);
+
+    private native byte nativeSessionInit(int sessionId, byte sessionType, String chipId);
+
+    private native byte nativeSessionDeInit(int sessionId, String chipId);
+
+    private native byte nativeGetSessionCount(String chipId);
+
+    private native byte nativeRangingStart(int sessionId, String chipId);
+
+    private native byte nativeRangingStop(int sessionId, String chipId);
+
+    private native byte nativeGetSessionState(int sessionId, String chipId);
+
+    private native UwbConfigStatusData nativeSetAppConfigurations(int sessionId, int noOfParams,
+            int appConfigParamLen, byte[] appConfigParams, String chipId);
+
+    private native UwbTlvData nativeGetAppConfigurations(int sessionId, int noOfParams,
+            int appConfigParamLen, byte[] appConfigParams, String chipId);
+
+    private native UwbConfigStatusData nativeSetRadarAppConfigurations(int sessionId,
+            int noOfParams, int appConfigParamLen, byte[] appConfigParams, String chipId);
+
+    private native UwbTlvData nativeGetCapsInfo(String chipId);
+
+    private native byte nativeControllerMulticastListUpdate(int sessionId, byte action,
+            byte noOfControlee, byte[] address, int[] subSessionId, byte[] subSessionKeyList,
+            String chipId);
+
+    private native byte nativeSetCountryCode(byte[] countryCode, String chipId);
+
+    private native boolean nativeSetLogMode(String logMode);
+
+    private native UwbVendorUciResponse nativeSendRawVendorCmd(int mt, int gid, int oid,
+            byte[] payload, String chipId);
+
+    private native DtTagUpdateRangingRoundsStatus nativeSessionUpdateDtTagRangingRounds(
+            int sessionId, int noOfActiveRangingRounds, byte[] rangingRoundIndexes, String chipId);
+
+    private native short nativeQueryDataSize(int sessionId, String chipId);
+
+    private native long nativeQueryUwbTimestamp(String chipId);
+
+    private native int nativeGetSessionToken(int sessionId, String chipId);
+
+    private native byte nativeSetHybridSessionConfigurations(int sessionId, int noOfPhases,
+            byte[] updateTime, byte[] phaseList, String chipId);
+
+    private native byte nativeSetTxPerRound(int sessionId, int count, String chipId);
+
+    private native int nativeGetTxPerRound(int sessionId, String chipId);
+
+    private native byte nativeSetSlotDuration(int sessionId, int slotDurationRstu, String chipId);
+
+    private native int nativeGetSlotDuration(int sessionId, String chipId);
+
+    private native int nativeGetBlockTimingBase(int sessionId, String chipId);
+
+    private native boolean nativeIsProvisionedStsSupported(String chipId);
+
+    private native byte nativeSetRangingTimeOffset(int sessionId, int offset, String chipId);
+
+    private native int nativeGetRangingTimeOffset(int sessionId, String chipId);
+
+    private native long nativeGetCompletedRoundCount(int sessionId, String chipId);
+
+    private native byte nativeSetSlotsPerRound(int sessionId, int slots, String chipId);
+
+    private native int nativeGetSlotsPerRound(int sessionId, String chipId);
+
+    private native byte nativeSetSubSessionId(int sessionId, int subSessionId, String chipId);
+
+    private native long nativeGetSubSessionId(int sessionId, String chipId);
+
+    private native int[] nativeGetSupportedFiraPhyVersions(String chipId);
+
+    private native byte nativeSetResultReportPhase(int sessionId, boolean enable, String chipId);
+
+    private native int nativeGetResultReportPhase(int sessionId, String chipId);
+
+    private native byte nativeSetMinFrameReportInterval(int sessionId, int intervalMs,
+            String chipId);
+
+    private native int nativeGetMinFrameReportInterval(int sessionId, String chipId);
+
+    private native boolean nativeIsConcurrentRangingRadarSupported(String chipId);
+
+    private native byte[] nativeGetRecentCommands(String chipId);
+
+    private native byte nativeSetRframeConfig(int sessionId, int config, String chipId);
+
+    private native int nativeGetRframeConfig(int sessionId, String chipId);
+
+    private native long[] nativeGetDataThroughputStats(int sessionId, String chipId);
+
+    private native byte nativeResetDataThroughputStats(int sessionId, String chipId);
+
+    private native byte nativeSetPreambleDuration(int sessionId, int durationSymbols,
+            String chipId);
+
+    private native int nativeGetPreambleDuration(int sessionId, String chipId);
+
+    private native int nativeGetEffectiveBlockDuration(int sessionId, String chipId);
+
+    private native byte nativeSetPsduDataRate(int sessionId, int rate, String chipId);
+
+    private native int nativeGetPsduDataRate(int sessionId, String chipId);
+
+    private native int[] nativeGetSupportedBandwidths(String chipId);
+
+    private native byte nativeSetHoppingSequence(int sessionId, int sequence, String chipId);
+
+    private native int nativeGetHoppingSequence(int sessionId, String chipId);
+
+    private native byte nativeSetBatchNotificationSize(int sessionId, int count, String chipId);
+
+    private native byte nativeGetRadarSessionState(int sessionId, String chipId);
+
+    private native boolean nativeIsLogModeSupported(String logModeStr);
+
+    private native byte nativeSetStsKeyRefreshRounds(int sessionId, int rounds, String chipId);
+
+    private native int nativeGetStsKeyRefreshRounds(int sessionId, String chipId);
+
+    private native int nativeGetStsMode(int sessionId, String chipId);
+
+    private native byte nativeSetRangingErrorPolicy(int sessionId, int policy, String chipId);
+
+    private native UwbDeviceInfoResponse nativeGetCachedDeviceInfo(String chipId);
+
+    private native byte nativeSetBlockDurationMultiplier(int sessionId, int multiplier,
+            String chipId);
+
+    private native int nativeGetBlockDurationMultiplier(int sessionId, String chipId);
+
+    private native int nativeIsDataEncrypted(int sessionId, String chipId);
+
+    private native UwbTlvData nativeGetRadarAppConfigurations(int sessionId,
+            byte[] radarConfigParams, String chipId);
+
+    private native byte nativeSetAppConfigMaxZeroPadding(int maxZeroPadding, String chipId);
+
+    private native byte nativeSetDataRetransmissionCount(int sessionId, int count, String chipId);
+
+    private native int nativeGetDataRetransmissionCount(int sessionId, String chipId);
+
+    private native int[] nativeGetSupportedStsKeyLengths(String chipId);
+
+    private native byte nativeRangingStartWithTimeout(int sessionId, int timeoutMs, String chipId);
+
+    private native byte nativeRangingStopWithTimeout(int sessionId, int timeoutMs, String chipId);
+
+    private native byte nativeSetLatencyLoggingEnabled(boolean enabled, String chipId);
+
+    private native String nativeGetLogMode();
+
+    private native UwbConfigStatusData[] nativeSetAppConfigurationsBatch(int[] sessionIds,
+            int[] noOfParams, byte[][] appConfigParams, String chipId);
+
+    private native UwbTwoWayMeasurement nativeGetControleeRangingResult(int sessionId,
+            byte[] address, String chipId);
+
+    private native int[][] nativeGetAllSessionStates(String chipId);
+
+    private native byte nativeSetCountryCodeStr(String countryCode, String chipId);
+
+    private native byte nativeSetAoaFilterWindow(int sessionId, int window, String chipId);
+
+    private native byte nativeSetNlosRecoveryMode(int sessionId, int mode, String chipId);
+
+    private native int nativeGetNlosRecoveryMode(int sessionId, String chipId);
+
+    private native byte nativeSetTofPrecision(int sessionId, int precision, String chipId);
+
+    private native int nativeGetTofPrecision(int sessionId, String chipId);
+
+    private native byte nativeSetOwrAoaRangingInterval(int sessionId, int intervalMs,
+            String chipId);
+
+    private native int nativeGetOwrAoaRangingInterval(int sessionId, String chipId);
+
+    private native int nativeGetMaxInbandPayload(int sessionId, String chipId);
+
+    private native byte nativeSetAoaBound(int sessionId, int azimuthMin, int azimuthMax,
+            int elevationMin, int elevationMax, String chipId);
+
+    private native int[] nativeGetAoaBound(int sessionId, String chipId);
+
+    private native int[][] nativeQueryDataSizeAll(String chipId);
+
+    private native int[] nativeGetSupportedRoundUsageModes(String chipId);
+
+    private native int nativeSendDataFragmented(int sessionId, byte[] address,
+            short uciSequenceNumber, byte[] appPayloadData, String chipId);
+
+    private native byte nativeSetMacAddressMode(int sessionId, int mode, String chipId);
+
+    private native int nativeGetMacAddressMode(int sessionId, String chipId);
+
+    private native int nativeGetSessionRole(int sessionId, String chipId);
+
+    private native long nativeSessionInitAndGetToken(int sessionId, byte sessionType,
+            String chipId);
+
+    private native byte nativeSetDataTransferStatusNtf(int sessionId, boolean enable,
+            String chipId);
+
+    private native int nativeGetDataTransferStatusNtf(int sessionId, String chipId);
+
+    private native byte nativeSetCommandRetryPolicy(int maxAttempts, int backoffMs, String chipId);
+
+    private native byte nativeSetVendorCmdAllowlist(int[] gidOidPairs);
+
+    private native UwbPowerStatsLong nativeGetPowerStatsLong(String chipId);
+
+    private native byte[] nativeGetErrorHistory(String chipId);
+
+    private native byte nativeValidateAppConfig(int noOfParams, byte[] appConfigParams);
+
+    private native boolean nativeIsSubSessionKeySupported(String chipId);
+
+    private native byte[][] nativeGetRecentRawResponses(int count, String chipId);
+
+    private native byte nativeSetRawResponseCapacity(int capacity, String chipId);
+
+    private native String[] nativeSetCountryCodeAllChips(byte[] countryCode);
+
+    private native int nativeGetHalState(String chipId);
+
+    private native int[][] nativeSessionDeInitAll(String chipId);
+
+    private native int nativeGetMaxRangingDataSessions(String chipId);
+
+    private native UwbTimestamp nativeQueryUwbTimestampWithResolution(String chipId);
+
+    private native byte nativeAddControlees(int sessionId, byte noOfControlee, byte[] addresses,
+            int[] subSessionIds, String chipId);
+
+    private native byte nativeRemoveControlees(int sessionId, byte noOfControlee,
+            byte[] addresses, int[] subSessionIds, String chipId);
+
+    private native UwbDeviceCapabilities nativeGetCapabilitiesStructured(String chipId);
+
+    private native byte nativeSessionSetPriority(int sessionId, int priority, String chipId);
+
+    private native float nativeGetMeasurementRate(int sessionId, String chipId);
+
+    private native byte nativeSetMacMode(int sessionId, int mode, String chipId);
+
+    private native int nativeGetMacMode(int sessionId, String chipId);
 }
@@ -0,0 +1,157 @@
+// Copyright 2026, The Android Open Source Project
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Moving average filter of the AoA reported in the two way ranging measurements.
+
+use std::collections::{HashMap, VecDeque};
+
+use crate::notification_manager_android::MacAddress;
+
+use uwb_core::error::{Error, Result};
+use uwb_core::uci::RangingMeasurements;
+use uwb_uci_packets::StatusCode;
+
+/// Filters the AoA azimuth and elevation of the two way ranging measurements of a session with a
+/// moving average over the last `window` successful measurements of each controlee.
+pub(crate) struct AoaFilter {
+    window: usize,
+    // Last (azimuth, elevation) of each controlee, oldest first.
+    history: HashMap<MacAddress, VecDeque<(i16, i16)>>,
+}
+
+impl AoaFilter {
+    /// Constructs the filter. A window of 1 leaves the AoA unchanged.
+    pub fn new(window: usize) -> Result<Self> {
+        if window == 0 {
+            return Err(Error::BadParameters);
+        }
+        Ok(Self { window, history: HashMap::new() })
+    }
+
+    /// Replaces the AoA of the successful two way ranging measurements by their moving average.
+    /// The other types of measurements are left unchanged.
+    pub fn filter(&mut self, measurements: &mut RangingMeasurements) {
+        match measurements {
+            RangingMeasurements::ShortAddressTwoWay(v) => {
+                for m in v.iter_mut().filter(|m| m.status == StatusCode::UciStatusOk) {
+                    (m.aoa_azimuth, m.aoa_elevation) = self.smooth(
+                        MacAddress::Short(m.mac_address),
+                        m.aoa_azimuth,
+                        m.aoa_elevation,
+                    );
+                }
+            }
+            RangingMeasurements::ExtendedAddressTwoWay(v) => {
+                for m in v.iter_mut().filter(|m| m.status == StatusCode::UciStatusOk) {
+                    (m.aoa_azimuth, m.aoa_elevation) = self.smooth(
+                        MacAddress::Extended(m.mac_address),
+                        m.aoa_azimuth,
+                        m.aoa_elevation,
+                    );
+                }
+            }
+            _ => {}
+        }
+    }
+
+    // The AoA are signed Q9.7 values, so they are averaged as i16.
+    fn smooth(&mut self, mac_address: MacAddress, azimuth: u16, elevation: u16) -> (u16, u16) {
+        let history = self.history.entry(mac_address).or_default();
+        if history.len() == self.window {
+            history.pop_front();
+        }
+        history.push_back((azimuth as i16, elevation as i16));
+
+        let count = history.len() as i32;
+        let (azimuth_sum, elevation_sum) =
+            history.iter().fold((0i32, 0i32), |(a, e), (azimuth, elevation)| {
+                (a + *azimuth as i32, e + *elevation as i32)
+            });
+        ((azimuth_sum / count) as i16 as u16, (elevation_sum / count) as i16 as u16)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use uwb_uci_packets::ShortAddressTwoWayRangingMeasurement;
+
+    fn measurements(mac_address: u16, aoa: &[(i16, i16)]) -> RangingMeasurements {
+        RangingMeasurements::ShortAddressTwoWay(
+            aoa.iter()
+                .map(|(azimuth, elevation)| ShortAddressTwoWayRangingMeasurement {
+                    mac_address,
+                    status: StatusCode::UciStatusOk,
+                    nlos: 0,
+                    distance: 100,
+                    aoa_azimuth: *azimuth as u16,
+                    aoa_azimuth_fom: 0,
+                    aoa_elevation: *elevation as u16,
+                    aoa_elevation_fom: 0,
+                    aoa_destination_azimuth: 0,
+                    aoa_destination_azimuth_fom: 0,
+                    aoa_destination_elevation: 0,
+                    aoa_destination_elevation_fom: 0,
+                    slot_index: 0,
+                    rssi: 0,
+                })
+                .collect(),
+        )
+    }
+
+    // Feeds each AoA to the filter in its own notification and returns the filtered AoA.
+    fn filter_sequence(filter: &mut AoaFilter, aoa: &[(i16, i16)]) -> Vec<(i16, i16)> {
+        aoa.iter()
+            .map(|aoa| {
+                let mut measurements = measurements(1, &[*aoa]);
+                filter.filter(&mut measurements);
+                match measurements {
+                    RangingMeasurements::ShortAddressTwoWay(v) => {
+                        (v[0].aoa_azimuth as i16, v[0].aoa_elevation as i16)
+                    }
+                    _ => panic!("unexpected measurement type"),
+                }
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_aoa_filter_window() {
+        assert!(AoaFilter::new(0).is_err());
+
+        let mut filter = AoaFilter::new(3).unwrap();
+        assert_eq!(
+            filter_sequence(&mut filter, &[(30, -9), (60, -3), (90, 0), (-30, 3), (0, 6)]),
+            vec![(30, -9), (45, -6), (60, -4), (40, 0), (20, 3)]
+        );
+    }
+
+    #[test]
+    fn test_aoa_filter_passthrough() {
+        let mut filter = AoaFilter::new(1).unwrap();
+        let aoa = [(30, -9), (-1000, 3), (i16::MAX, i16::MIN)];
+        assert_eq!(filter_sequence(&mut filter, &aoa), aoa.to_vec());
+    }
+
+    #[test]
+    fn test_aoa_filter_per_controlee() {
+        let mut filter = AoaFilter::new(2).unwrap();
+        let mut first = measurements(1, &[(10, 10)]);
+        filter.filter(&mut first);
+        let mut second = measurements(2, &[(50, 50)]);
+        filter.filter(&mut second);
+        assert_eq!(second, measurements(2, &[(50, 50)]));
+    }
+}
@@ -0,0 +1,1266 @@
+// Copyright 2026, The Android Open Source Project
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Builders and accessors for individual app configuration TLVs.
+//!
+//! The dedicated JNI setters validate their arguments here before the TLV is sent to the UWBS,
+//! and the matching getters decode the value read back from the UWBS.
+
+use crate::helper::{error_to_status_code, fail_with_status};
+
+use jni::sys::jint;
+use log::{error, warn};
+use uwb_core::error::{Error, Result};
+use uwb_core::params::{AppConfigTlv, SetAppConfigResponse};
+use uwb_core::uci::uci_manager_sync::UciManagerSync;
+use uwb_core::uci::UciManager;
+use uwb_uci_packets::{AppConfigTlvType, SessionState, StatusCode};
+
+/// Sets a single app configuration TLV on the session. Fails if the UWBS rejects it.
+pub(crate) fn session_set_app_config_tlv<U: UciManager>(
+    uci_manager: &UciManagerSync<U>,
+    session_id: u32,
+    tlv: AppConfigTlv,
+) -> Result<()> {
+    session_set_app_config_tlvs(uci_manager, session_id, vec![tlv])
+}
+
+/// Sets related app configuration TLVs on the session in a single command. Fails if the UWBS
+/// rejects any of them, with the status of the response returned to the Java side.
+pub(crate) fn session_set_app_config_tlvs<U: UciManager>(
+    uci_manager: &UciManagerSync<U>,
+    session_id: u32,
+    tlvs: Vec<AppConfigTlv>,
+) -> Result<()> {
+    let response = uci_manager.session_set_app_config(session_id, tlvs)?;
+    if response.status != StatusCode::UciStatusOk {
+        error!(
+            "UCI JNI: set app config rejected with {:?}: {:?}",
+            response.status, response.config_status
+        );
+        return Err(fail_with_status(Error::BadParameters, u8::from(response.status)));
+    }
+    Ok(())
+}
+
+/// Sets the app configurations of several sessions with `set_app_config`, in order, carrying on
+/// with the next sessions when one fails. A session fails if its TLVs could not be parsed, or if
+/// they could not be sent, which is reported as a response carrying the status of the failure.
+pub(crate) fn set_app_configs_batch(
+    configs: Vec<(u32, Result<Vec<AppConfigTlv>>)>,
+    mut set_app_config: impl FnMut(u32, Vec<AppConfigTlv>) -> Result<SetAppConfigResponse>,
+) -> Vec<SetAppConfigResponse> {
+    configs
+        .into_iter()
+        .map(|(session_id, tlvs)| match tlvs.and_then(|tlvs| set_app_config(session_id, tlvs)) {
+            Ok(response) => response,
+            Err(e) => {
+                error!("UCI JNI: set app config of session {} failed with {:?}", session_id, e);
+                SetAppConfigResponse { status: error_to_status_code(&e), config_status: vec![] }
+            }
+        })
+        .collect()
+}
+
+/// Reads back the raw value of a single app configuration of the session.
+pub(crate) fn session_get_app_config_value<U: UciManager>(
+    uci_manager: &UciManagerSync<U>,
+    session_id: u32,
+    cfg_id: AppConfigTlvType,
+) -> Result<Vec<u8>> {
+    uci_manager
+        .session_get_app_config(session_id, vec![cfg_id])?
+        .into_iter()
+        .map(AppConfigTlv::into_inner)
+        .find(|tlv| tlv.cfg_id == cfg_id)
+        .map(|tlv| tlv.v)
+        .ok_or(Error::BadParameters)
+}
+
+/// Reads back the ranging duration, in ms, of the session once started, which the UWBS may have
+/// adjusted from the requested one. Returns None if the session is not active.
+pub(crate) fn session_get_effective_block_duration_ms<U: UciManager>(
+    uci_manager: &UciManagerSync<U>,
+    session_id: u32,
+) -> Result<Option<u32>> {
+    if uci_manager.session_get_state(session_id)? != SessionState::SessionStateActive {
+        return Ok(None);
+    }
+    let value =
+        session_get_app_config_value(uci_manager, session_id, AppConfigTlvType::RangingDuration)?;
+    decode_le_value(&value).map(Some)
+}
+
+/// Decodes an app configuration value, which is little endian and at most 4 bytes long.
+pub(crate) fn decode_le_value(value: &[u8]) -> Result<u32> {
+    if value.is_empty() || value.len() > 4 {
+        return Err(Error::BadParameters);
+    }
+    Ok(value.iter().rev().fold(0, |acc, byte| (acc << 8) | *byte as u32))
+}
+
+/// Returns the size of the value of a FiRa app configuration, if fixed and known.
+fn expected_app_config_len(cfg_id: AppConfigTlvType) -> Option<usize> {
+    match cfg_id {
+        AppConfigTlvType::DeviceType
+        | AppConfigTlvType::RangingRoundUsage
+        | AppConfigTlvType::StsConfig
+        | AppConfigTlvType::RangingRoundControl
+        | AppConfigTlvType::RframeConfig
+        | AppConfigTlvType::PsduDataRate
+        | AppConfigTlvType::PreambleDuration
+        | AppConfigTlvType::DataRepetitionCount
+        | AppConfigTlvType::RangingTimeStruct
+        | AppConfigTlvType::SlotsPerRr
+        | AppConfigTlvType::KeyRotation
+        | AppConfigTlvType::KeyRotationRate
+        | AppConfigTlvType::HoppingMode => Some(1),
+        AppConfigTlvType::SlotDuration | AppConfigTlvType::MaxRrRetry => Some(2),
+        AppConfigTlvType::RangingDuration | AppConfigTlvType::SubSessionId => Some(4),
+        _ => None,
+    }
+}
+
+/// Checks the size of the value of each app configuration TLV whose size is known, without sending
+/// them to the UWBS. The other TLVs are accepted, and logged.
+pub(crate) fn validate_app_config_tlvs(tlvs: Vec<AppConfigTlv>) -> Result<()> {
+    for tlv in tlvs.into_iter().map(AppConfigTlv::into_inner) {
+        match expected_app_config_len(tlv.cfg_id) {
+            Some(len) if len != tlv.v.len() => {
+                error!(
+                    "UCI JNI: app config {:?} is {} bytes long, expected {}",
+                    tlv.cfg_id,
+                    tlv.v.len(),
+                    len
+                );
+                return Err(Error::BadParameters);
+            }
+            Some(_) => {}
+            None => warn!("UCI JNI: size of app config {:?} not validated", tlv.cfg_id),
+        }
+    }
+    Ok(())
+}
+
+// FiRa app configuration ids not referenced by name:
+const RANGE_DATA_NTF_AOA_BOUND: u8 = 0x1D;
+const SESSION_PRIORITY: u8 = 0x25;
+pub(crate) const MAC_ADDRESS_MODE: u8 = 0x26;
+pub(crate) const BLOCK_STRIDE_LENGTH: u8 = 0x2D;
+pub(crate) const MIN_FRAMES_PER_RR: u8 = 0x3A;
+const MTU_SIZE: u8 = 0x3B;
+pub(crate) const SESSION_DATA_TRANSFER_STATUS_NTF_CONFIG: u8 = 0x47;
+
+/// Returns the type of a FiRa app configuration from its id.
+pub(crate) fn fira_cfg_id(cfg_id: u8) -> Result<AppConfigTlvType> {
+    AppConfigTlvType::try_from(cfg_id).map_err(|_| Error::BadParameters)
+}
+
+/// Builds the MIN_FRAMES_PER_RR TLV, the number of frames the advertiser of an OWR for AoA session
+/// transmits in each ranging round. A ranging round carries at least one transmission.
+pub(crate) fn tx_per_round_tlv(count: jint) -> Result<AppConfigTlv> {
+    let count = u8::try_from(count).map_err(|_| Error::BadParameters)?;
+    if count == 0 {
+        return Err(Error::BadParameters);
+    }
+    Ok(AppConfigTlv::new(fira_cfg_id(MIN_FRAMES_PER_RR)?, vec![count]))
+}
+
+/// Builds the SLOT_DURATION TLV, in RSTU. The slot duration can not be shorter than the minimum
+/// slot duration advertised by the UWBS, if any.
+pub(crate) fn slot_duration_tlv(
+    slot_duration_rstu: jint,
+    min_slot_duration_rstu: Option<u32>,
+) -> Result<AppConfigTlv> {
+    let slot_duration = u16::try_from(slot_duration_rstu).map_err(|_| Error::BadParameters)?;
+    if slot_duration == 0 || (slot_duration as u32) < min_slot_duration_rstu.unwrap_or(0) {
+        return Err(Error::BadParameters);
+    }
+    Ok(AppConfigTlv::new(AppConfigTlvType::SlotDuration, slot_duration.to_le_bytes().to_vec()))
+}
+
+// Base unit of the ranging interval, in RSTU, for each RANGING_TIME_STRUCT value.
+const INTERVAL_BASED_TIMING_BASE_RSTU: u32 = 1200;
+const BLOCK_BASED_TIMING_BASE_RSTU: u32 = 2000;
+
+/// Decodes the base unit, in RSTU, the ranging interval of the session is expressed in from the
+/// RANGING_TIME_STRUCT value read back from the UWBS.
+pub(crate) fn block_timing_base_rstu(ranging_time_struct: u32) -> Result<u32> {
+    match ranging_time_struct {
+        0 => Ok(INTERVAL_BASED_TIMING_BASE_RSTU),
+        1 => Ok(BLOCK_BASED_TIMING_BASE_RSTU),
+        _ => Err(Error::BadParameters),
+    }
+}
+
+// Upper bound of UWB_INITIATION_TIME, in ms, as defined by FiRa 1.x.
+const MAX_UWB_INITIATION_TIME_MS: u32 = 10000;
+
+/// Builds the UWB_INITIATION_TIME TLV, the offset in ms between the session start and the first
+/// ranging round between the initiator and the responders.
+pub(crate) fn ranging_time_offset_tlv(offset_ms: jint) -> Result<AppConfigTlv> {
+    let offset_ms = u32::try_from(offset_ms).map_err(|_| Error::BadParameters)?;
+    if offset_ms > MAX_UWB_INITIATION_TIME_MS {
+        return Err(Error::BadParameters);
+    }
+    Ok(AppConfigTlv::new(AppConfigTlvType::UwbInitiationTime, offset_ms.to_le_bytes().to_vec()))
+}
+
+/// Builds the SLOTS_PER_RR TLV. A ranging round holds at least one slot, and the UWBS can not
+/// schedule more slots than the 1 byte field holds.
+pub(crate) fn slots_per_round_tlv(slots: jint) -> Result<AppConfigTlv> {
+    let slots = u8::try_from(slots).map_err(|_| Error::BadParameters)?;
+    if slots == 0 {
+        return Err(Error::BadParameters);
+    }
+    Ok(AppConfigTlv::new(AppConfigTlvType::SlotsPerRr, vec![slots]))
+}
+
+/// Builds the SUB_SESSION_ID TLV. The sub-session id is defined by the application and any 32 bits
+/// value is valid.
+pub(crate) fn sub_session_id_tlv(sub_session_id: jint) -> AppConfigTlv {
+    AppConfigTlv::new(
+        AppConfigTlvType::SubSessionId,
+        (sub_session_id as u32).to_le_bytes().to_vec(),
+    )
+}
+
+// Bit of RANGING_ROUND_CONTROL enabling the ranging result report message phase.
+const RANGING_RESULT_REPORT_MESSAGE: u8 = 0x01;
+
+/// Builds the RANGING_ROUND_CONTROL TLV from its current value, with the ranging result report
+/// phase enabled or disabled. The other phases of the ranging round are left unchanged.
+pub(crate) fn result_report_phase_tlv(
+    ranging_round_control: u32,
+    enable: bool,
+) -> Result<AppConfigTlv> {
+    let ranging_round_control =
+        u8::try_from(ranging_round_control).map_err(|_| Error::BadParameters)?;
+    let ranging_round_control = if enable {
+        ranging_round_control | RANGING_RESULT_REPORT_MESSAGE
+    } else {
+        ranging_round_control & !RANGING_RESULT_REPORT_MESSAGE
+    };
+    Ok(AppConfigTlv::new(AppConfigTlvType::RangingRoundControl, vec![ranging_round_control]))
+}
+
+/// Decodes whether the ranging result report phase is enabled from the RANGING_ROUND_CONTROL
+/// value read back from the UWBS.
+pub(crate) fn is_result_report_phase_enabled(ranging_round_control: u32) -> bool {
+    ranging_round_control & RANGING_RESULT_REPORT_MESSAGE as u32 != 0
+}
+
+/// Builds the RANGING_DURATION TLV of an OWR for AoA session, the interval in ms between two of its
+/// one way ranging rounds.
+pub(crate) fn owr_aoa_ranging_interval_tlv(interval_ms: jint) -> Result<AppConfigTlv> {
+    let interval_ms = u32::try_from(interval_ms).map_err(|_| Error::BadParameters)?;
+    if interval_ms == 0 {
+        return Err(Error::BadParameters);
+    }
+    Ok(AppConfigTlv::new(AppConfigTlvType::RangingDuration, interval_ms.to_le_bytes().to_vec()))
+}
+
+/// Reads the max size, in bytes, of the in-band data payload carried by a single packet of the
+/// session, reported by the UWBS as the MTU_SIZE app configuration.
+pub(crate) fn session_get_max_inband_payload<U: UciManager>(
+    uci_manager: &UciManagerSync<U>,
+    session_id: u32,
+) -> Result<u16> {
+    match *session_get_app_config_value(uci_manager, session_id, fira_cfg_id(MTU_SIZE)?)? {
+        [b0, b1] => Ok(u16::from_le_bytes([b0, b1])),
+        _ => Err(Error::BadParameters),
+    }
+}
+
+/// Builds the SESSION_DATA_TRANSFER_STATUS_NTF_CONFIG TLV, which selects whether the UWBS sends a
+/// data transfer status notification for each data message of the session.
+pub(crate) fn data_transfer_status_ntf_tlv(enable: bool) -> Result<AppConfigTlv> {
+    Ok(AppConfigTlv::new(
+        fira_cfg_id(SESSION_DATA_TRANSFER_STATUS_NTF_CONFIG)?,
+        vec![enable.into()],
+    ))
+}
+
+// Values of RFRAME_CONFIG, the STS packet configuration of the session. SP2 is reserved.
+const RFRAME_CONFIG_SP0: u8 = 0x00;
+const RFRAME_CONFIG_SP1: u8 = 0x01;
+const RFRAME_CONFIG_SP3: u8 = 0x03;
+
+/// Builds the RFRAME_CONFIG TLV. Only SP0, SP1 and SP3 are accepted.
+pub(crate) fn rframe_config_tlv(config: jint) -> Result<AppConfigTlv> {
+    match u8::try_from(config) {
+        Ok(config @ (RFRAME_CONFIG_SP0 | RFRAME_CONFIG_SP1 | RFRAME_CONFIG_SP3)) => {
+            Ok(AppConfigTlv::new(AppConfigTlvType::RframeConfig, vec![config]))
+        }
+        _ => Err(Error::BadParameters),
+    }
+}
+
+// Values of PREAMBLE_DURATION.
+const PREAMBLE_DURATION_T32_SYMBOLS: u8 = 0x00;
+const PREAMBLE_DURATION_T64_SYMBOLS: u8 = 0x01;
+
+/// Builds the PREAMBLE_DURATION TLV from the duration in symbols, either 32 or 64.
+pub(crate) fn preamble_duration_tlv(duration_symbols: jint) -> Result<AppConfigTlv> {
+    let preamble_duration = match duration_symbols {
+        32 => PREAMBLE_DURATION_T32_SYMBOLS,
+        64 => PREAMBLE_DURATION_T64_SYMBOLS,
+        _ => return Err(Error::BadParameters),
+    };
+    Ok(AppConfigTlv::new(AppConfigTlvType::PreambleDuration, vec![preamble_duration]))
+}
+
+/// Decodes the duration in symbols from the PREAMBLE_DURATION value read back from the UWBS.
+pub(crate) fn preamble_duration_symbols(preamble_duration: u32) -> Result<u32> {
+    match u8::try_from(preamble_duration) {
+        Ok(PREAMBLE_DURATION_T32_SYMBOLS) => Ok(32),
+        Ok(PREAMBLE_DURATION_T64_SYMBOLS) => Ok(64),
+        _ => Err(Error::BadParameters),
+    }
+}
+
+// Values of PSDU_DATA_RATE.
+const PSDU_DATA_RATE_6_81_MBPS: u8 = 0x00;
+const PSDU_DATA_RATE_7_80_MBPS: u8 = 0x01;
+const PSDU_DATA_RATE_27_2_MBPS: u8 = 0x02;
+const PSDU_DATA_RATE_31_2_MBPS: u8 = 0x03;
+const PSDU_DATA_RATE_850_KBPS: u8 = 0x04;
+
+/// Builds the PSDU_DATA_RATE TLV. The 27.2 and 31.2 Mbps rates are only available in HPRF mode,
+/// and are rejected if the UWBS does not support it.
+pub(crate) fn psdu_data_rate_tlv(rate: jint, hprf_supported: bool) -> Result<AppConfigTlv> {
+    match u8::try_from(rate) {
+        Ok(
+            rate @ (PSDU_DATA_RATE_6_81_MBPS | PSDU_DATA_RATE_7_80_MBPS | PSDU_DATA_RATE_850_KBPS),
+        ) => Ok(AppConfigTlv::new(AppConfigTlvType::PsduDataRate, vec![rate])),
+        Ok(rate @ (PSDU_DATA_RATE_27_2_MBPS | PSDU_DATA_RATE_31_2_MBPS)) if hprf_supported => {
+            Ok(AppConfigTlv::new(AppConfigTlvType::PsduDataRate, vec![rate]))
+        }
+        _ => Err(Error::BadParameters),
+    }
+}
+
+// Values of HOPPING_MODE. The range is reserved for vendor specific hopping sequences.
+const HOPPING_MODE_DISABLED: u8 = 0x00;
+const HOPPING_MODE_FIRA_HOPPING: u8 = 0x01;
+const HOPPING_MODE_VENDOR_SPECIFIC: std::ops::RangeInclusive<u8> = 0xA0..=0xAF;
+
+/// Builds the HOPPING_MODE TLV, selecting the hopping sequence of the controller. Hopping is
+/// disabled with 0, the FiRa hopping sequence is selected with 1 and vendor specific sequences
+/// with 0xA0 to 0xAF.
+pub(crate) fn hopping_sequence_tlv(sequence: jint) -> Result<AppConfigTlv> {
+    match u8::try_from(sequence) {
+        Ok(sequence)
+            if sequence == HOPPING_MODE_DISABLED
+                || sequence == HOPPING_MODE_FIRA_HOPPING
+                || HOPPING_MODE_VENDOR_SPECIFIC.contains(&sequence) =>
+        {
+            Ok(AppConfigTlv::new(AppConfigTlvType::HoppingMode, vec![sequence]))
+        }
+        _ => Err(Error::BadParameters),
+    }
+}
+
+// Upper bound of KEY_ROTATION_RATE, the STS key being refreshed every 2^KEY_ROTATION_RATE rounds.
+const MAX_KEY_ROTATION_RATE: u32 = 15;
+
+/// Builds the KEY_ROTATION and KEY_ROTATION_RATE TLVs, refreshing the STS key every `rounds`
+/// ranging rounds. `rounds` is a power of 2 up to 2^15, or 0 to disable the key rotation.
+pub(crate) fn sts_key_refresh_rounds_tlvs(rounds: jint) -> Result<Vec<AppConfigTlv>> {
+    let rounds = u32::try_from(rounds).map_err(|_| Error::BadParameters)?;
+    if rounds == 0 {
+        return Ok(vec![AppConfigTlv::new(AppConfigTlvType::KeyRotation, vec![0x00])]);
+    }
+    if !rounds.is_power_of_two() || rounds.trailing_zeros() > MAX_KEY_ROTATION_RATE {
+        return Err(Error::BadParameters);
+    }
+    Ok(vec![
+        AppConfigTlv::new(AppConfigTlvType::KeyRotation, vec![0x01]),
+        AppConfigTlv::new(AppConfigTlvType::KeyRotationRate, vec![rounds.trailing_zeros() as u8]),
+    ])
+}
+
+// STS modes, decoded from STS_CONFIG.
+const STS_MODE_STATIC: u8 = 0;
+const STS_MODE_DYNAMIC: u8 = 1;
+const STS_MODE_PROVISIONED: u8 = 2;
+
+/// Decodes the STS mode from the STS_CONFIG value read back from the UWBS. The individual key
+/// variants of the dynamic and provisioned STS are reported as their base mode.
+pub(crate) fn decode_sts_mode(sts_config: u32) -> Result<u8> {
+    match sts_config {
+        0x00 => Ok(STS_MODE_STATIC),
+        0x01 | 0x02 => Ok(STS_MODE_DYNAMIC),
+        0x03 | 0x04 => Ok(STS_MODE_PROVISIONED),
+        _ => Err(Error::BadParameters),
+    }
+}
+
+/// Returns whether the data transferred in the session is encrypted, from its STS_CONFIG value.
+/// The data is encrypted with the session key, which the static STS does not have.
+pub(crate) fn is_data_encrypted(sts_config: u32) -> Result<bool> {
+    decode_sts_mode(sts_config).map(|sts_mode| sts_mode != STS_MODE_STATIC)
+}
+
+/// Builds the MAX_RR_RETRY TLV from the ranging error policy of the session: the number of
+/// consecutive failed ranging rounds after which the UWBS stops the session, or 0 to keep ranging
+/// whatever the failures.
+pub(crate) fn ranging_error_policy_tlv(max_failed_rounds: jint) -> Result<AppConfigTlv> {
+    let max_failed_rounds = u16::try_from(max_failed_rounds).map_err(|_| Error::BadParameters)?;
+    Ok(AppConfigTlv::new(AppConfigTlvType::MaxRrRetry, max_failed_rounds.to_le_bytes().to_vec()))
+}
+
+/// Builds the BLOCK_STRIDE_LENGTH TLV from the multiplier of the block duration of the session,
+/// from 1 to 256: the session ranges in one of every `multiplier` blocks, skipping the others.
+pub(crate) fn block_duration_multiplier_tlv(multiplier: jint) -> Result<AppConfigTlv> {
+    let stride = multiplier.checked_sub(1).and_then(|stride| u8::try_from(stride).ok());
+    match stride {
+        Some(stride) => Ok(AppConfigTlv::new(fira_cfg_id(BLOCK_STRIDE_LENGTH)?, vec![stride])),
+        None => Err(Error::BadParameters),
+    }
+}
+
+/// Decodes the multiplier of the block duration of the session from its BLOCK_STRIDE_LENGTH.
+pub(crate) fn decode_block_duration_multiplier(stride: u32) -> Result<u32> {
+    match u8::try_from(stride) {
+        Ok(stride) => Ok(u32::from(stride) + 1),
+        Err(_) => Err(Error::BadParameters),
+    }
+}
+
+// Values of MAC_ADDRESS_MODE: 2 byte addresses, 8 byte addresses with a 2 byte MAC header, and 8
+// byte addresses.
+pub(crate) const MAC_ADDRESS_MODE_SHORT: u8 = 0;
+pub(crate) const MAC_ADDRESS_MODE_EXTENDED_SHORT_HEADER: u8 = 1;
+pub(crate) const MAC_ADDRESS_MODE_EXTENDED: u8 = 2;
+
+/// Builds the MAC_ADDRESS_MODE TLV, which selects the short or extended addressing of the session.
+pub(crate) fn mac_address_mode_tlv(mode: jint) -> Result<AppConfigTlv> {
+    match u8::try_from(mode) {
+        Ok(
+            mode @ (MAC_ADDRESS_MODE_SHORT
+            | MAC_ADDRESS_MODE_EXTENDED_SHORT_HEADER
+            | MAC_ADDRESS_MODE_EXTENDED),
+        ) => Ok(AppConfigTlv::new(fira_cfg_id(MAC_ADDRESS_MODE)?, vec![mode])),
+        _ => Err(Error::BadParameters),
+    }
+}
+
+/// Finds the MAC_ADDRESS_MODE set by the app configuration TLVs, if any.
+pub(crate) fn find_mac_address_mode(tlvs: &[AppConfigTlv]) -> Option<u8> {
+    let cfg_id = fira_cfg_id(MAC_ADDRESS_MODE).ok()?;
+    match tlvs.iter().find(|tlv| tlv.cfg_id == cfg_id)?.v[..] {
+        [mode] => Some(mode),
+        _ => None,
+    }
+}
+
+// Limits of the AoA azimuth and elevation, in degrees as signed Q9.7 values.
+const AOA_AZIMUTH_LIMIT: jint = 180 << 7;
+const AOA_ELEVATION_LIMIT: jint = 90 << 7;
+
+/// Builds the RANGE_DATA_NTF_AOA_BOUND TLV from the azimuth min and max, followed by the elevation
+/// min and max, in degrees as signed Q9.7 values. Fails if a min is above its max.
+pub(crate) fn aoa_bound_tlv(bound: [jint; 4]) -> Result<AppConfigTlv> {
+    let [azimuth_min, azimuth_max, elevation_min, elevation_max] = bound;
+    let azimuth_range = -AOA_AZIMUTH_LIMIT..=AOA_AZIMUTH_LIMIT;
+    let elevation_range = -AOA_ELEVATION_LIMIT..=AOA_ELEVATION_LIMIT;
+    if azimuth_min > azimuth_max
+        || elevation_min > elevation_max
+        || !azimuth_range.contains(&azimuth_min)
+        || !azimuth_range.contains(&azimuth_max)
+        || !elevation_range.contains(&elevation_min)
+        || !elevation_range.contains(&elevation_max)
+    {
+        return Err(Error::BadParameters);
+    }
+    Ok(AppConfigTlv::new(
+        fira_cfg_id(RANGE_DATA_NTF_AOA_BOUND)?,
+        bound.iter().flat_map(|value| (*value as i16).to_le_bytes()).collect(),
+    ))
+}
+
+/// Reads the AoA bounds of the range data notifications of the session, in the order of
+/// aoa_bound_tlv().
+pub(crate) fn session_get_aoa_bound<U: UciManager>(
+    uci_manager: &UciManagerSync<U>,
+    session_id: u32,
+) -> Result<[jint; 4]> {
+    let cfg_id = fira_cfg_id(RANGE_DATA_NTF_AOA_BOUND)?;
+    let value = session_get_app_config_value(uci_manager, session_id, cfg_id)?;
+    if value.len() != 8 {
+        return Err(Error::BadParameters);
+    }
+    let mut bound = [0; 4];
+    for (bound, bytes) in bound.iter_mut().zip(value.chunks_exact(2)) {
+        *bound = i16::from_le_bytes([bytes[0], bytes[1]]).into();
+    }
+    Ok(bound)
+}
+
+// Values of DEVICE_TYPE, the role of the local device in the session.
+const DEVICE_TYPE_CONTROLEE: u8 = 0;
+const DEVICE_TYPE_CONTROLLER: u8 = 1;
+
+/// Reads the role of the local device in the session, DEVICE_TYPE_CONTROLEE or
+/// DEVICE_TYPE_CONTROLLER, from its DEVICE_TYPE. Fails if the DEVICE_TYPE is not set.
+pub(crate) fn session_get_role<U: UciManager>(
+    uci_manager: &UciManagerSync<U>,
+    session_id: u32,
+) -> Result<u8> {
+    match *session_get_app_config_value(uci_manager, session_id, AppConfigTlvType::DeviceType)? {
+        [role @ (DEVICE_TYPE_CONTROLEE | DEVICE_TYPE_CONTROLLER)] => Ok(role),
+        _ => Err(Error::BadParameters),
+    }
+}
+
+/// Builds the DATA_REPETITION_COUNT TLV, the number of times a data message of the session is
+/// retransmitted after its first transmission, from 0 to 255.
+pub(crate) fn data_retransmission_count_tlv(count: jint) -> Result<AppConfigTlv> {
+    let count = u8::try_from(count).map_err(|_| Error::BadParameters)?;
+    Ok(AppConfigTlv::new(AppConfigTlvType::DataRepetitionCount, vec![count]))
+}
+
+// Range of SESSION_PRIORITY accepted by the UWBS, as defined by FiRa. Higher priority sessions
+// are scheduled first when sessions run concurrently.
+const MIN_SESSION_PRIORITY: u8 = 1;
+const MAX_SESSION_PRIORITY: u8 = 100;
+
+/// Sets the SESSION_PRIORITY of the session, from 1 to 100. Fails without sending any command if
+/// the priority is out of range.
+pub(crate) fn session_set_priority<U: UciManager>(
+    uci_manager: &UciManagerSync<U>,
+    session_id: u32,
+    priority: u8,
+) -> Result<()> {
+    if !(MIN_SESSION_PRIORITY..=MAX_SESSION_PRIORITY).contains(&priority) {
+        error!("UCI JNI: session priority {} out of range", priority);
+        return Err(Error::BadParameters);
+    }
+    let tlv = AppConfigTlv::new(fira_cfg_id(SESSION_PRIORITY)?, vec![priority]);
+    session_set_app_config_tlv(uci_manager, session_id, tlv)
+}
+
+// MAC modes of the session: two way ranging, or one way ranging where the responders do not reply
+// to the initiator.
+const MAC_MODE_TWO_WAY: u8 = 0;
+const MAC_MODE_ONE_WAY: u8 = 1;
+
+// Values of RANGING_ROUND_USAGE set for each MAC mode.
+const RANGING_ROUND_USAGE_DS_TWR_DEFERRED: u8 = 0x02;
+const RANGING_ROUND_USAGE_OWR_AOA: u8 = 0x06;
+
+/// Builds the RANGING_ROUND_USAGE TLV from the MAC mode of the session: DS-TWR with deferred mode
+/// for two way ranging, and OWR for AoA for one way ranging. Fails if the mode is unknown.
+fn mac_mode_tlv(mode: jint) -> Result<AppConfigTlv> {
+    let round_usage = match u8::try_from(mode) {
+        Ok(MAC_MODE_TWO_WAY) => RANGING_ROUND_USAGE_DS_TWR_DEFERRED,
+        Ok(MAC_MODE_ONE_WAY) => RANGING_ROUND_USAGE_OWR_AOA,
+        _ => return Err(Error::BadParameters),
+    };
+    Ok(AppConfigTlv::new(AppConfigTlvType::RangingRoundUsage, vec![round_usage]))
+}
+
+/// Sets the MAC mode of the session. The RANGING_ROUND_USAGE of the session is left unchanged if
+/// it already is of this mode, so that e.g. a SS-TWR session stays SS-TWR, and is otherwise set to
+/// the round usage of mac_mode_tlv(). Fails without sending any command if the mode is unknown.
+pub(crate) fn session_set_mac_mode<U: UciManager>(
+    uci_manager: &UciManagerSync<U>,
+    session_id: u32,
+    mode: jint,
+) -> Result<()> {
+    let tlv = mac_mode_tlv(mode)?;
+    let value =
+        session_get_app_config_value(uci_manager, session_id, AppConfigTlvType::RangingRoundUsage)?;
+    if decode_mac_mode(decode_le_value(&value)?).ok().map(jint::from) == Some(mode) {
+        return Ok(());
+    }
+    session_set_app_config_tlv(uci_manager, session_id, tlv)
+}
+
+/// Decodes the MAC mode of the session from the RANGING_ROUND_USAGE value read back from the UWBS.
+/// The TDoA and OWR for AoA round usages are one way ranging, the others two way ranging.
+pub(crate) fn decode_mac_mode(round_usage: u32) -> Result<u8> {
+    match round_usage {
+        0x01..=0x04 | 0x07 | 0x08 => Ok(MAC_MODE_TWO_WAY),
+        0x00 | 0x05 | 0x06 => Ok(MAC_MODE_ONE_WAY),
+        _ => Err(Error::BadParameters),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::helper::byte_result_helper;
+    use crate::test_utils::new_mock_uci_manager_sync;
+
+    use uwb_core::uci::mock_uci_manager::MockUciManager;
+
+    #[test]
+    fn test_decode_le_value() {
+        assert_eq!(decode_le_value(&[0x2a]).unwrap(), 0x2a);
+        assert_eq!(decode_le_value(&[0x34, 0x12]).unwrap(), 0x1234);
+        assert_eq!(decode_le_value(&[0x78, 0x56, 0x34, 0x12]).unwrap(), 0x12345678);
+        assert!(decode_le_value(&[]).is_err());
+        assert!(decode_le_value(&[0, 0, 0, 0, 0]).is_err());
+    }
+
+    #[test]
+    fn test_session_set_and_get_app_config_tlv() {
+        let tlv = AppConfigTlv::new(AppConfigTlvType::DataRepetitionCount, vec![3]);
+        let mut uci_manager_impl = MockUciManager::new();
+        uci_manager_impl.expect_session_set_app_config(
+            42, // Session id
+            vec![tlv.clone()],
+            vec![],
+            Ok(SetAppConfigResponse { status: StatusCode::UciStatusOk, config_status: vec![] }),
+        );
+        uci_manager_impl.expect_session_get_app_config(
+            42, // Session id
+            vec![AppConfigTlvType::DataRepetitionCount],
+            Ok(vec![tlv.clone()]),
+        );
+        uci_manager_impl.expect_session_set_app_config(
+            42, // Session id
+            vec![tlv.clone()],
+            vec![],
+            Ok(SetAppConfigResponse {
+                status: StatusCode::UciStatusInvalidParam,
+                config_status: vec![],
+            }),
+        );
+        let (_rt, uci_manager_sync) = new_mock_uci_manager_sync(uci_manager_impl);
+
+        assert!(session_set_app_config_tlv(&uci_manager_sync, 42, tlv.clone()).is_ok());
+        assert_eq!(
+            session_get_app_config_value(
+                &uci_manager_sync,
+                42,
+                AppConfigTlvType::DataRepetitionCount
+            )
+            .unwrap(),
+            vec![3]
+        );
+        assert!(session_set_app_config_tlv(&uci_manager_sync, 42, tlv).is_err());
+    }
+
+    #[test]
+    fn test_session_set_app_config_tlvs_device_status() {
+        let tlv = AppConfigTlv::new(AppConfigTlvType::DeviceRole, vec![0x01]);
+        let mut uci_manager_impl = MockUciManager::new();
+        uci_manager_impl.expect_session_set_app_config(
+            42, // Session id
+            vec![tlv.clone()],
+            vec![],
+            Ok(SetAppConfigResponse {
+                status: StatusCode::UciStatusSessionActive,
+                config_status: vec![],
+            }),
+        );
+        let (_rt, uci_manager_sync) = new_mock_uci_manager_sync(uci_manager_impl);
+
+        // The status of the device is returned to the Java side, not the one of BadParameters.
+        assert_eq!(
+            byte_result_helper(
+                session_set_app_config_tlvs(&uci_manager_sync, 42, vec![tlv]),
+                "test"
+            ),
+            u8::from(StatusCode::UciStatusSessionActive) as i8
+        );
+    }
+
+    #[test]
+    fn test_tx_per_round_tlv() {
+        assert_eq!(
+            tx_per_round_tlv(3).unwrap(),
+            AppConfigTlv::new(fira_cfg_id(MIN_FRAMES_PER_RR).unwrap(), vec![3])
+        );
+        assert!(tx_per_round_tlv(0).is_err());
+        assert!(tx_per_round_tlv(256).is_err());
+        assert!(tx_per_round_tlv(-1).is_err());
+    }
+
+    #[test]
+    fn test_slot_duration_tlv() {
+        assert_eq!(
+            slot_duration_tlv(2400, None).unwrap(),
+            AppConfigTlv::new(AppConfigTlvType::SlotDuration, vec![0x60, 0x09])
+        );
+        assert_eq!(
+            slot_duration_tlv(2400, Some(2400)).unwrap(),
+            AppConfigTlv::new(AppConfigTlvType::SlotDuration, vec![0x60, 0x09])
+        );
+        assert!(slot_duration_tlv(1200, Some(2400)).is_err());
+        assert!(slot_duration_tlv(0, None).is_err());
+        assert!(slot_duration_tlv(65536, None).is_err());
+    }
+
+    #[test]
+    fn test_block_timing_base_rstu() {
+        let mut uci_manager_impl = MockUciManager::new();
+        uci_manager_impl.expect_session_get_app_config(
+            42, // Session id
+            vec![AppConfigTlvType::RangingTimeStruct],
+            Ok(vec![AppConfigTlv::new(AppConfigTlvType::RangingTimeStruct, vec![1])]),
+        );
+        let (_rt, uci_manager_sync) = new_mock_uci_manager_sync(uci_manager_impl);
+
+        let value = session_get_app_config_value(
+            &uci_manager_sync,
+            42,
+            AppConfigTlvType::RangingTimeStruct,
+        )
+        .unwrap();
+        assert_eq!(block_timing_base_rstu(decode_le_value(&value).unwrap()).unwrap(), 2000);
+        assert_eq!(block_timing_base_rstu(0).unwrap(), 1200);
+        assert!(block_timing_base_rstu(2).is_err());
+    }
+
+    #[test]
+    fn test_ranging_time_offset_tlv() {
+        assert_eq!(
+            ranging_time_offset_tlv(1000).unwrap(),
+            AppConfigTlv::new(AppConfigTlvType::UwbInitiationTime, vec![0xe8, 0x03, 0, 0])
+        );
+        assert_eq!(
+            ranging_time_offset_tlv(0).unwrap(),
+            AppConfigTlv::new(AppConfigTlvType::UwbInitiationTime, vec![0, 0, 0, 0])
+        );
+        assert!(ranging_time_offset_tlv(10001).is_err());
+        assert!(ranging_time_offset_tlv(-1).is_err());
+    }
+
+    #[test]
+    fn test_slots_per_round_tlv() {
+        assert_eq!(
+            slots_per_round_tlv(25).unwrap(),
+            AppConfigTlv::new(AppConfigTlvType::SlotsPerRr, vec![25])
+        );
+        assert!(slots_per_round_tlv(0).is_err());
+        assert!(slots_per_round_tlv(256).is_err());
+    }
+
+    #[test]
+    fn test_sub_session_id_round_trip() {
+        let tlv = sub_session_id_tlv(0x89abcdefu32 as i32);
+        assert_eq!(
+            tlv,
+            AppConfigTlv::new(AppConfigTlvType::SubSessionId, vec![0xef, 0xcd, 0xab, 0x89])
+        );
+        let mut uci_manager_impl = MockUciManager::new();
+        uci_manager_impl.expect_session_set_app_config(
+            42, // Session id
+            vec![tlv.clone()],
+            vec![],
+            Ok(SetAppConfigResponse { status: StatusCode::UciStatusOk, config_status: vec![] }),
+        );
+        uci_manager_impl.expect_session_get_app_config(
+            42, // Session id
+            vec![AppConfigTlvType::SubSessionId],
+            Ok(vec![tlv.clone()]),
+        );
+        let (_rt, uci_manager_sync) = new_mock_uci_manager_sync(uci_manager_impl);
+
+        assert!(session_set_app_config_tlv(&uci_manager_sync, 42, tlv).is_ok());
+        let value =
+            session_get_app_config_value(&uci_manager_sync, 42, AppConfigTlvType::SubSessionId)
+                .unwrap();
+        assert_eq!(decode_le_value(&value).unwrap(), 0x89abcdef);
+    }
+
+    #[test]
+    fn test_result_report_phase_tlv() {
+        // Control message and ranging control phase enabled.
+        let tlv = result_report_phase_tlv(0x06, true).unwrap();
+        assert_eq!(tlv, AppConfigTlv::new(AppConfigTlvType::RangingRoundControl, vec![0x07]));
+        assert!(is_result_report_phase_enabled(0x07));
+
+        let tlv = result_report_phase_tlv(0x07, false).unwrap();
+        assert_eq!(tlv, AppConfigTlv::new(AppConfigTlvType::RangingRoundControl, vec![0x06]));
+        assert!(!is_result_report_phase_enabled(0x06));
+
+        assert!(result_report_phase_tlv(0x100, true).is_err());
+    }
+
+    #[test]
+    fn test_rframe_config_tlv() {
+        let tlv = rframe_config_tlv(3).unwrap();
+        assert_eq!(tlv, AppConfigTlv::new(AppConfigTlvType::RframeConfig, vec![0x03]));
+        assert!(rframe_config_tlv(0).is_ok());
+        assert!(rframe_config_tlv(1).is_ok());
+
+        assert!(rframe_config_tlv(2).is_err());
+        assert!(rframe_config_tlv(4).is_err());
+        assert!(rframe_config_tlv(-1).is_err());
+    }
+
+    #[test]
+    fn test_preamble_duration_tlv() {
+        let tlv = preamble_duration_tlv(64).unwrap();
+        assert_eq!(tlv, AppConfigTlv::new(AppConfigTlvType::PreambleDuration, vec![0x01]));
+        let tlv = preamble_duration_tlv(32).unwrap();
+        assert_eq!(tlv, AppConfigTlv::new(AppConfigTlvType::PreambleDuration, vec![0x00]));
+        assert!(preamble_duration_tlv(16).is_err());
+
+        assert_eq!(preamble_duration_symbols(0x01).unwrap(), 64);
+        assert!(preamble_duration_symbols(0x02).is_err());
+    }
+
+    #[test]
+    fn test_session_get_effective_block_duration_ms() {
+        let mut uci_manager_impl = MockUciManager::new();
+        uci_manager_impl.expect_session_get_state(42, Ok(SessionState::SessionStateActive));
+        uci_manager_impl.expect_session_get_app_config(
+            42, // Session id
+            vec![AppConfigTlvType::RangingDuration],
+            // 240 ms requested, adjusted to 288 ms by the UWBS.
+            Ok(vec![AppConfigTlv::new(AppConfigTlvType::RangingDuration, vec![0x20, 0x01, 0, 0])]),
+        );
+        uci_manager_impl.expect_session_get_state(42, Ok(SessionState::SessionStateIdle));
+        let (_rt, uci_manager_sync) = new_mock_uci_manager_sync(uci_manager_impl);
+
+        assert_eq!(
+            session_get_effective_block_duration_ms(&uci_manager_sync, 42).unwrap(),
+            Some(288)
+        );
+        assert_eq!(session_get_effective_block_duration_ms(&uci_manager_sync, 42).unwrap(), None);
+    }
+
+    #[test]
+    fn test_psdu_data_rate_tlv() {
+        let tlv = psdu_data_rate_tlv(0, false).unwrap();
+        assert_eq!(tlv, AppConfigTlv::new(AppConfigTlvType::PsduDataRate, vec![0x00]));
+        let tlv = psdu_data_rate_tlv(2, true).unwrap();
+        assert_eq!(tlv, AppConfigTlv::new(AppConfigTlvType::PsduDataRate, vec![0x02]));
+
+        // HPRF rate without HPRF support.
+        assert!(psdu_data_rate_tlv(2, false).is_err());
+        assert!(psdu_data_rate_tlv(5, true).is_err());
+        assert!(psdu_data_rate_tlv(-1, true).is_err());
+    }
+
+    #[test]
+    fn test_hopping_sequence_tlv() {
+        let tlv = hopping_sequence_tlv(1).unwrap();
+        assert_eq!(tlv, AppConfigTlv::new(AppConfigTlvType::HoppingMode, vec![0x01]));
+        let tlv = hopping_sequence_tlv(0xA3).unwrap();
+        assert_eq!(tlv, AppConfigTlv::new(AppConfigTlvType::HoppingMode, vec![0xA3]));
+        assert!(hopping_sequence_tlv(0).is_ok());
+
+        assert!(hopping_sequence_tlv(2).is_err());
+        assert!(hopping_sequence_tlv(0xB0).is_err());
+        assert!(hopping_sequence_tlv(-1).is_err());
+    }
+
+    #[test]
+    fn test_sts_key_refresh_rounds_tlvs() {
+        assert_eq!(
+            sts_key_refresh_rounds_tlvs(16).unwrap(),
+            vec![
+                AppConfigTlv::new(AppConfigTlvType::KeyRotation, vec![0x01]),
+                AppConfigTlv::new(AppConfigTlvType::KeyRotationRate, vec![0x04]),
+            ]
+        );
+        assert_eq!(
+            sts_key_refresh_rounds_tlvs(0).unwrap(),
+            vec![AppConfigTlv::new(AppConfigTlvType::KeyRotation, vec![0x00])]
+        );
+        assert!(sts_key_refresh_rounds_tlvs(1 << 15).is_ok());
+
+        assert!(sts_key_refresh_rounds_tlvs(1 << 16).is_err());
+        assert!(sts_key_refresh_rounds_tlvs(12).is_err());
+        assert!(sts_key_refresh_rounds_tlvs(-1).is_err());
+    }
+
+    #[test]
+    fn test_sts_mode_round_trip() {
+        // Provisioned STS for responder specific sub-session key.
+        let tlv = AppConfigTlv::new(AppConfigTlvType::StsConfig, vec![0x04]);
+        let mut uci_manager_impl = MockUciManager::new();
+        uci_manager_impl.expect_session_set_app_config(
+            42, // Session id
+            vec![tlv.clone()],
+            vec![],
+            Ok(SetAppConfigResponse { status: StatusCode::UciStatusOk, config_status: vec![] }),
+        );
+        uci_manager_impl.expect_session_get_app_config(
+            42, // Session id
+            vec![AppConfigTlvType::StsConfig],
+            Ok(vec![tlv.clone()]),
+        );
+        let (_rt, uci_manager_sync) = new_mock_uci_manager_sync(uci_manager_impl);
+
+        assert!(session_set_app_config_tlv(&uci_manager_sync, 42, tlv).is_ok());
+        let value =
+            session_get_app_config_value(&uci_manager_sync, 42, AppConfigTlvType::StsConfig)
+                .unwrap();
+        assert_eq!(
+            decode_sts_mode(decode_le_value(&value).unwrap()).unwrap(),
+            STS_MODE_PROVISIONED
+        );
+
+        assert_eq!(decode_sts_mode(0x00).unwrap(), STS_MODE_STATIC);
+        assert_eq!(decode_sts_mode(0x01).unwrap(), STS_MODE_DYNAMIC);
+        assert!(decode_sts_mode(0x05).is_err());
+    }
+
+    #[test]
+    fn test_ranging_error_policy_tlv() {
+        // Stop the session after 5 consecutive failed rounds.
+        let tlv = ranging_error_policy_tlv(5).unwrap();
+        assert_eq!(tlv, AppConfigTlv::new(AppConfigTlvType::MaxRrRetry, vec![0x05, 0x00]));
+        // Continue after failures.
+        let tlv = ranging_error_policy_tlv(0).unwrap();
+        assert_eq!(tlv, AppConfigTlv::new(AppConfigTlvType::MaxRrRetry, vec![0x00, 0x00]));
+
+        assert!(ranging_error_policy_tlv(-1).is_err());
+        assert!(ranging_error_policy_tlv(0x10000).is_err());
+    }
+
+    #[test]
+    fn test_block_duration_multiplier_tlv() {
+        let cfg_id = fira_cfg_id(BLOCK_STRIDE_LENGTH).unwrap();
+        assert_eq!(block_duration_multiplier_tlv(1).unwrap(), AppConfigTlv::new(cfg_id, vec![0]));
+        assert_eq!(block_duration_multiplier_tlv(3).unwrap(), AppConfigTlv::new(cfg_id, vec![2]));
+        assert_eq!(
+            block_duration_multiplier_tlv(256).unwrap(),
+            AppConfigTlv::new(cfg_id, vec![0xff])
+        );
+        assert!(block_duration_multiplier_tlv(0).is_err());
+        assert!(block_duration_multiplier_tlv(257).is_err());
+        assert!(block_duration_multiplier_tlv(-1).is_err());
+        assert!(block_duration_multiplier_tlv(jint::MIN).is_err());
+
+        assert_eq!(decode_block_duration_multiplier(0).unwrap(), 1);
+        assert_eq!(decode_block_duration_multiplier(0xff).unwrap(), 256);
+        assert!(decode_block_duration_multiplier(0x100).is_err());
+    }
+
+    #[test]
+    fn test_is_data_encrypted() {
+        let encrypted_tlv = AppConfigTlv::new(AppConfigTlvType::StsConfig, vec![0x01]);
+        let unencrypted_tlv = AppConfigTlv::new(AppConfigTlvType::StsConfig, vec![0x00]);
+        let mut uci_manager_impl = MockUciManager::new();
+        uci_manager_impl.expect_session_get_app_config(
+            42, // Session id
+            vec![AppConfigTlvType::StsConfig],
+            Ok(vec![encrypted_tlv]),
+        );
+        uci_manager_impl.expect_session_get_app_config(
+            43, // Session id
+            vec![AppConfigTlvType::StsConfig],
+            Ok(vec![unencrypted_tlv]),
+        );
+        let (_rt, uci_manager_sync) = new_mock_uci_manager_sync(uci_manager_impl);
+
+        let value =
+            session_get_app_config_value(&uci_manager_sync, 42, AppConfigTlvType::StsConfig)
+                .unwrap();
+        assert!(is_data_encrypted(decode_le_value(&value).unwrap()).unwrap());
+        let value =
+            session_get_app_config_value(&uci_manager_sync, 43, AppConfigTlvType::StsConfig)
+                .unwrap();
+        assert!(!is_data_encrypted(decode_le_value(&value).unwrap()).unwrap());
+
+        assert!(is_data_encrypted(0x04).unwrap());
+        assert!(is_data_encrypted(0x05).is_err());
+    }
+
+    #[test]
+    fn test_data_retransmission_count_tlv() {
+        let tlv = data_retransmission_count_tlv(3).unwrap();
+        assert_eq!(tlv, AppConfigTlv::new(AppConfigTlvType::DataRepetitionCount, vec![0x03]));
+        // No retransmission: the data message is only sent once.
+        let tlv = data_retransmission_count_tlv(0).unwrap();
+        assert_eq!(tlv, AppConfigTlv::new(AppConfigTlvType::DataRepetitionCount, vec![0x00]));
+        assert!(data_retransmission_count_tlv(255).is_ok());
+
+        assert!(data_retransmission_count_tlv(256).is_err());
+        assert!(data_retransmission_count_tlv(-1).is_err());
+    }
+
+    #[test]
+    fn test_set_app_configs_batch() {
+        let tlv = AppConfigTlv::new(AppConfigTlvType::DataRepetitionCount, vec![3]);
+        let ok_response =
+            SetAppConfigResponse { status: StatusCode::UciStatusOk, config_status: vec![] };
+        let mut uci_manager_impl = MockUciManager::new();
+        uci_manager_impl.expect_session_set_app_config(
+            1, // Session id
+            vec![tlv.clone()],
+            vec![],
+            Ok(ok_response.clone()),
+        );
+        uci_manager_impl.expect_session_set_app_config(
+            2, // Session id
+            vec![tlv.clone()],
+            vec![],
+            Err(Error::CommandRetry),
+        );
+        uci_manager_impl.expect_session_set_app_config(
+            3, // Session id
+            vec![tlv.clone()],
+            vec![],
+            Ok(ok_response.clone()),
+        );
+        let (_rt, uci_manager_sync) = new_mock_uci_manager_sync(uci_manager_impl);
+
+        let configs = vec![
+            (1, Ok(vec![tlv.clone()])),
+            (2, Ok(vec![tlv.clone()])),
+            (3, Ok(vec![tlv.clone()])),
+            // Not sent, as its TLVs could not be parsed.
+            (4, Err(Error::BadParameters)),
+        ];
+        let responses = set_app_configs_batch(configs, |session_id, tlvs| {
+            uci_manager_sync.session_set_app_config(session_id, tlvs)
+        });
+        assert_eq!(
+            responses,
+            vec![
+                ok_response.clone(),
+                SetAppConfigResponse {
+                    status: StatusCode::UciStatusCommandRetry,
+                    config_status: vec![]
+                },
+                ok_response,
+                SetAppConfigResponse {
+                    status: StatusCode::UciStatusInvalidParam,
+                    config_status: vec![]
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_owr_aoa_ranging_interval_tlv() {
+        assert_eq!(
+            owr_aoa_ranging_interval_tlv(240).unwrap(),
+            AppConfigTlv::new(AppConfigTlvType::RangingDuration, vec![0xf0, 0x00, 0x00, 0x00])
+        );
+        assert!(owr_aoa_ranging_interval_tlv(0).is_err());
+        assert!(owr_aoa_ranging_interval_tlv(-1).is_err());
+    }
+
+    #[test]
+    fn test_session_get_max_inband_payload() {
+        let cfg_id = fira_cfg_id(MTU_SIZE).unwrap();
+        let mut uci_manager_impl = MockUciManager::new();
+        uci_manager_impl.expect_session_get_app_config(
+            42, // Session id
+            vec![cfg_id],
+            Ok(vec![AppConfigTlv::new(cfg_id, vec![0x7b, 0x00])]),
+        );
+        uci_manager_impl.expect_session_get_app_config(
+            43, // Session id
+            vec![cfg_id],
+            Ok(vec![AppConfigTlv::new(cfg_id, vec![0x7b])]),
+        );
+        let (_rt, uci_manager_sync) = new_mock_uci_manager_sync(uci_manager_impl);
+
+        assert_eq!(session_get_max_inband_payload(&uci_manager_sync, 42).unwrap(), 123);
+        assert!(session_get_max_inband_payload(&uci_manager_sync, 43).is_err());
+    }
+
+    #[test]
+    fn test_aoa_bound() {
+        // Azimuth from -90 to 45.5 degrees, elevation from -10 to 10 degrees.
+        let bound = [-90 << 7, (45 << 7) + 64, -10 << 7, 10 << 7];
+        let tlv = aoa_bound_tlv(bound).unwrap();
+        let cfg_id = fira_cfg_id(RANGE_DATA_NTF_AOA_BOUND).unwrap();
+        assert_eq!(
+            tlv,
+            AppConfigTlv::new(cfg_id, vec![0x00, 0xd3, 0xc0, 0x16, 0x00, 0xfb, 0x00, 0x05])
+        );
+        let mut uci_manager_impl = MockUciManager::new();
+        uci_manager_impl.expect_session_get_app_config(
+            42, // Session id
+            vec![cfg_id],
+            Ok(vec![tlv]),
+        );
+        let (_rt, uci_manager_sync) = new_mock_uci_manager_sync(uci_manager_impl);
+        assert_eq!(session_get_aoa_bound(&uci_manager_sync, 42).unwrap(), bound);
+
+        // Inverted azimuth and elevation bounds.
+        assert!(aoa_bound_tlv([45 << 7, -90 << 7, -10 << 7, 10 << 7]).is_err());
+        assert!(aoa_bound_tlv([-90 << 7, 45 << 7, 10 << 7, -10 << 7]).is_err());
+        // Out of range.
+        assert!(aoa_bound_tlv([-181 << 7, 45 << 7, -10 << 7, 10 << 7]).is_err());
+        assert!(aoa_bound_tlv([-90 << 7, 45 << 7, -10 << 7, 91 << 7]).is_err());
+    }
+
+    #[test]
+    fn test_mac_address_mode_tlv() {
+        let cfg_id = fira_cfg_id(MAC_ADDRESS_MODE).unwrap();
+        assert_eq!(mac_address_mode_tlv(0).unwrap(), AppConfigTlv::new(cfg_id, vec![0x00]));
+        assert_eq!(mac_address_mode_tlv(2).unwrap(), AppConfigTlv::new(cfg_id, vec![0x02]));
+        assert!(mac_address_mode_tlv(1).is_ok());
+        assert!(mac_address_mode_tlv(3).is_err());
+        assert!(mac_address_mode_tlv(-1).is_err());
+    }
+
+    #[test]
+    fn test_find_mac_address_mode() {
+        let device_role = AppConfigTlv::new(AppConfigTlvType::DeviceRole, vec![0x01]);
+        assert_eq!(
+            find_mac_address_mode(&[device_role.clone(), mac_address_mode_tlv(2).unwrap()]),
+            Some(2)
+        );
+        assert_eq!(find_mac_address_mode(&[device_role]), None);
+        let malformed = AppConfigTlv::new(fira_cfg_id(MAC_ADDRESS_MODE).unwrap(), vec![0, 0]);
+        assert_eq!(find_mac_address_mode(&[malformed]), None);
+    }
+
+    #[test]
+    fn test_session_get_role() {
+        let controller =
+            AppConfigTlv::new(AppConfigTlvType::DeviceType, vec![DEVICE_TYPE_CONTROLLER]);
+        let mut uci_manager_impl = MockUciManager::new();
+        uci_manager_impl.expect_session_set_app_config(
+            42, // Session id
+            vec![controller.clone()],
+            vec![],
+            Ok(SetAppConfigResponse { status: StatusCode::UciStatusOk, config_status: vec![] }),
+        );
+        uci_manager_impl.expect_session_get_app_config(
+            42, // Session id
+            vec![AppConfigTlvType::DeviceType],
+            Ok(vec![controller.clone()]),
+        );
+        uci_manager_impl.expect_session_get_app_config(
+            43, // Session id
+            vec![AppConfigTlvType::DeviceType],
+            Ok(vec![AppConfigTlv::new(AppConfigTlvType::DeviceType, vec![DEVICE_TYPE_CONTROLEE])]),
+        );
+        uci_manager_impl.expect_session_get_app_config(
+            44, // Session id
+            vec![AppConfigTlvType::DeviceType],
+            Ok(vec![]),
+        );
+        let (_rt, uci_manager_sync) = new_mock_uci_manager_sync(uci_manager_impl);
+
+        assert!(session_set_app_config_tlv(&uci_manager_sync, 42, controller).is_ok());
+        assert_eq!(session_get_role(&uci_manager_sync, 42).unwrap(), DEVICE_TYPE_CONTROLLER);
+        assert_eq!(session_get_role(&uci_manager_sync, 43).unwrap(), DEVICE_TYPE_CONTROLEE);
+        assert!(session_get_role(&uci_manager_sync, 44).is_err());
+    }
+
+    #[test]
+    fn test_data_transfer_status_ntf_tlv() {
+        let cfg_id = fira_cfg_id(SESSION_DATA_TRANSFER_STATUS_NTF_CONFIG).unwrap();
+        assert_eq!(
+            data_transfer_status_ntf_tlv(true).unwrap(),
+            AppConfigTlv::new(cfg_id, vec![0x01])
+        );
+        assert_eq!(
+            data_transfer_status_ntf_tlv(false).unwrap(),
+            AppConfigTlv::new(cfg_id, vec![0x00])
+        );
+    }
+
+    #[test]
+    fn test_validate_app_config_tlvs() {
+        let well_formed = vec![
+            AppConfigTlv::new(AppConfigTlvType::DeviceType, vec![1]),
+            AppConfigTlv::new(AppConfigTlvType::SlotDuration, vec![0x60, 0x09]),
+            AppConfigTlv::new(AppConfigTlvType::RangingDuration, vec![0xc8, 0, 0, 0]),
+        ];
+        assert!(validate_app_config_tlvs(well_formed).is_ok());
+        assert!(validate_app_config_tlvs(vec![]).is_ok());
+
+        let wrong_len = vec![
+            AppConfigTlv::new(AppConfigTlvType::DeviceType, vec![1]),
+            AppConfigTlv::new(AppConfigTlvType::RangingDuration, vec![0xc8, 0]),
+        ];
+        assert!(validate_app_config_tlvs(wrong_len).is_err());
+
+        // The size of the vendor app configurations is not validated.
+        let unknown =
+            vec![AppConfigTlv::new(AppConfigTlvType::NbOfRangeMeasurements, vec![1, 2, 3])];
+        assert!(validate_app_config_tlvs(unknown).is_ok());
+    }
+
+    #[test]
+    fn test_session_set_priority() {
+        let mut uci_manager_impl = MockUciManager::new();
+        for priority in [1, 100] {
+            uci_manager_impl.expect_session_set_app_config(
+                42, // Session id
+                vec![AppConfigTlv::new(fira_cfg_id(SESSION_PRIORITY).unwrap(), vec![priority])],
+                vec![],
+                Ok(SetAppConfigResponse { status: StatusCode::UciStatusOk, config_status: vec![] }),
+            );
+        }
+        let (_rt, uci_manager_sync) = new_mock_uci_manager_sync(uci_manager_impl);
+
+        assert!(session_set_priority(&uci_manager_sync, 42, 1).is_ok());
+        assert!(session_set_priority(&uci_manager_sync, 42, 100).is_ok());
+        // Rejected without sending the command.
+        assert!(session_set_priority(&uci_manager_sync, 42, 0).is_err());
+        assert!(session_set_priority(&uci_manager_sync, 42, 101).is_err());
+    }
+
+    #[test]
+    fn test_mac_mode_tlv() {
+        assert_eq!(
+            mac_mode_tlv(MAC_MODE_TWO_WAY as i32).unwrap(),
+            AppConfigTlv::new(AppConfigTlvType::RangingRoundUsage, vec![0x02])
+        );
+        assert_eq!(
+            mac_mode_tlv(MAC_MODE_ONE_WAY as i32).unwrap(),
+            AppConfigTlv::new(AppConfigTlvType::RangingRoundUsage, vec![0x06])
+        );
+        assert!(mac_mode_tlv(2).is_err());
+        assert!(mac_mode_tlv(-1).is_err());
+    }
+
+    #[test]
+    fn test_decode_mac_mode() {
+        for round_usage in [0x01, 0x02, 0x03, 0x04, 0x07, 0x08] {
+            assert_eq!(decode_mac_mode(round_usage).unwrap(), MAC_MODE_TWO_WAY);
+        }
+        for round_usage in [0x00, 0x05, 0x06] {
+            assert_eq!(decode_mac_mode(round_usage).unwrap(), MAC_MODE_ONE_WAY);
+        }
+        assert!(decode_mac_mode(0x09).is_err());
+    }
+
+    #[test]
+    fn test_session_set_mac_mode() {
+        let mut uci_manager_impl = MockUciManager::new();
+        // SS-TWR with non-deferred mode, then DL-TDoA.
+        for round_usage in [0x03, 0x05] {
+            uci_manager_impl.expect_session_get_app_config(
+                42, // Session id
+                vec![AppConfigTlvType::RangingRoundUsage],
+                Ok(vec![AppConfigTlv::new(AppConfigTlvType::RangingRoundUsage, vec![round_usage])]),
+            );
+        }
+        uci_manager_impl.expect_session_set_app_config(
+            42, // Session id
+            vec![AppConfigTlv::new(AppConfigTlvType::RangingRoundUsage, vec![0x02])],
+            vec![],
+            Ok(SetAppConfigResponse { status: StatusCode::UciStatusOk, config_status: vec![] }),
+        );
+        let (_rt, uci_manager_sync) = new_mock_uci_manager_sync(uci_manager_impl);
+
+        // The SS-TWR round usage is preserved, without sending any command.
+        assert!(session_set_mac_mode(&uci_manager_sync, 42, MAC_MODE_TWO_WAY as i32).is_ok());
+        // The DL-TDoA round usage is replaced with DS-TWR.
+        assert!(session_set_mac_mode(&uci_manager_sync, 42, MAC_MODE_TWO_WAY as i32).is_ok());
+        // Rejected without sending any command.
+        assert!(session_set_mac_mode(&uci_manager_sync, 42, 2).is_err());
+    }
+}
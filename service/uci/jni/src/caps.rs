@@ -0,0 +1,410 @@
+// Copyright 2026, The Android Open Source Project
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Decoding of the capability TLVs reported by CORE_GET_CAPS_INFO.
+//!
+//! Ids of the capability TLVs mirror com.android.server.uwb.config.CapabilityParam.
+
+use crate::app_config::decode_le_value;
+
+use uwb_uci_packets::CapTlv;
+
+/// Id of a FiRa capability, which were renumbered in FiRa 2.0.
+pub(crate) struct FiraCapId {
+    v1: u8,
+    v2: u8,
+}
+
+// FiRa capability ids:
+pub(crate) const SUPPORTED_FIRA_PHY_VERSION_RANGE: FiraCapId = FiraCapId { v1: 0x00, v2: 0x02 };
+pub(crate) const SUPPORTED_STS_CONFIG: FiraCapId = FiraCapId { v1: 0x04, v2: 0x07 };
+pub(crate) const SUPPORTED_HPRF_PARAMETER_SETS: FiraCapId = FiraCapId { v1: 0x0F, v2: 0x12 };
+pub(crate) const SUPPORTED_CHANNELS: FiraCapId = FiraCapId { v1: 0x0B, v2: 0x0E };
+const SUPPORTED_RANGING_METHOD: FiraCapId = FiraCapId { v1: 0x03, v2: 0x06 };
+
+// Android vendor capability ids, common across FiRa versions:
+const RADAR_SUPPORT: u8 = 0xB0;
+pub(crate) const SUPPORTED_MIN_SLOT_DURATION_RSTU: u8 = 0xE8;
+const SUPPORTED_MAX_RANGING_SESSIONS: u8 = 0xE9;
+
+// Bits of SUPPORTED_STS_CONFIG:
+const PROVISIONED_STS: u8 = 0x08;
+const PROVISIONED_STS_INDIVIDUAL_CONTROLEE_KEY: u8 = 0x10;
+
+// Channel of each bit of SUPPORTED_CHANNELS.
+const CHANNELS: [u8; 8] = [5, 6, 8, 9, 10, 12, 13, 14];
+
+// Bandwidth, in kHz, of the channels of SUPPORTED_CHANNELS, all 499.2 MHz wide.
+const CHANNEL_BANDWIDTH_KHZ: u32 = 499_200;
+
+// Bits of SUPPORTED_SESSION_KEY_LENGTH: 256 bits keys for dynamic STS and for provisioned STS.
+const SESSION_KEY_LENGTH_256_BITS: u8 = 0x03;
+
+// FiRa 2.0 reports the 4 bytes PHY version range with this id, where FiRa 1.x reports the 1 byte
+// supported device roles.
+const SUPPORTED_FIRA_PHY_VERSION_RANGE_VER_2_0: u8 = 0x02;
+// Only defined by FiRa 2.0.
+const SUPPORTED_SESSION_KEY_LENGTH_VER_2_0: u8 = 0x16;
+
+/// FiRa protocol version, as encoded in the version range capabilities.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(crate) struct FiraVersion {
+    pub major: u8,
+    pub minor: u8,
+}
+
+/// Range of FiRa protocol versions supported by the UWBS.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(crate) struct FiraVersionRange {
+    pub min: FiraVersion,
+    pub max: FiraVersion,
+}
+
+/// Capabilities of the UWBS commonly looked up by the framework, decoded from the capability TLVs.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub(crate) struct DeviceCapabilities {
+    pub max_ranging_sessions: Option<u32>,
+    /// RANGING_ROUND_USAGE values supported by the UWBS.
+    pub round_usages: Vec<u8>,
+    pub channels: Vec<u8>,
+    /// Capability TLVs not decoded above, in the order reported by the UWBS.
+    pub raw_remaining: Vec<CapTlv>,
+}
+
+impl DeviceCapabilities {
+    /// Decodes the capabilities from the TLVs reported by CORE_GET_CAPS_INFO. Capabilities not
+    /// advertised are None or empty.
+    pub fn from_cap_tlvs(tlvs: &[CapTlv]) -> Self {
+        let decoded_cap_ids = [
+            SUPPORTED_MAX_RANGING_SESSIONS,
+            fira_cap_id(tlvs, SUPPORTED_RANGING_METHOD),
+            fira_cap_id(tlvs, SUPPORTED_CHANNELS),
+        ];
+        Self {
+            max_ranging_sessions: find_cap_u32(tlvs, SUPPORTED_MAX_RANGING_SESSIONS),
+            round_usages: find_supported_round_usages(tlvs),
+            channels: find_supported_channels(tlvs),
+            raw_remaining: tlvs
+                .iter()
+                .filter(|tlv| !decoded_cap_ids.contains(&u8::from(tlv.t)))
+                .cloned()
+                .collect(),
+        }
+    }
+}
+
+/// Returns the value of the capability TLV with id `cap_id`, if advertised by the UWBS.
+pub(crate) fn find_cap_value(tlvs: &[CapTlv], cap_id: u8) -> Option<&[u8]> {
+    tlvs.iter().find(|tlv| u8::from(tlv.t) == cap_id).map(|tlv| tlv.v.as_slice())
+}
+
+/// Returns true if the capabilities are reported in the FiRa 2.0 layout.
+fn is_fira_v2(tlvs: &[CapTlv]) -> bool {
+    find_cap_value(tlvs, SUPPORTED_FIRA_PHY_VERSION_RANGE_VER_2_0).map_or(false, |v| v.len() == 4)
+}
+
+/// Returns the id of the FiRa capability `cap_id` matching the FiRa version of the UWBS.
+fn fira_cap_id(tlvs: &[CapTlv], cap_id: FiraCapId) -> u8 {
+    if is_fira_v2(tlvs) {
+        cap_id.v2
+    } else {
+        cap_id.v1
+    }
+}
+
+/// Returns the value of the FiRa capability TLV `cap_id`, with the id matching the FiRa version
+/// of the UWBS.
+pub(crate) fn find_fira_cap_value(tlvs: &[CapTlv], cap_id: FiraCapId) -> Option<&[u8]> {
+    find_cap_value(tlvs, fira_cap_id(tlvs, cap_id))
+}
+
+/// Returns true if the UWBS supports provisioned STS, with keys managed outside of the UWBS.
+pub(crate) fn is_provisioned_sts_supported(tlvs: &[CapTlv]) -> bool {
+    find_fira_cap_value(tlvs, SUPPORTED_STS_CONFIG)
+        .and_then(|value| value.first())
+        .map_or(false, |sts_config| sts_config & PROVISIONED_STS != 0)
+}
+
+/// Returns true if the UWBS supports provisioned STS with a sub-session key for each controlee, as
+/// sent in the multicast list updates.
+pub(crate) fn is_sub_session_key_supported(tlvs: &[CapTlv]) -> bool {
+    find_fira_cap_value(tlvs, SUPPORTED_STS_CONFIG)
+        .and_then(|value| value.first())
+        .map_or(false, |sts_config| sts_config & PROVISIONED_STS_INDIVIDUAL_CONTROLEE_KEY != 0)
+}
+
+/// Decodes a little endian integer capability of at most 4 bytes.
+pub(crate) fn find_cap_u32(tlvs: &[CapTlv], cap_id: u8) -> Option<u32> {
+    find_cap_value(tlvs, cap_id).and_then(|value| decode_le_value(value).ok())
+}
+
+/// Decodes SUPPORTED_MAX_RANGING_SESSION_NUMBER, the max number of sessions the UWBS runs at the
+/// same time.
+pub(crate) fn find_max_ranging_sessions(tlvs: &[CapTlv]) -> Option<u32> {
+    find_cap_u32(tlvs, SUPPORTED_MAX_RANGING_SESSIONS)
+}
+
+/// Decodes the range of FiRa PHY versions supported by the UWBS, encoded as the major and minor
+/// numbers of the min version followed by those of the max version.
+pub(crate) fn find_fira_phy_version_range(tlvs: &[CapTlv]) -> Option<FiraVersionRange> {
+    match *find_fira_cap_value(tlvs, SUPPORTED_FIRA_PHY_VERSION_RANGE)? {
+        [min_major, min_minor, max_major, max_minor] => Some(FiraVersionRange {
+            min: FiraVersion { major: min_major, minor: min_minor },
+            max: FiraVersion { major: max_major, minor: max_minor },
+        }),
+        _ => None,
+    }
+}
+
+/// Returns true if the UWBS can run ranging and radar sessions at the same time. Radar sessions are
+/// scheduled by the UWBS alongside its ranging sessions, so this holds as soon as RADAR_SUPPORT
+/// advertises a radar mode.
+pub(crate) fn is_concurrent_ranging_radar_supported(tlvs: &[CapTlv]) -> bool {
+    find_cap_u32(tlvs, RADAR_SUPPORT).map_or(false, |radar_modes| radar_modes != 0)
+}
+
+/// Returns true if the UWBS supports at least one HPRF parameter set.
+pub(crate) fn is_hprf_supported(tlvs: &[CapTlv]) -> bool {
+    find_fira_cap_value(tlvs, SUPPORTED_HPRF_PARAMETER_SETS)
+        .map_or(false, |value| value.iter().any(|parameter_sets| *parameter_sets != 0))
+}
+
+/// Decodes the UWB channels supported by the UWBS. Empty if not advertised.
+pub(crate) fn find_supported_channels(tlvs: &[CapTlv]) -> Vec<u8> {
+    let channels =
+        find_fira_cap_value(tlvs, SUPPORTED_CHANNELS).and_then(|value| value.first()).unwrap_or(&0);
+    CHANNELS
+        .iter()
+        .enumerate()
+        .filter(|(bit, _)| channels & (1 << bit) != 0)
+        .map(|(_, channel)| *channel)
+        .collect()
+}
+
+/// Decodes the RANGING_ROUND_USAGE values supported by the UWBS, bit N of the little endian
+/// ranging methods being set if the usage N is supported. Empty if not advertised.
+pub(crate) fn find_supported_round_usages(tlvs: &[CapTlv]) -> Vec<u8> {
+    let methods = find_fira_cap_value(tlvs, SUPPORTED_RANGING_METHOD).unwrap_or(&[]);
+    methods
+        .iter()
+        .enumerate()
+        .flat_map(|(index, byte)| {
+            (0..8).filter(move |bit| byte & (1 << bit) != 0).map(move |bit| (index * 8 + bit) as u8)
+        })
+        .collect()
+}
+
+/// Decodes the channel bandwidths, in kHz, supported by the UWBS, from its supported channels.
+/// Empty if no channel is advertised.
+pub(crate) fn find_supported_bandwidths_khz(tlvs: &[CapTlv]) -> Vec<u32> {
+    if find_supported_channels(tlvs).is_empty() {
+        vec![]
+    } else {
+        vec![CHANNEL_BANDWIDTH_KHZ]
+    }
+}
+
+/// Decodes the lengths, in bits, of the STS keys supported by the UWBS from the FiRa 2.0
+/// SUPPORTED_SESSION_KEY_LENGTH. 128 bits keys are always supported. Empty before FiRa 2.0.
+pub(crate) fn find_supported_sts_key_lengths(tlvs: &[CapTlv]) -> Vec<u32> {
+    if !is_fira_v2(tlvs) {
+        return vec![];
+    }
+    match find_cap_value(tlvs, SUPPORTED_SESSION_KEY_LENGTH_VER_2_0) {
+        Some([key_lengths, ..]) if key_lengths & SESSION_KEY_LENGTH_256_BITS != 0 => vec![128, 256],
+        _ => vec![128],
+    }
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use super::*;
+
+    use uwb_uci_packets::CapTlvType;
+
+    /// Builds a capability TLV for tests.
+    pub(crate) fn cap_tlv(cap_id: u8, v: Vec<u8>) -> CapTlv {
+        CapTlv { t: CapTlvType::try_from(cap_id).unwrap(), v }
+    }
+
+    #[test]
+    fn test_find_cap_u32() {
+        let tlvs = vec![cap_tlv(SUPPORTED_MIN_SLOT_DURATION_RSTU, vec![0x60, 0x09, 0, 0])];
+        assert_eq!(find_cap_u32(&tlvs, SUPPORTED_MIN_SLOT_DURATION_RSTU), Some(2400));
+        assert_eq!(find_cap_u32(&[], SUPPORTED_MIN_SLOT_DURATION_RSTU), None);
+    }
+
+    #[test]
+    fn test_is_provisioned_sts_supported() {
+        // FiRa 1.x: static and provisioned STS.
+        assert!(is_provisioned_sts_supported(&[cap_tlv(0x04, vec![0x09])]));
+        // FiRa 2.0: static and dynamic STS only.
+        assert!(!is_provisioned_sts_supported(&[
+            cap_tlv(0x02, vec![1, 1, 2, 0]),
+            cap_tlv(0x07, vec![0x03]),
+        ]));
+        // FiRa 2.0: provisioned STS.
+        assert!(is_provisioned_sts_supported(&[
+            cap_tlv(0x02, vec![1, 1, 2, 0]),
+            cap_tlv(0x07, vec![0x08]),
+        ]));
+        assert!(!is_provisioned_sts_supported(&[]));
+    }
+
+    #[test]
+    fn test_is_sub_session_key_supported() {
+        // FiRa 1.x: static and provisioned STS, with and without individual controlee keys.
+        assert!(is_sub_session_key_supported(&[cap_tlv(0x04, vec![0x19])]));
+        assert!(!is_sub_session_key_supported(&[cap_tlv(0x04, vec![0x09])]));
+        // FiRa 2.0: provisioned STS with individual controlee keys.
+        assert!(is_sub_session_key_supported(&[
+            cap_tlv(0x02, vec![1, 1, 2, 0]),
+            cap_tlv(0x07, vec![0x18]),
+        ]));
+        // FiRa 2.0 reports the supported device types, not the STS config, with the FiRa 1.x id.
+        assert!(!is_sub_session_key_supported(&[
+            cap_tlv(0x02, vec![1, 1, 2, 0]),
+            cap_tlv(0x04, vec![0x18]),
+        ]));
+        assert!(!is_sub_session_key_supported(&[]));
+    }
+
+    #[test]
+    fn test_find_fira_phy_version_range() {
+        let range = FiraVersionRange {
+            min: FiraVersion { major: 1, minor: 1 },
+            max: FiraVersion { major: 2, minor: 0 },
+        };
+        // FiRa 1.x: PHY version range, followed by the 1 byte device roles.
+        assert_eq!(
+            find_fira_phy_version_range(&[
+                cap_tlv(0x00, vec![1, 1, 2, 0]),
+                cap_tlv(0x02, vec![0x03]),
+            ]),
+            Some(range)
+        );
+        // FiRa 2.0.
+        assert_eq!(find_fira_phy_version_range(&[cap_tlv(0x02, vec![1, 1, 2, 0])]), Some(range));
+        assert_eq!(find_fira_phy_version_range(&[cap_tlv(0x00, vec![1, 1])]), None);
+        assert_eq!(find_fira_phy_version_range(&[]), None);
+    }
+
+    #[test]
+    fn test_is_concurrent_ranging_radar_supported() {
+        // Radar sweep samples.
+        assert!(is_concurrent_ranging_radar_supported(&[cap_tlv(RADAR_SUPPORT, vec![0x01])]));
+        assert!(!is_concurrent_ranging_radar_supported(&[cap_tlv(RADAR_SUPPORT, vec![0x00])]));
+        assert!(!is_concurrent_ranging_radar_supported(&[]));
+    }
+
+    #[test]
+    fn test_is_hprf_supported() {
+        assert!(is_hprf_supported(&[cap_tlv(0x0F, vec![0x01, 0, 0, 0, 0])]));
+        assert!(!is_hprf_supported(&[cap_tlv(0x0F, vec![0, 0, 0, 0, 0])]));
+        assert!(is_hprf_supported(&[
+            cap_tlv(0x02, vec![1, 1, 2, 0]),
+            cap_tlv(0x12, vec![0, 0x04, 0, 0, 0]),
+        ]));
+        assert!(!is_hprf_supported(&[]));
+    }
+
+    #[test]
+    fn test_find_supported_channels() {
+        let tlvs = vec![cap_tlv(SUPPORTED_CHANNELS.v1, vec![0x09])];
+        assert_eq!(find_supported_channels(&tlvs), vec![5, 9]);
+        assert!(find_supported_channels(&[]).is_empty());
+    }
+
+    #[test]
+    fn test_find_supported_round_usages() {
+        // FiRa 1.x: SS-TWR and DS-TWR deferred, and OWR for AoA.
+        assert_eq!(
+            find_supported_round_usages(&[cap_tlv(SUPPORTED_RANGING_METHOD.v1, vec![0x46])]),
+            vec![1, 2, 6]
+        );
+        // FiRa 2.0: DS-TWR non deferred and aDS-TWR.
+        assert_eq!(
+            find_supported_round_usages(&[
+                cap_tlv(0x02, vec![1, 1, 2, 0]),
+                cap_tlv(SUPPORTED_RANGING_METHOD.v2, vec![0x10, 0x01]),
+            ]),
+            vec![4, 8]
+        );
+        assert!(find_supported_round_usages(&[]).is_empty());
+    }
+
+    #[test]
+    fn test_find_supported_bandwidths_khz() {
+        let tlvs = vec![cap_tlv(SUPPORTED_CHANNELS.v1, vec![0x09])];
+        assert_eq!(find_supported_bandwidths_khz(&tlvs), vec![499_200]);
+        let tlvs =
+            vec![cap_tlv(0x02, vec![1, 1, 2, 0]), cap_tlv(SUPPORTED_CHANNELS.v2, vec![0x02])];
+        assert_eq!(find_supported_bandwidths_khz(&tlvs), vec![499_200]);
+        assert!(
+            find_supported_bandwidths_khz(&[cap_tlv(SUPPORTED_CHANNELS.v1, vec![0])]).is_empty()
+        );
+        assert!(find_supported_bandwidths_khz(&[]).is_empty());
+    }
+
+    #[test]
+    fn test_find_supported_sts_key_lengths() {
+        let fira_v2 = cap_tlv(0x02, vec![1, 1, 2, 0]);
+        for (key_lengths, expected) in
+            [(0x00, vec![128]), (0x01, vec![128, 256]), (0x02, vec![128, 256])]
+        {
+            let tlvs = vec![
+                fira_v2.clone(),
+                cap_tlv(SUPPORTED_SESSION_KEY_LENGTH_VER_2_0, vec![key_lengths]),
+            ];
+            assert_eq!(find_supported_sts_key_lengths(&tlvs), expected);
+        }
+        assert_eq!(find_supported_sts_key_lengths(&[fira_v2]), vec![128]);
+        // FiRa 1.x does not report the supported key lengths.
+        assert!(find_supported_sts_key_lengths(&[cap_tlv(0x02, vec![0x01])]).is_empty());
+        assert!(find_supported_sts_key_lengths(&[]).is_empty());
+    }
+
+    #[test]
+    fn test_device_capabilities_from_cap_tlvs() {
+        // FiRa 2.0 capabilities.
+        let tlvs = vec![
+            cap_tlv(0x00, vec![0x00, 0x04]), // Max message size
+            cap_tlv(SUPPORTED_FIRA_PHY_VERSION_RANGE.v2, vec![1, 1, 2, 0]),
+            cap_tlv(SUPPORTED_RANGING_METHOD.v2, vec![0x16]),
+            cap_tlv(SUPPORTED_CHANNELS.v2, vec![0x09]),
+            cap_tlv(SUPPORTED_MIN_SLOT_DURATION_RSTU, vec![0x60, 0x09, 0, 0]),
+            cap_tlv(SUPPORTED_MAX_RANGING_SESSIONS, vec![5, 0, 0, 0]),
+            cap_tlv(RADAR_SUPPORT, vec![0x01]),
+        ];
+        assert_eq!(
+            DeviceCapabilities::from_cap_tlvs(&tlvs),
+            DeviceCapabilities {
+                max_ranging_sessions: Some(5),
+                round_usages: vec![1, 2, 4],
+                channels: vec![5, 9],
+                raw_remaining: vec![
+                    tlvs[0].clone(),
+                    tlvs[1].clone(),
+                    tlvs[4].clone(),
+                    tlvs[6].clone(),
+                ],
+            }
+        );
+
+        let empty = DeviceCapabilities::from_cap_tlvs(&[]);
+        assert_eq!(empty.max_ranging_sessions, None);
+        assert!(empty.channels.is_empty());
+        assert!(empty.raw_remaining.is_empty());
+    }
+}
@@ -0,0 +1,1289 @@
+// Copyright 2026, The Android Open Source Project
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Implementation of ChipState.
+
+use std::collections::{HashMap, VecDeque};
+use std::sync::mpsc;
+use std::thread;
+use std::time::Duration;
+
+use crate::aoa_filter::AoaFilter;
+use crate::nlos_recovery::{NlosRecovery, NLOS_RECOVERY_HOLD_LAST_LOS, NLOS_RECOVERY_REPORT};
+use crate::notification_manager_android::TwoWayRangingMeasurement;
+use crate::retry_policy::RetryPolicy;
+use crate::timestamp::TimestampResolution;
+
+use log::warn;
+use uwb_core::error::{Error, Result};
+use uwb_core::params::GetDeviceInfoResponse;
+use uwb_core::uci::{RangingMeasurements, SessionRangeData};
+use uwb_uci_packets::{Controlee, StatusCode, UpdateMulticastListAction};
+
+// Opcodes, the GID followed by the OID, of the UCI commands recorded in the recent commands.
+pub(crate) const CORE_DEVICE_RESET: u16 = 0x0000;
+pub(crate) const CORE_GET_CAPS_INFO: u16 = 0x0003;
+pub(crate) const SESSION_INIT: u16 = 0x0100;
+pub(crate) const SESSION_DEINIT: u16 = 0x0101;
+pub(crate) const SESSION_SET_APP_CONFIG: u16 = 0x0103;
+pub(crate) const SESSION_GET_APP_CONFIG: u16 = 0x0104;
+pub(crate) const SESSION_START: u16 = 0x0200;
+pub(crate) const SESSION_STOP: u16 = 0x0201;
+
+// Session type of the Android radar sessions.
+const SESSION_TYPE_RADAR: u8 = 0xA1;
+
+// Number of recent commands kept for bug reports.
+const MAX_RECENT_COMMANDS: usize = 32;
+
+// Number of recent generic errors kept for post-mortem analysis.
+const MAX_ERROR_HISTORY: usize = 32;
+
+// Sliding window over which the measurement rate of the sessions is computed.
+const MEASUREMENT_RATE_WINDOW_MS: u64 = 5000;
+
+/// Default number of recent raw UCI responses kept for conformance logging.
+const DEFAULT_RAW_RESPONSE_CAPACITY: usize = 16;
+
+/// State of the HAL channel of the chip, distinct from the state of the UWBS.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub(crate) enum HalState {
+    #[default]
+    Closed = 0,
+    Opening = 1,
+    Open = 2,
+    Error = 3,
+}
+
+/// Outcome of a UCI command sent to the UWBS.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(crate) struct CommandRecord {
+    pub opcode: u16,
+    pub status: StatusCode,
+    pub timestamp_ms: u64,
+}
+
+/// Generic error notified by the UWBS.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(crate) struct ErrorRecord {
+    pub status: StatusCode,
+    pub timestamp_ms: u64,
+}
+
+impl ErrorRecord {
+    // Size of an encoded ErrorRecord.
+    const ENCODED_LEN: usize = 9;
+
+    /// Encodes the record as the status and the little endian timestamp.
+    fn encode(&self, buf: &mut Vec<u8>) {
+        buf.push(u8::from(self.status));
+        buf.extend_from_slice(&self.timestamp_ms.to_le_bytes());
+    }
+}
+
+/// Raw packets of the most recent UCI responses received from the UWBS, oldest first.
+#[derive(Debug)]
+struct RawResponseLog {
+    capacity: usize,
+    responses: VecDeque<Vec<u8>>,
+}
+
+impl Default for RawResponseLog {
+    fn default() -> Self {
+        Self { capacity: DEFAULT_RAW_RESPONSE_CAPACITY, responses: VecDeque::new() }
+    }
+}
+
+/// Data transferred by a session since the stats were last reset.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub(crate) struct DataThroughputStats {
+    pub bytes_sent: u64,
+    pub bytes_received: u64,
+    // Time of the reset of the stats, or of the first transfer, and of the last transfer.
+    start_ms: Option<u64>,
+    last_ms: u64,
+}
+
+impl DataThroughputStats {
+    fn on_transfer(&mut self, timestamp_ms: u64) {
+        self.start_ms.get_or_insert(timestamp_ms);
+        self.last_ms = timestamp_ms;
+    }
+
+    /// Computes the throughput, in bits per second, between the start of the stats and the last
+    /// transfer.
+    pub fn throughput_bps(&self) -> u64 {
+        match self.start_ms {
+            Some(start_ms) if self.last_ms > start_ms => {
+                (self.bytes_sent + self.bytes_received) * 8 * 1000 / (self.last_ms - start_ms)
+            }
+            _ => 0,
+        }
+    }
+}
+
+impl CommandRecord {
+    // Size of an encoded CommandRecord.
+    const ENCODED_LEN: usize = 11;
+
+    /// Encodes the record as the little endian opcode, the status and the little endian timestamp.
+    fn encode(&self, buf: &mut Vec<u8>) {
+        buf.extend_from_slice(&self.opcode.to_le_bytes());
+        buf.push(u8::from(self.status));
+        buf.extend_from_slice(&self.timestamp_ms.to_le_bytes());
+    }
+}
+
+/// Observer of the latency of the UCI commands, called with the opcode of each command once its
+/// response is received. It is called on a thread of its own, in the order of the commands.
+pub(crate) type LatencyObserver = Box<dyn Fn(u16, Duration) + Send>;
+
+/// State tracked by the JNI layer for a single UWB chip. It is shared between the
+/// NotificationManagerAndroid of the chip, which updates it on notifications, and the JNI calls.
+#[derive(Default)]
+pub(crate) struct ChipState {
+    device_info: Option<GetDeviceInfoResponse>,
+    session_types: HashMap<u32, u8>,
+    completed_round_counts: HashMap<u32, u64>,
+    min_report_intervals: HashMap<u32, u64>,
+    // Time of the last ranging notification delivered for each throttled session.
+    last_report_times: HashMap<u32, u64>,
+    recent_commands: VecDeque<CommandRecord>,
+    error_history: VecDeque<ErrorRecord>,
+    raw_responses: RawResponseLog,
+    hal_state: HalState,
+    data_throughput_stats: HashMap<u32, DataThroughputStats>,
+    batch_notification_sizes: HashMap<u32, usize>,
+    pending_range_data: HashMap<u32, SessionRangeData>,
+    app_config_max_zero_padding: usize,
+    // Queue of the latencies, handed over to the thread of the latency observer if any.
+    latency_samples: Option<mpsc::Sender<(u16, Duration)>>,
+    controlee_measurements: HashMap<u32, HashMap<Vec<u8>, TwoWayRangingMeasurement>>,
+    aoa_filters: HashMap<u32, AoaFilter>,
+    nlos_recoveries: HashMap<u32, NlosRecovery>,
+    distance_precisions: HashMap<u32, u16>,
+    mac_address_modes: HashMap<u32, u8>,
+    multicast_lists: HashMap<u32, Vec<Controlee>>,
+    // Time of the recent ranging notifications of each session, and their number of measurements.
+    measurement_times: HashMap<u32, VecDeque<(u64, usize)>>,
+    retry_policy: RetryPolicy,
+    timestamp_resolution: Option<TimestampResolution>,
+}
+
+impl ChipState {
+    /// Updates the state on a ranging notification. Each notification completes a ranging round.
+    pub fn on_range_data(&mut self, range_data: &SessionRangeData) {
+        // session_token has already been mapped to session_id by uci layer.
+        *self.completed_round_counts.entry(range_data.session_token).or_default() += 1;
+
+        let measurements: Vec<TwoWayRangingMeasurement> = match &range_data.ranging_measurements {
+            RangingMeasurements::ShortAddressTwoWay(v) => {
+                v.iter().cloned().map(TwoWayRangingMeasurement::from).collect()
+            }
+            RangingMeasurements::ExtendedAddressTwoWay(v) => {
+                v.iter().cloned().map(TwoWayRangingMeasurement::from).collect()
+            }
+            _ => return,
+        };
+        let controlee_measurements =
+            self.controlee_measurements.entry(range_data.session_token).or_default();
+        for measurement in measurements.into_iter() {
+            controlee_measurements
+                .insert(measurement.mac_address.clone().into_ne_bytes(), measurement);
+        }
+    }
+
+    /// Records the time of a ranging notification, for the measurement rate of the session.
+    pub fn on_range_data_received(&mut self, range_data: &SessionRangeData, timestamp_ms: u64) {
+        let measurement_times = self.measurement_times.entry(range_data.session_token).or_default();
+        measurement_times
+            .push_back((timestamp_ms, measurement_count(&range_data.ranging_measurements)));
+        while measurement_times
+            .front()
+            .map_or(false, |(t, _)| t + MEASUREMENT_RATE_WINDOW_MS <= timestamp_ms)
+        {
+            measurement_times.pop_front();
+        }
+    }
+
+    /// Computes the number of measurements per second of the session over the last 5 seconds
+    /// before `now_ms`. 0 without recent measurement.
+    pub fn measurement_rate(&self, session_id: u32, now_ms: u64) -> f32 {
+        let count: usize = self.measurement_times.get(&session_id).map_or(0, |times| {
+            times
+                .iter()
+                .filter(|(t, _)| *t <= now_ms && t + MEASUREMENT_RATE_WINDOW_MS > now_ms)
+                .map(|(_, count)| count)
+                .sum()
+        });
+        count as f32 * 1000.0 / MEASUREMENT_RATE_WINDOW_MS as f32
+    }
+
+    /// Gets the last two way ranging measurement of the controlee with the MAC address `address`,
+    /// in native byte order, in the session.
+    pub fn controlee_measurement(
+        &self,
+        session_id: u32,
+        address: &[u8],
+    ) -> Option<TwoWayRangingMeasurement> {
+        self.controlee_measurements.get(&session_id)?.get(address).cloned()
+    }
+
+    /// Sets the policy of the retry of the UCI commands the UWBS asks to retry.
+    pub fn set_retry_policy(&mut self, retry_policy: RetryPolicy) {
+        self.retry_policy = retry_policy;
+    }
+
+    /// Gets the policy of the retry of the UCI commands the UWBS asks to retry.
+    pub fn retry_policy(&self) -> RetryPolicy {
+        self.retry_policy
+    }
+
+    /// Caches the resolution of the UWBS timestamps, measured once per chip.
+    pub fn on_timestamp_resolution(&mut self, timestamp_resolution: TimestampResolution) {
+        self.timestamp_resolution = Some(timestamp_resolution);
+    }
+
+    /// Gets the resolution of the UWBS timestamps, if already measured.
+    pub fn timestamp_resolution(&self) -> Option<TimestampResolution> {
+        self.timestamp_resolution
+    }
+
+    /// Sets the state of the HAL channel of the chip.
+    pub fn set_hal_state(&mut self, hal_state: HalState) {
+        self.hal_state = hal_state;
+    }
+
+    /// Gets the state of the HAL channel of the chip.
+    pub fn hal_state(&self) -> HalState {
+        self.hal_state
+    }
+
+    /// Caches the device info reported by the UWBS when the HAL is opened.
+    pub fn on_device_info(&mut self, device_info: GetDeviceInfoResponse) {
+        self.device_info = Some(device_info);
+    }
+
+    /// Gets the device info reported by the UWBS when the HAL was last opened, if any.
+    pub fn cached_device_info(&self) -> Option<GetDeviceInfoResponse> {
+        self.device_info.clone()
+    }
+
+    /// Records the type of a session once initialized.
+    pub fn on_session_init(&mut self, session_id: u32, session_type: u8) {
+        self.session_types.insert(session_id, session_type);
+    }
+
+    /// Clears the state of a session once deinitialized.
+    pub fn on_session_deinit(&mut self, session_id: u32) {
+        self.session_types.remove(&session_id);
+        self.completed_round_counts.remove(&session_id);
+        self.data_throughput_stats.remove(&session_id);
+        self.batch_notification_sizes.remove(&session_id);
+        self.pending_range_data.remove(&session_id);
+        self.nlos_recoveries.remove(&session_id);
+        self.distance_precisions.remove(&session_id);
+        self.min_report_intervals.remove(&session_id);
+        self.last_report_times.remove(&session_id);
+        self.controlee_measurements.remove(&session_id);
+        self.aoa_filters.remove(&session_id);
+        self.mac_address_modes.remove(&session_id);
+        self.multicast_lists.remove(&session_id);
+        self.measurement_times.remove(&session_id);
+    }
+
+    /// Gets the controlees not yet in the multicast list of the session, without duplicates.
+    /// Controlees are identified by their short address.
+    pub fn controlees_to_add(&self, session_id: u32, controlees: Vec<Controlee>) -> Vec<Controlee> {
+        let mut to_add: Vec<Controlee> = vec![];
+        for controlee in controlees.into_iter() {
+            if !self.is_in_multicast_list(session_id, &controlee)
+                && !to_add.iter().any(|c| c.short_address == controlee.short_address)
+            {
+                to_add.push(controlee);
+            }
+        }
+        to_add
+    }
+
+    /// Gets the controlees in the multicast list of the session, without duplicates. The other
+    /// controlees are ignored.
+    pub fn controlees_to_remove(
+        &self,
+        session_id: u32,
+        controlees: Vec<Controlee>,
+    ) -> Vec<Controlee> {
+        let mut to_remove: Vec<Controlee> = vec![];
+        for controlee in controlees.into_iter() {
+            if !self.is_in_multicast_list(session_id, &controlee) {
+                warn!(
+                    "UCI JNI: controlee {:?} not in the multicast list of session {}",
+                    controlee.short_address, session_id
+                );
+            } else if !to_remove.iter().any(|c| c.short_address == controlee.short_address) {
+                to_remove.push(controlee);
+            }
+        }
+        to_remove
+    }
+
+    /// Updates the multicast list of the session once the update is accepted by the UWBS.
+    pub fn on_multicast_list_updated(
+        &mut self,
+        session_id: u32,
+        action: UpdateMulticastListAction,
+        controlees: &[Controlee],
+    ) {
+        let multicast_list = self.multicast_lists.entry(session_id).or_default();
+        multicast_list.retain(|c| !controlees.iter().any(|u| u.short_address == c.short_address));
+        if action != UpdateMulticastListAction::RemoveControlee {
+            multicast_list.extend(controlees.iter().cloned());
+        }
+    }
+
+    /// Gets the controlees in the multicast list of the session, in the order they were added.
+    pub fn multicast_list(&self, session_id: u32) -> Vec<Controlee> {
+        self.multicast_lists.get(&session_id).cloned().unwrap_or_default()
+    }
+
+    fn is_in_multicast_list(&self, session_id: u32, controlee: &Controlee) -> bool {
+        self.multicast_lists
+            .get(&session_id)
+            .map_or(false, |l| l.iter().any(|c| c.short_address == controlee.short_address))
+    }
+
+    /// Clears the state of all the sessions, once the UWBS dropped them on a reset or when the HAL
+    /// is closed.
+    pub fn on_all_sessions_deinit(&mut self) {
+        for session_id in self.session_ids() {
+            self.on_session_deinit(session_id);
+        }
+    }
+
+    /// Caches the MAC_ADDRESS_MODE of the session once set, or drops it with None when it is
+    /// unknown, as when the UWBS rejected an app configuration setting it.
+    pub fn on_mac_address_mode_set(&mut self, session_id: u32, mode: Option<u8>) {
+        match mode {
+            Some(mode) => self.mac_address_modes.insert(session_id, mode),
+            None => self.mac_address_modes.remove(&session_id),
+        };
+    }
+
+    /// Gets the cached MAC_ADDRESS_MODE of the session, if known.
+    pub fn mac_address_mode(&self, session_id: u32) -> Option<u8> {
+        self.mac_address_modes.get(&session_id).copied()
+    }
+
+    /// Sets the minimum interval in ms between two ranging notifications of the session delivered
+    /// to the Java side. 0 disables the throttling.
+    pub fn set_min_report_interval(&mut self, session_id: u32, interval_ms: u64) {
+        if interval_ms == 0 {
+            self.min_report_intervals.remove(&session_id);
+        } else {
+            self.min_report_intervals.insert(session_id, interval_ms);
+        }
+        self.last_report_times.remove(&session_id);
+    }
+
+    /// Gets the minimum interval in ms between two ranging notifications of the session delivered
+    /// to the Java side, 0 if not throttled.
+    pub fn min_report_interval(&self, session_id: u32) -> u64 {
+        self.min_report_intervals.get(&session_id).copied().unwrap_or(0)
+    }
+
+    /// Returns the range data to deliver, or None to drop it if the last ranging notification of
+    /// the session was delivered less than its minimum report interval before `timestamp_ms`.
+    pub fn throttle_range_data(
+        &mut self,
+        range_data: SessionRangeData,
+        timestamp_ms: u64,
+    ) -> Option<SessionRangeData> {
+        // session_token has already been mapped to session_id by uci layer.
+        let session_id = range_data.session_token;
+        let interval_ms = self.min_report_interval(session_id);
+        if interval_ms == 0 {
+            return Some(range_data);
+        }
+        match self.last_report_times.get(&session_id) {
+            Some(last_ms) if timestamp_ms < last_ms + interval_ms => None,
+            _ => {
+                self.last_report_times.insert(session_id, timestamp_ms);
+                Some(range_data)
+            }
+        }
+    }
+
+    /// Gets the ids of the sessions initialized and not yet deinitialized.
+    pub fn session_ids(&self) -> Vec<u32> {
+        self.session_types.keys().copied().collect()
+    }
+
+    /// Returns true if the session was initialized as a radar session.
+    pub fn is_radar_session(&self, session_id: u32) -> bool {
+        self.session_types.get(&session_id) == Some(&SESSION_TYPE_RADAR)
+    }
+
+    /// Gets the number of ranging rounds completed by the session since its initialization.
+    pub fn completed_round_count(&self, session_id: u32) -> u64 {
+        self.completed_round_counts.get(&session_id).copied().unwrap_or(0)
+    }
+
+    /// Sets the number of ranging measurements of the session aggregated in a single range data
+    /// notification. A size of 1 disables the batching.
+    pub fn set_batch_notification_size(&mut self, session_id: u32, size: usize) -> Result<()> {
+        if size == 0 {
+            return Err(Error::BadParameters);
+        }
+        self.batch_notification_sizes.insert(session_id, size);
+        Ok(())
+    }
+
+    /// Sets the number of ranging measurements of each controlee of the session averaged to smooth
+    /// their AoA. A window of 1 disables the filter.
+    pub fn set_aoa_filter_window(&mut self, session_id: u32, window: usize) -> Result<()> {
+        let filter = AoaFilter::new(window)?;
+        if window == 1 {
+            self.aoa_filters.remove(&session_id);
+        } else {
+            self.aoa_filters.insert(session_id, filter);
+        }
+        Ok(())
+    }
+
+    /// Smooths the AoA of the ranging measurements if a filter is set for the session.
+    pub fn filter_aoa(&mut self, range_data: &mut SessionRangeData) {
+        // session_token has already been mapped to session_id by uci layer.
+        if let Some(filter) = self.aoa_filters.get_mut(&range_data.session_token) {
+            filter.filter(&mut range_data.ranging_measurements);
+        }
+    }
+
+    /// Sets how the ranging measurements of the session recover after a NLOS detection, one of the
+    /// NLOS_RECOVERY_* modes. Unknown modes are rejected.
+    pub fn set_nlos_recovery_mode(&mut self, session_id: u32, mode: u8) -> Result<()> {
+        match NlosRecovery::new(mode)? {
+            Some(recovery) => self.nlos_recoveries.insert(session_id, recovery),
+            None => self.nlos_recoveries.remove(&session_id),
+        };
+        Ok(())
+    }
+
+    /// Gets the NLOS recovery mode of the session, NLOS_RECOVERY_REPORT by default.
+    pub fn nlos_recovery_mode(&self, session_id: u32) -> u8 {
+        if self.nlos_recoveries.contains_key(&session_id) {
+            NLOS_RECOVERY_HOLD_LAST_LOS
+        } else {
+            NLOS_RECOVERY_REPORT
+        }
+    }
+
+    /// Holds the last ranging measurements in line of sight in place of the NLOS ones if the
+    /// session recovers from NLOS.
+    pub fn recover_nlos(&mut self, range_data: &mut SessionRangeData) {
+        // session_token has already been mapped to session_id by uci layer.
+        if let Some(recovery) = self.nlos_recoveries.get_mut(&range_data.session_token) {
+            recovery.recover(&mut range_data.ranging_measurements);
+        }
+    }
+
+    /// Sets the precision, in cm, of the distances of the session: they are rounded to the nearest
+    /// multiple of it. A precision of 1 leaves the distances as reported by the UWBS.
+    pub fn set_distance_precision(&mut self, session_id: u32, precision_cm: u16) -> Result<()> {
+        match precision_cm {
+            0 => return Err(Error::BadParameters),
+            1 => self.distance_precisions.remove(&session_id),
+            _ => self.distance_precisions.insert(session_id, precision_cm),
+        };
+        Ok(())
+    }
+
+    /// Gets the precision, in cm, of the distances of the session, 1 by default.
+    pub fn distance_precision(&self, session_id: u32) -> u16 {
+        self.distance_precisions.get(&session_id).copied().unwrap_or(1)
+    }
+
+    /// Rounds the distances of the successful two way ranging measurements to the precision of the
+    /// session.
+    pub fn round_distances(&self, range_data: &mut SessionRangeData) {
+        // session_token has already been mapped to session_id by uci layer.
+        let precision_cm = self.distance_precision(range_data.session_token);
+        if precision_cm == 1 {
+            return;
+        }
+        match &mut range_data.ranging_measurements {
+            RangingMeasurements::ShortAddressTwoWay(v) => {
+                for m in v.iter_mut().filter(|m| m.status == StatusCode::UciStatusOk) {
+                    m.distance = round_distance(m.distance, precision_cm);
+                }
+            }
+            RangingMeasurements::ExtendedAddressTwoWay(v) => {
+                for m in v.iter_mut().filter(|m| m.status == StatusCode::UciStatusOk) {
+                    m.distance = round_distance(m.distance, precision_cm);
+                }
+            }
+            _ => {}
+        }
+    }
+
+    /// Aggregates the ranging measurements of the session, and returns the range data to deliver
+    /// once the batch is complete. The other fields of the range data are the ones of the last
+    /// notification of the batch. A pending batch is delivered as is if the type of the ranging
+    /// measurements changes.
+    pub fn batch_range_data(
+        &mut self,
+        mut range_data: SessionRangeData,
+    ) -> Option<SessionRangeData> {
+        // session_token has already been mapped to session_id by uci layer.
+        let session_id = range_data.session_token;
+        let batch_size = self.batch_notification_sizes.get(&session_id).copied().unwrap_or(1);
+        if batch_size <= 1 {
+            return Some(range_data);
+        }
+        let batch = match self.pending_range_data.remove(&session_id) {
+            Some(mut pending) => {
+                if !append_measurements(
+                    &mut pending.ranging_measurements,
+                    &mut range_data.ranging_measurements,
+                ) {
+                    self.pending_range_data.insert(session_id, range_data);
+                    return Some(pending);
+                }
+                range_data.ranging_measurements = pending.ranging_measurements;
+                range_data
+            }
+            None => range_data,
+        };
+        if measurement_count(&batch.ranging_measurements) >= batch_size {
+            return Some(batch);
+        }
+        self.pending_range_data.insert(session_id, batch);
+        None
+    }
+
+    /// Records the outcome of a UCI command. Only the most recent commands are kept.
+    pub fn on_command(&mut self, opcode: u16, status: StatusCode, timestamp_ms: u64) {
+        if self.recent_commands.len() == MAX_RECENT_COMMANDS {
+            self.recent_commands.pop_front();
+        }
+        self.recent_commands.push_back(CommandRecord { opcode, status, timestamp_ms });
+    }
+
+    /// Records a generic error notified by the UWBS. Only the most recent errors are kept.
+    pub fn on_generic_error(&mut self, status: StatusCode, timestamp_ms: u64) {
+        if self.error_history.len() == MAX_ERROR_HISTORY {
+            self.error_history.pop_front();
+        }
+        self.error_history.push_back(ErrorRecord { status, timestamp_ms });
+    }
+
+    /// Records the raw packet of a UCI response. Only the most recent responses are kept.
+    pub fn on_raw_response(&mut self, packet: Vec<u8>) {
+        let raw_responses = &mut self.raw_responses;
+        if raw_responses.capacity == 0 {
+            return;
+        }
+        if raw_responses.responses.len() == raw_responses.capacity {
+            raw_responses.responses.pop_front();
+        }
+        raw_responses.responses.push_back(packet);
+    }
+
+    /// Sets the number of raw UCI responses kept, dropping the oldest ones beyond it.
+    pub fn set_raw_response_capacity(&mut self, capacity: usize) {
+        let raw_responses = &mut self.raw_responses;
+        let excess = raw_responses.responses.len().saturating_sub(capacity);
+        raw_responses.responses.drain(..excess);
+        raw_responses.capacity = capacity;
+    }
+
+    /// Gets the raw packets of the last `count` UCI responses, from the oldest to the most recent
+    /// one.
+    pub fn last_raw_responses(&self, count: usize) -> Vec<Vec<u8>> {
+        let responses = &self.raw_responses.responses;
+        responses.iter().skip(responses.len().saturating_sub(count)).cloned().collect()
+    }
+
+    /// Sets the observer of the latency of the UCI commands, or removes it if None. The previous
+    /// observer, if any, is still notified of the latencies queued before.
+    pub fn set_latency_observer(&mut self, latency_observer: Option<LatencyObserver>) {
+        // Dropping the previous sender ends the thread of the previous observer once its queue is
+        // drained.
+        self.latency_samples = latency_observer.map(|latency_observer| {
+            let (sender, receiver) = mpsc::channel();
+            thread::spawn(move || {
+                for (opcode, latency) in receiver {
+                    latency_observer(opcode, latency);
+                }
+            });
+            sender
+        });
+    }
+
+    /// Queues the latency of a UCI command for the latency observer, if any. The observer is not
+    /// called under the lock of the ChipState.
+    pub fn on_command_latency(&self, opcode: u16, latency: Duration) {
+        if let Some(latency_samples) = &self.latency_samples {
+            if latency_samples.send((opcode, latency)).is_err() {
+                warn!("Latency observer is gone, dropping latency of command {:#06x}", opcode);
+            }
+        }
+    }
+
+    /// Updates the data stats of the session on a data packet sent to the UWBS.
+    pub fn on_data_sent(&mut self, session_id: u32, len: usize, timestamp_ms: u64) {
+        let stats = self.data_throughput_stats.entry(session_id).or_default();
+        stats.bytes_sent += len as u64;
+        stats.on_transfer(timestamp_ms);
+    }
+
+    /// Updates the data stats of the session on a data packet received from the UWBS.
+    pub fn on_data_received(&mut self, session_id: u32, len: usize, timestamp_ms: u64) {
+        let stats = self.data_throughput_stats.entry(session_id).or_default();
+        stats.bytes_received += len as u64;
+        stats.on_transfer(timestamp_ms);
+    }
+
+    /// Gets the data stats of the session since they were last reset.
+    pub fn data_throughput_stats(&self, session_id: u32) -> DataThroughputStats {
+        self.data_throughput_stats.get(&session_id).copied().unwrap_or_default()
+    }
+
+    /// Resets the data stats of the session. The throughput is then computed from `timestamp_ms`.
+    pub fn reset_data_throughput_stats(&mut self, session_id: u32, timestamp_ms: u64) {
+        self.data_throughput_stats.insert(
+            session_id,
+            DataThroughputStats { start_ms: Some(timestamp_ms), ..Default::default() },
+        );
+    }
+
+    /// Sets the number of zero bytes allowed after the app configuration TLVs given to the chip.
+    pub fn set_app_config_max_zero_padding(&mut self, max_zero_padding: usize) {
+        self.app_config_max_zero_padding = max_zero_padding;
+    }
+
+    /// Gets the number of zero bytes allowed after the app configuration TLVs given to the chip,
+    /// none by default.
+    pub fn app_config_max_zero_padding(&self) -> usize {
+        self.app_config_max_zero_padding
+    }
+
+    /// Encodes the recent commands, from the oldest to the most recent one.
+    pub fn encode_recent_commands(&self) -> Vec<u8> {
+        let mut buf = Vec::with_capacity(self.recent_commands.len() * CommandRecord::ENCODED_LEN);
+        for record in &self.recent_commands {
+            record.encode(&mut buf);
+        }
+        buf
+    }
+
+    /// Encodes the recent generic errors, from the oldest to the most recent one.
+    pub fn encode_error_history(&self) -> Vec<u8> {
+        let mut buf = Vec::with_capacity(self.error_history.len() * ErrorRecord::ENCODED_LEN);
+        for record in &self.error_history {
+            record.encode(&mut buf);
+        }
+        buf
+    }
+}
+
+fn measurement_count(measurements: &RangingMeasurements) -> usize {
+    match measurements {
+        RangingMeasurements::ShortAddressTwoWay(v) => v.len(),
+        RangingMeasurements::ExtendedAddressTwoWay(v) => v.len(),
+        RangingMeasurements::ShortAddressOwrAoa(v) => v.len(),
+        RangingMeasurements::ExtendedAddressOwrAoa(v) => v.len(),
+        RangingMeasurements::ShortAddressDltdoa(v) => v.len(),
+        RangingMeasurements::ExtendedAddressDltdoa(v) => v.len(),
+    }
+}
+
+/// Appends `measurements` to `batch`. Returns false if their types differ.
+fn append_measurements(
+    batch: &mut RangingMeasurements,
+    measurements: &mut RangingMeasurements,
+) -> bool {
+    match (batch, measurements) {
+        (
+            RangingMeasurements::ShortAddressTwoWay(a),
+            RangingMeasurements::ShortAddressTwoWay(b),
+        ) => a.append(b),
+        (
+            RangingMeasurements::ExtendedAddressTwoWay(a),
+            RangingMeasurements::ExtendedAddressTwoWay(b),
+        ) => a.append(b),
+        (
+            RangingMeasurements::ShortAddressOwrAoa(a),
+            RangingMeasurements::ShortAddressOwrAoa(b),
+        ) => a.append(b),
+        (
+            RangingMeasurements::ExtendedAddressOwrAoa(a),
+            RangingMeasurements::ExtendedAddressOwrAoa(b),
+        ) => a.append(b),
+        (
+            RangingMeasurements::ShortAddressDltdoa(a),
+            RangingMeasurements::ShortAddressDltdoa(b),
+        ) => a.append(b),
+        (
+            RangingMeasurements::ExtendedAddressDltdoa(a),
+            RangingMeasurements::ExtendedAddressDltdoa(b),
+        ) => a.append(b),
+        _ => return false,
+    }
+    true
+}
+
+// Rounds the distance to the nearest multiple of the precision, down if it does not fit.
+fn round_distance(distance_cm: u16, precision_cm: u16) -> u16 {
+    let (distance_cm, precision_cm) = (u32::from(distance_cm), u32::from(precision_cm));
+    let rounded = (distance_cm + precision_cm / 2) / precision_cm * precision_cm;
+    if rounded > u32::from(u16::MAX) {
+        (rounded - precision_cm) as u16
+    } else {
+        rounded as u16
+    }
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use super::*;
+
+    use uwb_uci_packets::{RangingMeasurementType, ShortAddressTwoWayRangingMeasurement};
+
+    /// Builds a two way ranging notification for tests.
+    pub(crate) fn range_data(
+        session_token: u32,
+        ranging_measurements: RangingMeasurements,
+    ) -> SessionRangeData {
+        SessionRangeData {
+            sequence_number: 1,
+            session_token,
+            current_ranging_interval_ms: 200,
+            ranging_measurement_type: RangingMeasurementType::TwoWay,
+            ranging_measurements,
+            rcr_indicator: 0,
+            raw_ranging_data: vec![],
+        }
+    }
+
+    fn two_way_measurement(mac_address: u16) -> ShortAddressTwoWayRangingMeasurement {
+        ShortAddressTwoWayRangingMeasurement {
+            mac_address,
+            status: StatusCode::UciStatusOk,
+            nlos: 0,
+            distance: 100,
+            aoa_azimuth: 0,
+            aoa_azimuth_fom: 0,
+            aoa_elevation: 0,
+            aoa_elevation_fom: 0,
+            aoa_destination_azimuth: 0,
+            aoa_destination_azimuth_fom: 0,
+            aoa_destination_elevation: 0,
+            aoa_destination_elevation_fom: 0,
+            slot_index: 0,
+            rssi: 0,
+        }
+    }
+
+    #[test]
+    fn test_throttle_range_data() {
+        let mut chip_state = ChipState::default();
+        let data = range_data(42, RangingMeasurements::ShortAddressTwoWay(vec![]));
+        // Throttling is disabled by default.
+        assert_eq!(chip_state.min_report_interval(42), 0);
+        for timestamp_ms in [1000, 1001] {
+            assert!(chip_state.throttle_range_data(data.clone(), timestamp_ms).is_some());
+        }
+
+        chip_state.set_min_report_interval(42, 200);
+        assert_eq!(chip_state.min_report_interval(42), 200);
+        assert!(chip_state.throttle_range_data(data.clone(), 1000).is_some());
+        assert!(chip_state.throttle_range_data(data.clone(), 1100).is_none());
+        assert!(chip_state.throttle_range_data(data.clone(), 1199).is_none());
+        assert!(chip_state.throttle_range_data(data.clone(), 1200).is_some());
+        // Other sessions are not throttled.
+        let other_data = range_data(43, RangingMeasurements::ShortAddressTwoWay(vec![]));
+        assert!(chip_state.throttle_range_data(other_data, 1201).is_some());
+
+        chip_state.set_min_report_interval(42, 0);
+        assert!(chip_state.throttle_range_data(data, 1201).is_some());
+
+        chip_state.set_min_report_interval(42, 200);
+        chip_state.on_session_deinit(42);
+        assert_eq!(chip_state.min_report_interval(42), 0);
+    }
+
+    #[test]
+    fn test_completed_round_count() {
+        let mut chip_state = ChipState::default();
+        for _ in 0..3 {
+            chip_state
+                .on_range_data(&range_data(42, RangingMeasurements::ShortAddressTwoWay(vec![])));
+        }
+        chip_state.on_range_data(&range_data(43, RangingMeasurements::ShortAddressTwoWay(vec![])));
+        assert_eq!(chip_state.completed_round_count(42), 3);
+        assert_eq!(chip_state.completed_round_count(43), 1);
+        assert_eq!(chip_state.completed_round_count(44), 0);
+
+        chip_state.on_session_deinit(42);
+        assert_eq!(chip_state.completed_round_count(42), 0);
+        assert_eq!(chip_state.completed_round_count(43), 1);
+    }
+
+    #[test]
+    fn test_recent_commands() {
+        let mut chip_state = ChipState::default();
+        chip_state.on_command(SESSION_INIT, StatusCode::UciStatusOk, 1000);
+        chip_state.on_command(SESSION_START, StatusCode::UciStatusFailed, 0x0102030405);
+        assert_eq!(
+            chip_state.encode_recent_commands(),
+            vec![
+                0x00, 0x01, 0x00, 0xe8, 0x03, 0, 0, 0, 0, 0, 0, // SESSION_INIT
+                0x00, 0x02, 0x02, 0x05, 0x04, 0x03, 0x02, 0x01, 0, 0, 0, // SESSION_START
+            ]
+        );
+
+        for timestamp_ms in 0..MAX_RECENT_COMMANDS as u64 {
+            chip_state.on_command(SESSION_SET_APP_CONFIG, StatusCode::UciStatusOk, timestamp_ms);
+        }
+        let recent_commands = chip_state.encode_recent_commands();
+        assert_eq!(recent_commands.len(), MAX_RECENT_COMMANDS * CommandRecord::ENCODED_LEN);
+        // The oldest commands were dropped.
+        assert_eq!(&recent_commands[..3], &[0x03, 0x01, 0x00]);
+        assert_eq!(&recent_commands[3..11], &0u64.to_le_bytes());
+    }
+
+    #[test]
+    fn test_error_history() {
+        let mut chip_state = ChipState::default();
+        assert!(chip_state.encode_error_history().is_empty());
+
+        chip_state.on_generic_error(StatusCode::UciStatusFailed, 1000);
+        chip_state.on_generic_error(StatusCode::UciStatusSyntaxError, 0x0102030405);
+        chip_state.on_generic_error(StatusCode::UciStatusCommandRetry, 2000);
+        assert_eq!(
+            chip_state.encode_error_history(),
+            vec![
+                0x02, 0xe8, 0x03, 0, 0, 0, 0, 0, 0, // UCI_STATUS_FAILED
+                0x03, 0x05, 0x04, 0x03, 0x02, 0x01, 0, 0, 0, // UCI_STATUS_SYNTAX_ERROR
+                0x0A, 0xd0, 0x07, 0, 0, 0, 0, 0, 0, // UCI_STATUS_COMMAND_RETRY
+            ]
+        );
+
+        for timestamp_ms in 0..MAX_ERROR_HISTORY as u64 {
+            chip_state.on_generic_error(StatusCode::UciStatusFailed, timestamp_ms);
+        }
+        let error_history = chip_state.encode_error_history();
+        assert_eq!(error_history.len(), MAX_ERROR_HISTORY * ErrorRecord::ENCODED_LEN);
+        // The oldest errors were dropped.
+        assert_eq!(&error_history[..9], &[0x02, 0, 0, 0, 0, 0, 0, 0, 0]);
+    }
+
+    #[test]
+    fn test_raw_responses() {
+        let mut chip_state = ChipState::default();
+        assert_eq!(chip_state.raw_responses.capacity, DEFAULT_RAW_RESPONSE_CAPACITY);
+        chip_state.set_raw_response_capacity(3);
+        assert!(chip_state.last_raw_responses(3).is_empty());
+
+        for i in 0..4u8 {
+            chip_state.on_raw_response(vec![0x40, i, 0x00, 0x01, 0x00]);
+        }
+        // The oldest response was dropped.
+        assert_eq!(
+            chip_state.last_raw_responses(3),
+            vec![
+                vec![0x40, 1, 0x00, 0x01, 0x00],
+                vec![0x40, 2, 0x00, 0x01, 0x00],
+                vec![0x40, 3, 0x00, 0x01, 0x00],
+            ]
+        );
+        assert_eq!(chip_state.last_raw_responses(1), vec![vec![0x40, 3, 0x00, 0x01, 0x00]]);
+        assert_eq!(chip_state.last_raw_responses(10).len(), 3);
+        assert!(chip_state.last_raw_responses(0).is_empty());
+
+        // Lowering the capacity drops the oldest responses.
+        chip_state.set_raw_response_capacity(1);
+        assert_eq!(chip_state.last_raw_responses(3), vec![vec![0x40, 3, 0x00, 0x01, 0x00]]);
+
+        chip_state.set_raw_response_capacity(0);
+        chip_state.on_raw_response(vec![0x40, 0x00, 0x00, 0x01, 0x00]);
+        assert!(chip_state.last_raw_responses(1).is_empty());
+    }
+
+    #[test]
+    fn test_data_throughput_stats() {
+        let mut chip_state = ChipState::default();
+        assert_eq!(chip_state.data_throughput_stats(42).throughput_bps(), 0);
+
+        chip_state.on_data_sent(42, 1000, 1000);
+        assert_eq!(chip_state.data_throughput_stats(42).throughput_bps(), 0);
+        chip_state.on_data_received(42, 500, 1500);
+        chip_state.on_data_sent(42, 1000, 2000);
+        let stats = chip_state.data_throughput_stats(42);
+        assert_eq!(stats.bytes_sent, 2000);
+        assert_eq!(stats.bytes_received, 500);
+        // 2500 bytes in 1 second.
+        assert_eq!(stats.throughput_bps(), 20000);
+
+        chip_state.reset_data_throughput_stats(42, 3000);
+        assert_eq!(chip_state.data_throughput_stats(42).bytes_sent, 0);
+        chip_state.on_data_received(42, 250, 3500);
+        // 250 bytes in 0.5 second since the reset.
+        assert_eq!(chip_state.data_throughput_stats(42).throughput_bps(), 4000);
+
+        chip_state.on_session_deinit(42);
+        assert_eq!(chip_state.data_throughput_stats(42), DataThroughputStats::default());
+    }
+
+    #[test]
+    fn test_batch_range_data() {
+        let mut chip_state = ChipState::default();
+        assert!(chip_state.set_batch_notification_size(42, 0).is_err());
+        // Batching is disabled by default.
+        let data =
+            range_data(42, RangingMeasurements::ShortAddressTwoWay(vec![two_way_measurement(1)]));
+        assert_eq!(chip_state.batch_range_data(data.clone()), Some(data));
+
+        chip_state.set_batch_notification_size(42, 3).unwrap();
+        for mac_address in 1..3 {
+            assert_eq!(
+                chip_state.batch_range_data(range_data(
+                    42,
+                    RangingMeasurements::ShortAddressTwoWay(vec![two_way_measurement(mac_address)])
+                )),
+                None
+            );
+        }
+        assert_eq!(
+            chip_state.batch_range_data(range_data(
+                42,
+                RangingMeasurements::ShortAddressTwoWay(vec![two_way_measurement(3)])
+            )),
+            Some(range_data(
+                42,
+                RangingMeasurements::ShortAddressTwoWay(vec![
+                    two_way_measurement(1),
+                    two_way_measurement(2),
+                    two_way_measurement(3),
+                ])
+            ))
+        );
+
+        chip_state.set_batch_notification_size(42, 1).unwrap();
+        let data =
+            range_data(42, RangingMeasurements::ShortAddressTwoWay(vec![two_way_measurement(4)]));
+        assert_eq!(chip_state.batch_range_data(data.clone()), Some(data));
+    }
+
+    #[test]
+    fn test_is_radar_session() {
+        let mut chip_state = ChipState::default();
+        chip_state.on_session_init(42, SESSION_TYPE_RADAR);
+        chip_state.on_session_init(43, 0x00);
+        assert!(chip_state.is_radar_session(42));
+        assert!(!chip_state.is_radar_session(43));
+        assert!(!chip_state.is_radar_session(44));
+
+        chip_state.on_session_deinit(42);
+        assert!(!chip_state.is_radar_session(42));
+    }
+
+    #[test]
+    fn test_cached_device_info() {
+        let mut chip_state = ChipState::default();
+        assert_eq!(chip_state.cached_device_info(), None);
+
+        let device_info = GetDeviceInfoResponse {
+            status: StatusCode::UciStatusOk,
+            uci_version: 0x0002,
+            mac_version: 0x0002,
+            phy_version: 0x0002,
+            uci_test_version: 0x0001,
+            vendor_spec_info: vec![0x0a, 0x0b],
+        };
+        chip_state.on_device_info(device_info.clone());
+        assert_eq!(chip_state.cached_device_info(), Some(device_info));
+    }
+
+    #[test]
+    fn test_session_ids() {
+        let mut chip_state = ChipState::default();
+        chip_state.on_session_init(1, 0x00);
+        chip_state.on_session_init(2, SESSION_TYPE_RADAR);
+        let mut session_ids = chip_state.session_ids();
+        session_ids.sort_unstable();
+        assert_eq!(session_ids, vec![1, 2]);
+
+        chip_state.on_session_deinit(1);
+        assert_eq!(chip_state.session_ids(), vec![2]);
+
+        chip_state.on_session_init(3, 0x00);
+        chip_state.on_all_sessions_deinit();
+        assert!(chip_state.session_ids().is_empty());
+        assert!(!chip_state.is_radar_session(2));
+    }
+
+    #[test]
+    fn test_app_config_max_zero_padding() {
+        let mut chip_state = ChipState::default();
+        assert_eq!(chip_state.app_config_max_zero_padding(), 0);
+
+        chip_state.set_app_config_max_zero_padding(3);
+        assert_eq!(chip_state.app_config_max_zero_padding(), 3);
+    }
+
+    #[test]
+    fn test_latency_observer() {
+        let mut chip_state = ChipState::default();
+        let (sender, receiver) = mpsc::channel();
+        chip_state.set_latency_observer(Some(Box::new(move |opcode, latency| {
+            sender.send((opcode, latency)).unwrap();
+        })));
+        chip_state.on_command_latency(SESSION_INIT, Duration::from_millis(3));
+        chip_state.on_command_latency(SESSION_START, Duration::from_millis(5));
+        assert_eq!(
+            receiver.recv_timeout(Duration::from_secs(1)).unwrap(),
+            (SESSION_INIT, Duration::from_millis(3))
+        );
+        assert_eq!(
+            receiver.recv_timeout(Duration::from_secs(1)).unwrap(),
+            (SESSION_START, Duration::from_millis(5))
+        );
+
+        // Removing the observer ends its thread, dropping the sender of the test.
+        chip_state.set_latency_observer(None);
+        chip_state.on_command_latency(SESSION_STOP, Duration::from_millis(1));
+        assert!(receiver.recv_timeout(Duration::from_secs(1)).is_err());
+    }
+
+    #[test]
+    fn test_controlee_measurement() {
+        let mut chip_state = ChipState::default();
+        let mut measurement = two_way_measurement(0x0102);
+        chip_state.on_range_data(&range_data(
+            42,
+            RangingMeasurements::ShortAddressTwoWay(vec![
+                measurement.clone(),
+                two_way_measurement(0x0304),
+            ]),
+        ));
+        measurement.distance = 150;
+        chip_state.on_range_data(&range_data(
+            42,
+            RangingMeasurements::ShortAddressTwoWay(vec![measurement.clone()]),
+        ));
+
+        let address = 0x0102u16.to_ne_bytes();
+        assert_eq!(
+            chip_state.controlee_measurement(42, &address),
+            Some(TwoWayRangingMeasurement::from(measurement))
+        );
+        assert_eq!(
+            chip_state.controlee_measurement(42, &0x0304u16.to_ne_bytes()).map(|m| m.distance),
+            Some(100)
+        );
+        assert_eq!(chip_state.controlee_measurement(42, &0x0506u16.to_ne_bytes()), None);
+        assert_eq!(chip_state.controlee_measurement(43, &address), None);
+
+        chip_state.on_session_deinit(42);
+        assert_eq!(chip_state.controlee_measurement(42, &address), None);
+    }
+
+    #[test]
+    fn test_filter_aoa() {
+        let aoa_range_data = |aoa_azimuth| {
+            range_data(
+                42,
+                RangingMeasurements::ShortAddressTwoWay(vec![
+                    ShortAddressTwoWayRangingMeasurement { aoa_azimuth, ..two_way_measurement(1) },
+                ]),
+            )
+        };
+        let mut chip_state = ChipState::default();
+        assert!(chip_state.set_aoa_filter_window(42, 0).is_err());
+
+        let mut range_data = aoa_range_data(10);
+        chip_state.filter_aoa(&mut range_data);
+        assert_eq!(range_data, aoa_range_data(10));
+
+        chip_state.set_aoa_filter_window(42, 2).unwrap();
+        let mut range_data = aoa_range_data(10);
+        chip_state.filter_aoa(&mut range_data);
+        assert_eq!(range_data, aoa_range_data(10));
+        let mut range_data = aoa_range_data(20);
+        chip_state.filter_aoa(&mut range_data);
+        assert_eq!(range_data, aoa_range_data(15));
+
+        chip_state.set_aoa_filter_window(42, 1).unwrap();
+        let mut range_data = aoa_range_data(20);
+        chip_state.filter_aoa(&mut range_data);
+        assert_eq!(range_data, aoa_range_data(20));
+    }
+
+    #[test]
+    fn test_recover_nlos() {
+        let nlos_range_data = |nlos, distance| {
+            range_data(
+                42,
+                RangingMeasurements::ShortAddressTwoWay(vec![
+                    ShortAddressTwoWayRangingMeasurement {
+                        nlos,
+                        distance,
+                        ..two_way_measurement(1)
+                    },
+                ]),
+            )
+        };
+        let mut chip_state = ChipState::default();
+        assert!(chip_state.set_nlos_recovery_mode(42, 2).is_err());
+        assert_eq!(chip_state.nlos_recovery_mode(42), NLOS_RECOVERY_REPORT);
+
+        chip_state.recover_nlos(&mut nlos_range_data(0, 100));
+        let mut range_data = nlos_range_data(1, 300);
+        chip_state.recover_nlos(&mut range_data);
+        assert_eq!(range_data, nlos_range_data(1, 300));
+
+        chip_state.set_nlos_recovery_mode(42, NLOS_RECOVERY_HOLD_LAST_LOS).unwrap();
+        assert_eq!(chip_state.nlos_recovery_mode(42), NLOS_RECOVERY_HOLD_LAST_LOS);
+        chip_state.recover_nlos(&mut nlos_range_data(0, 100));
+        let mut range_data = nlos_range_data(1, 300);
+        chip_state.recover_nlos(&mut range_data);
+        assert_eq!(range_data, nlos_range_data(1, 100));
+
+        chip_state.on_session_deinit(42);
+        assert_eq!(chip_state.nlos_recovery_mode(42), NLOS_RECOVERY_REPORT);
+    }
+
+    #[test]
+    fn test_round_distances() {
+        let distance_range_data = |distance| {
+            range_data(
+                42,
+                RangingMeasurements::ShortAddressTwoWay(vec![
+                    ShortAddressTwoWayRangingMeasurement { distance, ..two_way_measurement(1) },
+                ]),
+            )
+        };
+        let mut chip_state = ChipState::default();
+        assert!(chip_state.set_distance_precision(42, 0).is_err());
+        assert_eq!(chip_state.distance_precision(42), 1);
+        let mut range_data = distance_range_data(123);
+        chip_state.round_distances(&mut range_data);
+        assert_eq!(range_data, distance_range_data(123));
+
+        chip_state.set_distance_precision(42, 10).unwrap();
+        assert_eq!(chip_state.distance_precision(42), 10);
+        for (distance, rounded) in [(123, 120), (125, 130), (0, 0), (u16::MAX, 65530)] {
+            let mut range_data = distance_range_data(distance);
+            chip_state.round_distances(&mut range_data);
+            assert_eq!(range_data, distance_range_data(rounded));
+        }
+
+        chip_state.on_session_deinit(42);
+        assert_eq!(chip_state.distance_precision(42), 1);
+    }
+
+    #[test]
+    fn test_round_distance() {
+        assert_eq!(round_distance(149, 100), 100);
+        assert_eq!(round_distance(150, 100), 200);
+        assert_eq!(round_distance(65_500, 100), 65_500);
+        // 65 600 does not fit.
+        assert_eq!(round_distance(65_550, 100), 65_500);
+        assert_eq!(round_distance(65_535, 1), 65_535);
+    }
+
+    #[test]
+    fn test_mac_address_mode() {
+        let mut chip_state = ChipState::default();
+        assert_eq!(chip_state.mac_address_mode(42), None);
+
+        chip_state.on_mac_address_mode_set(42, Some(2));
+        assert_eq!(chip_state.mac_address_mode(42), Some(2));
+        chip_state.on_mac_address_mode_set(42, None);
+        assert_eq!(chip_state.mac_address_mode(42), None);
+
+        chip_state.on_mac_address_mode_set(42, Some(0));
+        chip_state.on_session_deinit(42);
+        assert_eq!(chip_state.mac_address_mode(42), None);
+    }
+
+    #[test]
+    fn test_multicast_list_add_then_remove() {
+        let controlee = |address: u8| Controlee { short_address: [address, 0], subsession_id: 0 };
+        let mut chip_state = ChipState::default();
+
+        let to_add = chip_state.controlees_to_add(42, vec![controlee(1), controlee(2)]);
+        assert_eq!(to_add, vec![controlee(1), controlee(2)]);
+        chip_state.on_multicast_list_updated(42, UpdateMulticastListAction::AddControlee, &to_add);
+
+        // Only the controlees in the list are removed, the unknown one is ignored.
+        let to_remove = chip_state.controlees_to_remove(42, vec![controlee(1), controlee(3)]);
+        assert_eq!(to_remove, vec![controlee(1)]);
+        chip_state.on_multicast_list_updated(
+            42,
+            UpdateMulticastListAction::RemoveControlee,
+            &to_remove,
+        );
+        assert_eq!(chip_state.multicast_list(42), vec![controlee(2)]);
+        assert!(chip_state.controlees_to_remove(42, vec![controlee(1)]).is_empty());
+
+        chip_state.on_session_deinit(42);
+        assert!(chip_state.multicast_list(42).is_empty());
+    }
+
+    #[test]
+    fn test_multicast_list_dedups_added_controlees() {
+        let controlee = |address: u8| Controlee { short_address: [address, 0], subsession_id: 0 };
+        let mut chip_state = ChipState::default();
+        chip_state.on_multicast_list_updated(
+            42,
+            UpdateMulticastListAction::AddControlee,
+            &[controlee(1)],
+        );
+
+        let to_add =
+            chip_state.controlees_to_add(42, vec![controlee(1), controlee(2), controlee(2)]);
+        assert_eq!(to_add, vec![controlee(2)]);
+        chip_state.on_multicast_list_updated(42, UpdateMulticastListAction::AddControlee, &to_add);
+        assert_eq!(chip_state.multicast_list(42), vec![controlee(1), controlee(2)]);
+    }
+
+    #[test]
+    fn test_measurement_rate() {
+        let mut chip_state = ChipState::default();
+        assert_eq!(chip_state.measurement_rate(42, 1000), 0.0);
+
+        // A notification of 2 measurements every 200 ms.
+        let notification = range_data(
+            42,
+            RangingMeasurements::ShortAddressTwoWay(vec![
+                two_way_measurement(0x1111),
+                two_way_measurement(0x2222),
+            ]),
+        );
+        for timestamp_ms in (200..=10000).step_by(200) {
+            chip_state.on_range_data_received(&notification, timestamp_ms);
+        }
+        // 25 notifications of 2 measurements in the last 5 seconds.
+        assert_eq!(chip_state.measurement_rate(42, 10000), 10.0);
+        // Only the notifications of the last 2 seconds are still in the window.
+        assert_eq!(chip_state.measurement_rate(42, 13000), 4.0);
+        assert_eq!(chip_state.measurement_rate(42, 20000), 0.0);
+        assert_eq!(chip_state.measurement_rate(43, 10000), 0.0);
+
+        chip_state.on_session_deinit(42);
+        assert_eq!(chip_state.measurement_rate(42, 10000), 0.0);
+    }
+}
@@ -0,0 +1,175 @@
+// Copyright 2026, The Android Open Source Project
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Sending application data larger than the max data size of the session.
+
+use uwb_core::error::{Error, Result};
+use uwb_core::uci::uci_manager_sync::UciManagerSync;
+use uwb_core::uci::UciManager;
+
+/// Sends the payload in fragments of the max data size of the session, in order, with consecutive
+/// UCI sequence numbers starting at `uci_sequence_number`. Stops at the first fragment which fails
+/// to be sent. Returns the lengths of the fragments sent, and the result of the transfer.
+pub(crate) fn send_data_fragmented<U: UciManager>(
+    uci_manager: &UciManagerSync<U>,
+    session_id: u32,
+    address: Vec<u8>,
+    uci_sequence_number: u16,
+    payload: Vec<u8>,
+) -> (Vec<usize>, Result<()>) {
+    let max_data_size = match uci_manager.session_query_max_data_size(session_id) {
+        Ok(0) => return (vec![], Err(Error::BadParameters)),
+        Ok(size) => size as usize,
+        Err(e) => return (vec![], Err(e)),
+    };
+    // An empty payload is still sent, as a single empty fragment.
+    let fragments: Vec<&[u8]> = if payload.is_empty() {
+        vec![&payload[..]]
+    } else {
+        payload.chunks(max_data_size).collect()
+    };
+
+    let mut fragment_lens = vec![];
+    for (index, fragment) in fragments.into_iter().enumerate() {
+        if let Err(e) = uci_manager.send_data_packet(
+            session_id,
+            address.clone(),
+            uci_sequence_number.wrapping_add(index as u16),
+            fragment.to_vec(),
+        ) {
+            return (fragment_lens, Err(e));
+        }
+        fragment_lens.push(fragment.len());
+    }
+    (fragment_lens, Ok(()))
+}
+
+/// Encodes the outcome of send_data_fragmented() for the Java side: the number of fragments sent
+/// if the whole payload was sent, and -(1 + the number of fragments sent) if the transfer failed,
+/// so -1 if it failed before sending any fragment.
+pub(crate) fn fragmented_send_outcome(fragment_count: usize, result: &Result<()>) -> i32 {
+    let fragment_count = i32::try_from(fragment_count).unwrap_or(i32::MAX);
+    match result {
+        Ok(()) => fragment_count,
+        Err(_) => (-1i32).saturating_sub(fragment_count),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::test_utils::new_mock_uci_manager_sync;
+
+    use uwb_core::uci::mock_uci_manager::MockUciManager;
+
+    const ADDRESS: [u8; 2] = [0x01, 0x02];
+
+    fn expect_send(
+        uci_manager_impl: &mut MockUciManager,
+        uci_sequence_number: u16,
+        payload: &[u8],
+        out: Result<()>,
+    ) {
+        uci_manager_impl.expect_send_data_packet(
+            42, // Session id
+            ADDRESS.to_vec(),
+            uci_sequence_number,
+            payload.to_vec(),
+            out,
+        );
+    }
+
+    #[test]
+    fn test_send_data_fragmented_at_limit() {
+        let payload: Vec<u8> = (0..4).collect();
+        let mut uci_manager_impl = MockUciManager::new();
+        uci_manager_impl.expect_session_query_max_data_size(42, Ok(4));
+        expect_send(&mut uci_manager_impl, 7, &payload, Ok(()));
+        let (_rt, uci_manager_sync) = new_mock_uci_manager_sync(uci_manager_impl);
+
+        let (fragment_lens, result) =
+            send_data_fragmented(&uci_manager_sync, 42, ADDRESS.to_vec(), 7, payload);
+        assert_eq!(fragment_lens, vec![4]);
+        assert!(result.is_ok());
+    }
+
+    #[test]
+    fn test_send_data_fragmented_over_limit() {
+        let payload: Vec<u8> = (0..5).collect();
+        let mut uci_manager_impl = MockUciManager::new();
+        uci_manager_impl.expect_session_query_max_data_size(42, Ok(4));
+        expect_send(&mut uci_manager_impl, 7, &payload[..4], Ok(()));
+        expect_send(&mut uci_manager_impl, 8, &payload[4..], Ok(()));
+        let (_rt, uci_manager_sync) = new_mock_uci_manager_sync(uci_manager_impl);
+
+        let (fragment_lens, result) =
+            send_data_fragmented(&uci_manager_sync, 42, ADDRESS.to_vec(), 7, payload);
+        assert_eq!(fragment_lens, vec![4, 1]);
+        assert!(result.is_ok());
+    }
+
+    #[test]
+    fn test_send_data_fragmented_well_over_limit() {
+        let payload: Vec<u8> = (0..10).collect();
+        let mut uci_manager_impl = MockUciManager::new();
+        uci_manager_impl.expect_session_query_max_data_size(42, Ok(4));
+        expect_send(&mut uci_manager_impl, u16::MAX, &payload[..4], Ok(()));
+        expect_send(&mut uci_manager_impl, 0, &payload[4..8], Ok(()));
+        expect_send(&mut uci_manager_impl, 1, &payload[8..], Ok(()));
+        let (_rt, uci_manager_sync) = new_mock_uci_manager_sync(uci_manager_impl);
+
+        let (fragment_lens, result) =
+            send_data_fragmented(&uci_manager_sync, 42, ADDRESS.to_vec(), u16::MAX, payload);
+        assert_eq!(fragment_lens, vec![4, 4, 2]);
+        assert!(result.is_ok());
+    }
+
+    #[test]
+    fn test_send_data_fragmented_failure() {
+        let payload: Vec<u8> = (0..10).collect();
+        let mut uci_manager_impl = MockUciManager::new();
+        uci_manager_impl.expect_session_query_max_data_size(42, Ok(4));
+        expect_send(&mut uci_manager_impl, 7, &payload[..4], Ok(()));
+        expect_send(&mut uci_manager_impl, 8, &payload[4..8], Err(Error::Timeout));
+        let (_rt, uci_manager_sync) = new_mock_uci_manager_sync(uci_manager_impl);
+
+        let (fragment_lens, result) =
+            send_data_fragmented(&uci_manager_sync, 42, ADDRESS.to_vec(), 7, payload);
+        assert_eq!(fragment_lens, vec![4]);
+        assert!(matches!(result, Err(Error::Timeout)));
+    }
+
+    #[test]
+    fn test_send_data_fragmented_query_failure() {
+        let mut uci_manager_impl = MockUciManager::new();
+        uci_manager_impl.expect_session_query_max_data_size(42, Err(Error::Timeout));
+        let (_rt, uci_manager_sync) = new_mock_uci_manager_sync(uci_manager_impl);
+
+        let (fragment_lens, result) =
+            send_data_fragmented(&uci_manager_sync, 42, ADDRESS.to_vec(), 7, vec![0x01]);
+        assert!(fragment_lens.is_empty());
+        assert!(matches!(result, Err(Error::Timeout)));
+        assert_eq!(fragmented_send_outcome(fragment_lens.len(), &result), -1);
+    }
+
+    #[test]
+    fn test_fragmented_send_outcome() {
+        assert_eq!(fragmented_send_outcome(3, &Ok(())), 3);
+        assert_eq!(fragmented_send_outcome(0, &Ok(())), 0);
+        // Partial transfer, after 1 fragment sent.
+        assert_eq!(fragmented_send_outcome(1, &Err(Error::Timeout)), -2);
+        assert_eq!(fragmented_send_outcome(0, &Err(Error::Timeout)), -1);
+    }
+}
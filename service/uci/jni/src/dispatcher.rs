@@ -14,35 +14,143 @@
 
 //! Implementation of Dispatcher and related methods.
 
+use crate::chip_state::{ChipState, HalState};
+use crate::hal_monitor::MonitoredUciHal;
+use crate::helper::{error_to_status_code, fail_with_status, now_ms, STATUS_NO_SUCH_CHIP};
 use crate::notification_manager_android::NotificationManagerAndroidBuilder;
+use crate::response_logger::ResponseCapturingLogger;
 
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 use std::ops::Deref;
-use std::sync::{Arc, RwLock, RwLockReadGuard};
+use std::sync::{Arc, Mutex, MutexGuard, RwLock, RwLockReadGuard};
+use std::time::{Duration, Instant};
 
-use jni::objects::{GlobalRef, JObject, JString};
+use jni::objects::{GlobalRef, JObject, JString, JValue};
 use jni::{JNIEnv, JavaVM, MonitorGuard};
 use lazy_static::lazy_static;
 use log::error;
 use tokio::runtime::{Builder as RuntimeBuilder, Runtime};
 use uci_hal_android::uci_hal_android::UciHalAndroid;
 use uwb_core::error::{Error, Result};
+use uwb_core::params::CountryCode;
 use uwb_core::uci::pcapng_uci_logger_factory::PcapngUciLoggerFactoryBuilder;
 use uwb_core::uci::uci_logger::UciLoggerMode;
 use uwb_core::uci::uci_logger_factory::UciLoggerFactory;
 use uwb_core::uci::uci_manager_sync::UciManagerSync;
-use uwb_core::uci::UciManagerImpl;
+use uwb_core::uci::{UciManager, UciManagerImpl};
+use uwb_uci_packets::StatusCode;
 
 lazy_static! {
     /// Shared unique dispatcher that may be created and deleted during runtime.
     static ref DISPATCHER: RwLock<Option<Dispatcher>> = RwLock::new(None);
 }
 
+/// Handles the unexpected disconnection of the HAL of the chip `chip_id`: the sessions of the chip
+/// are dropped, and the Java side is notified with onHalDisconnected.
+fn on_hal_disconnected(
+    vm: &JavaVM,
+    callback_obj: &GlobalRef,
+    chip_id: &str,
+    chip_state: &Mutex<ChipState>,
+) {
+    match chip_state.lock() {
+        Ok(mut chip_state) => {
+            chip_state.set_hal_state(HalState::Error);
+            chip_state.on_all_sessions_deinit();
+        }
+        Err(e) => error!("UCI JNI: failed to lock chip state: {:?}", e),
+    }
+    let result = vm.attach_current_thread().and_then(|env| {
+        let chip_id = env.new_string(chip_id)?;
+        env.call_method(
+            callback_obj.as_obj(),
+            "onHalDisconnected",
+            "(Ljava/lang/String;)V",
+            &[JValue::Object(*chip_id)],
+        )?;
+        Ok(())
+    });
+    if let Err(e) = result {
+        error!("UCI JNI: failed to notify the disconnection of the HAL of {}: {:?}", chip_id, e);
+    }
+}
+
+/// Allowlist of the (GID, OID) of the raw vendor commands forwarded to the UWBS. All the commands
+/// are allowed when no allowlist is set.
+#[derive(Debug, Default)]
+pub(crate) struct VendorCmdAllowlist(Option<HashSet<(u32, u32)>>);
+
+impl VendorCmdAllowlist {
+    /// Constructs the allowlist from the flattened (GID, OID) pairs. None allows all the commands.
+    pub fn new(gid_oid_pairs: Option<&[i32]>) -> Result<Self> {
+        let pairs = match gid_oid_pairs {
+            Some(pairs) => pairs,
+            None => return Ok(Self(None)),
+        };
+        if pairs.len() % 2 != 0 {
+            return Err(Error::BadParameters);
+        }
+        pairs
+            .chunks_exact(2)
+            .map(|pair| match (u32::try_from(pair[0]), u32::try_from(pair[1])) {
+                (Ok(gid), Ok(oid)) => Ok((gid, oid)),
+                _ => Err(Error::BadParameters),
+            })
+            .collect::<Result<HashSet<_>>>()
+            .map(|pairs| Self(Some(pairs)))
+    }
+
+    /// Fails with BadParameters if the raw vendor command is not allowed.
+    pub fn check(&self, gid: u32, oid: u32) -> Result<()> {
+        match &self.0 {
+            Some(pairs) if !pairs.contains(&(gid, oid)) => {
+                error!("UCI JNI: vendor command GID {:#x} OID {:#x} not allowed", gid, oid);
+                Err(Error::BadParameters)
+            }
+            _ => Ok(()),
+        }
+    }
+}
+
+/// Sets the country code on each chip of `managers`, carrying on with the next chips when one
+/// fails. Returns the sorted ids of the chips which failed.
+pub(crate) fn set_country_code_on_chips<'a, U: UciManager>(
+    managers: impl IntoIterator<Item = (&'a String, &'a UciManagerSync<U>)>,
+    country_code: &CountryCode,
+) -> Vec<String> {
+    let mut failed_chip_ids = managers
+        .into_iter()
+        .filter_map(|(chip_id, manager)| {
+            manager.android_set_country_code(country_code.clone()).err().map(|e| {
+                error!("UCI JNI: failed to set country code of chip {}: {:?}", chip_id, e);
+                chip_id.clone()
+            })
+        })
+        .collect::<Vec<_>>();
+    failed_chip_ids.sort();
+    failed_chip_ids
+}
+
+/// Looks up the value registered for the chip `chip_id`. An unregistered chip id is logged, so that
+/// the logs show which chip was requested, and fails with BadParameters, reported to the Java side
+/// as STATUS_NO_SUCH_CHIP.
+fn find_chip<'m, V>(map: &'m HashMap<String, V>, chip_id: &str) -> Result<&'m V> {
+    map.get(chip_id).ok_or_else(|| {
+        error!("UCI JNI: no such chip {:?}, registered chips: {:?}", chip_id, map.keys());
+        fail_with_status(Error::BadParameters, STATUS_NO_SUCH_CHIP)
+    })
+}
+
 /// Dispatcher is managed by Java side. Construction and Destruction are provoked by JNI function
 /// nativeDispatcherNew and nativeDispatcherDestroy respectively.
 /// Destruction does NOT wait until the spawned threads are closed.
 pub(crate) struct Dispatcher {
     pub manager_map: HashMap<String, UciManagerSync<UciManagerImpl>>,
+    pub chip_state_map: HashMap<String, Arc<Mutex<ChipState>>>,
+    // First chip the dispatcher was created with, used by the JNI calls not taking a chip id.
+    default_chip_id: Option<String>,
+    logger_mode: Mutex<UciLoggerMode>,
+    vendor_cmd_allowlist: Mutex<VendorCmdAllowlist>,
     _runtime: Runtime,
 }
 impl Dispatcher {
@@ -59,6 +167,7 @@ impl Dispatcher {
             .build()
             .map_err(|_| Error::ForeignFunctionInterface)?;
         let mut manager_map = HashMap::<String, UciManagerSync<UciManagerImpl>>::new();
+        let mut chip_state_map = HashMap::<String, Arc<Mutex<ChipState>>>::new();
         let mut log_file_factory = PcapngUciLoggerFactoryBuilder::new()
             .log_path("/data/misc/apexdata/com.android.uwb/log".into())
             .filename_prefix("uwb_uci".to_owned())
@@ -66,22 +175,41 @@ impl Dispatcher {
             .build()
             .ok_or(Error::Unknown)?;
         for chip_id in chip_ids {
-            let logger = log_file_factory.build_logger(chip_id.as_ref()).ok_or(Error::Unknown)?;
+            let chip_state = Arc::new(Mutex::new(ChipState::default()));
+            let logger = ResponseCapturingLogger::new(
+                log_file_factory.build_logger(chip_id.as_ref()).ok_or(Error::Unknown)?,
+                chip_state.clone(),
+            );
+            let on_disconnected = {
+                let callback_obj = callback_obj.clone();
+                let chip_id = chip_id.as_ref().to_owned();
+                let chip_state = chip_state.clone();
+                Arc::new(move || on_hal_disconnected(vm, &callback_obj, &chip_id, &chip_state))
+            };
             let manager = UciManagerSync::new(
-                UciHalAndroid::new(chip_id.as_ref()),
+                MonitoredUciHal::new(UciHalAndroid::new(chip_id.as_ref()), on_disconnected),
                 NotificationManagerAndroidBuilder {
                     chip_id: chip_id.as_ref().to_owned(),
                     vm,
                     class_loader_obj: class_loader_obj.clone(),
                     callback_obj: callback_obj.clone(),
+                    chip_state: chip_state.clone(),
                 },
                 logger,
                 UciLoggerMode::Filtered,
                 runtime.handle().to_owned(),
             )?;
             manager_map.insert(chip_id.as_ref().to_string(), manager);
+            chip_state_map.insert(chip_id.as_ref().to_string(), chip_state);
         }
-        Ok(Self { manager_map, _runtime: runtime })
+        Ok(Self {
+            manager_map,
+            chip_state_map,
+            default_chip_id: chip_ids.first().map(|chip_id| chip_id.as_ref().to_owned()),
+            logger_mode: Mutex::new(UciLoggerMode::Filtered),
+            vendor_cmd_allowlist: Mutex::new(VendorCmdAllowlist::default()),
+            _runtime: runtime,
+        })
     }
 
     /// Sets log mode for all chips.
@@ -89,6 +217,24 @@ impl Dispatcher {
         for (_, manager) in self.manager_map.iter() {
             manager.set_logger_mode(logger_mode.clone())?;
         }
+        *self.logger_mode.lock().map_err(|_| Error::Unknown)? = logger_mode;
+        Ok(())
+    }
+
+    /// Gets the log mode of all chips.
+    pub fn logger_mode(&self) -> Result<UciLoggerMode> {
+        Ok(self.logger_mode.lock().map_err(|_| Error::Unknown)?.clone())
+    }
+
+    /// Sets the country code on all chips, carrying on with the next chips when one fails. Returns
+    /// the sorted ids of the chips which failed.
+    pub fn set_country_code_all(&self, country_code: CountryCode) -> Vec<String> {
+        set_country_code_on_chips(&self.manager_map, &country_code)
+    }
+
+    /// Sets the allowlist of the raw vendor commands of all chips.
+    pub fn set_vendor_cmd_allowlist(&self, allowlist: VendorCmdAllowlist) -> Result<()> {
+        *self.vendor_cmd_allowlist.lock().map_err(|_| Error::Unknown)? = allowlist;
         Ok(())
     }
 
@@ -134,6 +280,21 @@ impl Dispatcher {
         GuardedDispatcher::new(jni_guard, read_lock)
     }
 
+    /// Sends a UCI command to the chip with chip_id, retried according to the retry policy of the
+    /// chip, recording its outcome in the recent commands of the chip and notifying its latency,
+    /// including the retries, to the latency observer of the chip, if any. The dispatcher is locked
+    /// for each attempt only, so that the other calls are not blocked during the backoff. The
+    /// caller must not hold the dispatcher.
+    pub fn record_command<'a, T>(
+        env: JNIEnv<'a>,
+        obj: JObject<'a>,
+        chip_id: JString,
+        opcode: u16,
+        command: impl FnMut(&GuardedUciManager<'a>) -> Result<T>,
+    ) -> Result<T> {
+        record_command(|| Self::get_uci_manager(env, obj, chip_id), opcode, command)
+    }
+
     /// Gets reference to UciManagerSync with chip_id.
     pub fn get_uci_manager<'a>(
         env: JNIEnv<'a>,
@@ -145,6 +306,17 @@ impl Dispatcher {
             String::from(env.get_string(chip_id).map_err(|_| Error::ForeignFunctionInterface)?);
         guarded_dispatcher.into_guarded_uci_manager(&chip_id_str)
     }
+
+    /// Gets reference to the UciManagerSync of the default chip, the first one the dispatcher was
+    /// created with.
+    pub fn get_default_uci_manager<'a>(
+        env: JNIEnv<'a>,
+        obj: JObject<'a>,
+    ) -> Result<GuardedUciManager<'a>> {
+        let guarded_dispatcher = Self::get_dispatcher(env, obj)?;
+        let chip_id = guarded_dispatcher.default_chip_id.clone().ok_or(Error::BadParameters)?;
+        guarded_dispatcher.into_guarded_uci_manager(&chip_id)
+    }
 }
 
 /// Lifetimed reference to UciManagerSync that locks Java object while reference is alive.
@@ -154,6 +326,65 @@ pub(crate) struct GuardedUciManager<'a> {
     chip_id: String,
 }
 
+impl<'a> GuardedUciManager<'a> {
+    /// Gets the state tracked for the chip of the UciManagerSync.
+    pub fn chip_state(&self) -> Result<MutexGuard<ChipState>> {
+        self.chip_state_mutex().lock().map_err(|_| Error::Unknown)
+    }
+
+    fn chip_state_mutex(&self) -> &Mutex<ChipState> {
+        // Unwrap GuardedUciManager will not panic since content is checked at creation.
+        self.read_lock.as_ref().unwrap().chip_state_map.get(&self.chip_id).unwrap()
+    }
+
+    /// Fails with BadParameters if the raw vendor command is not allowed by the allowlist of the
+    /// dispatcher.
+    pub fn check_vendor_cmd(&self, gid: u32, oid: u32) -> Result<()> {
+        // Unwrap GuardedUciManager will not panic since content is checked at creation.
+        let allowlist = &self.read_lock.as_ref().unwrap().vendor_cmd_allowlist;
+        allowlist.lock().map_err(|_| Error::Unknown)?.check(gid, oid)
+    }
+}
+
+/// Sends a UCI command as Dispatcher::record_command, to the chip locked by `get_uci_manager`.
+fn record_command<'a, T>(
+    mut get_uci_manager: impl FnMut() -> Result<GuardedUciManager<'a>>,
+    opcode: u16,
+    mut command: impl FnMut(&GuardedUciManager<'a>) -> Result<T>,
+) -> Result<T> {
+    let retry_policy = get_uci_manager()?.chip_state()?.retry_policy();
+    let start = Instant::now();
+    // The guard of each attempt is dropped before the backoff.
+    let result = retry_policy.run(|| command(&get_uci_manager()?));
+    let latency = start.elapsed();
+    match get_uci_manager() {
+        Ok(uci_manager) => record_outcome(uci_manager.chip_state_mutex(), opcode, &result, latency),
+        Err(e) => error!("Failed to record UCI command {:#06x}: {:?}", opcode, e),
+    }
+    result
+}
+
+/// Records the outcome of a UCI command in the recent commands of the chip of `chip_state`, and
+/// notifies its latency to the latency observer of the chip, if any.
+fn record_outcome<T>(
+    chip_state: &Mutex<ChipState>,
+    opcode: u16,
+    result: &Result<T>,
+    latency: Duration,
+) {
+    let status = match result {
+        Ok(_) => StatusCode::UciStatusOk,
+        Err(e) => error_to_status_code(e),
+    };
+    match chip_state.lock() {
+        Ok(mut chip_state) => {
+            chip_state.on_command(opcode, status, now_ms());
+            chip_state.on_command_latency(opcode, latency);
+        }
+        Err(e) => error!("Failed to record UCI command {:#06x}: {:?}", opcode, e),
+    }
+}
+
 impl<'a> Deref for GuardedUciManager<'a> {
     type Target = UciManagerSync<UciManagerImpl>;
     fn deref(&self) -> &Self::Target {
@@ -183,7 +414,7 @@ impl<'a> GuardedDispatcher<'a> {
 
     /// Conversion to GuardedUciManager:
     pub fn into_guarded_uci_manager(self, chip_id: &str) -> Result<GuardedUciManager<'a>> {
-        let _uci_manager = self.manager_map.get(chip_id).ok_or(Error::BadParameters)?;
+        let _uci_manager = find_chip(&self.manager_map, chip_id)?;
         Ok(GuardedUciManager {
             _jni_guard: self._jni_guard,
             read_lock: self.read_lock,
@@ -199,3 +430,96 @@ impl<'a> Deref for GuardedDispatcher<'a> {
         self.read_lock.as_ref().unwrap()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use std::sync::mpsc;
+
+    use crate::chip_state::{SESSION_INIT, SESSION_START};
+    use crate::helper::byte_result_helper;
+    use crate::test_utils::new_mock_uci_manager_sync;
+
+    use uwb_core::uci::mock_uci_manager::MockUciManager;
+
+    #[test]
+    fn test_find_chip() {
+        let chips = HashMap::from([("chip0".to_owned(), 0), ("chip1".to_owned(), 1)]);
+        assert_eq!(find_chip(&chips, "chip1").unwrap(), &1);
+        assert!(matches!(find_chip(&chips, "chip2"), Err(Error::BadParameters)));
+        assert!(matches!(find_chip(&HashMap::<String, u32>::new(), ""), Err(Error::BadParameters)));
+
+        // An unknown chip is told apart from bad parameters on the Java side.
+        assert_eq!(
+            byte_result_helper(find_chip(&chips, "chip2"), "test"),
+            STATUS_NO_SUCH_CHIP as i8
+        );
+        assert_eq!(
+            byte_result_helper(Err::<(), _>(Error::BadParameters), "test"),
+            u8::from(StatusCode::UciStatusInvalidParam) as i8
+        );
+    }
+
+    #[test]
+    fn test_set_country_code_on_chips() {
+        let country_code = CountryCode::new(b"US").unwrap();
+        let mut runtimes = vec![];
+        let mut managers = HashMap::new();
+        for (chip_id, out) in [("chip0", Ok(())), ("chip1", Err(Error::Timeout)), ("chip2", Ok(()))]
+        {
+            let mut uci_manager_impl = MockUciManager::new();
+            uci_manager_impl.expect_android_set_country_code(country_code.clone(), out);
+            let (rt, uci_manager_sync) = new_mock_uci_manager_sync(uci_manager_impl);
+            runtimes.push(rt);
+            managers.insert(chip_id.to_owned(), uci_manager_sync);
+        }
+
+        // The chips following the failing one are still set.
+        assert_eq!(set_country_code_on_chips(&managers, &country_code), vec!["chip1".to_owned()]);
+    }
+
+    #[test]
+    fn test_vendor_cmd_allowlist() {
+        let allowlist = VendorCmdAllowlist::new(Some(&[0x09, 0x01, 0x0C, 0x02])).unwrap();
+        assert!(allowlist.check(0x09, 0x01).is_ok());
+        assert!(allowlist.check(0x0C, 0x02).is_ok());
+        assert!(allowlist.check(0x09, 0x02).is_err());
+        assert!(allowlist.check(0x0A, 0x01).is_err());
+
+        // An empty allowlist allows no command.
+        assert!(VendorCmdAllowlist::new(Some(&[])).unwrap().check(0x09, 0x01).is_err());
+
+        assert!(VendorCmdAllowlist::new(Some(&[0x09])).is_err());
+        assert!(VendorCmdAllowlist::new(Some(&[0x09, -1])).is_err());
+    }
+
+    #[test]
+    fn test_vendor_cmd_allowlist_permits_all_by_default() {
+        assert!(VendorCmdAllowlist::default().check(0x09, 0x01).is_ok());
+        assert!(VendorCmdAllowlist::new(None).unwrap().check(0x0F, 0x3F).is_ok());
+    }
+
+    #[test]
+    fn test_record_outcome_notifies_latency() {
+        let chip_state = Arc::new(Mutex::new(ChipState::default()));
+        let observed_chip_state = Arc::downgrade(&chip_state);
+        let (sender, receiver) = mpsc::channel();
+        chip_state.lock().unwrap().set_latency_observer(Some(Box::new(move |opcode, latency| {
+            // The observer is not called under the lock of the ChipState.
+            let chip_state = observed_chip_state.upgrade().unwrap();
+            let recorded = !chip_state.lock().unwrap().encode_recent_commands().is_empty();
+            sender.send((opcode, latency, recorded)).unwrap();
+        })));
+
+        record_outcome(&chip_state, SESSION_INIT, &Ok(()), Duration::from_millis(3));
+        record_outcome(&chip_state, SESSION_START, &Err::<(), _>(Error::Timeout), Duration::ZERO);
+
+        let (opcode, latency, recorded) = receiver.recv_timeout(Duration::from_secs(1)).unwrap();
+        assert_eq!(opcode, SESSION_INIT);
+        assert_eq!(latency, Duration::from_millis(3));
+        assert!(recorded);
+        let (opcode, _, _) = receiver.recv_timeout(Duration::from_secs(1)).unwrap();
+        assert_eq!(opcode, SESSION_START);
+    }
+}
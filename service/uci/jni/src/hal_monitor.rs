@@ -0,0 +1,177 @@
+// Copyright 2026, The Android Open Source Project
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Detection of the unexpected disconnection of the UCI HAL.
+
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+
+use async_trait::async_trait;
+use log::error;
+use tokio::sync::mpsc;
+use uwb_core::error::Result;
+use uwb_core::uci::uci_hal::{UciHal, UciHalPacket};
+
+/// Observer of the unexpected disconnection of the HAL, called at most once per opening of the HAL.
+pub(crate) type HalDisconnectObserver = Arc<dyn Fn() + Send + Sync>;
+
+/// UciHal forwarding the packets received from the wrapped HAL, and calling the observer if the HAL
+/// stops delivering them while open: the HAL dropped its packet sender without being closed, as
+/// when the HAL process dies.
+pub(crate) struct MonitoredUciHal<H: UciHal> {
+    hal: H,
+    on_disconnected: HalDisconnectObserver,
+    // Set while the HAL is closed on request, so that the end of its packets is expected.
+    closing: Arc<AtomicBool>,
+}
+
+impl<H: UciHal> MonitoredUciHal<H> {
+    /// Wraps `hal`, calling `on_disconnected` if it disconnects unexpectedly.
+    pub fn new(hal: H, on_disconnected: HalDisconnectObserver) -> Self {
+        Self { hal, on_disconnected, closing: Arc::new(AtomicBool::new(false)) }
+    }
+}
+
+#[async_trait]
+impl<H: UciHal> UciHal for MonitoredUciHal<H> {
+    async fn open(&mut self, packet_sender: mpsc::UnboundedSender<UciHalPacket>) -> Result<()> {
+        let (hal_packet_sender, mut hal_packet_receiver) = mpsc::unbounded_channel();
+        self.closing.store(false, Ordering::SeqCst);
+        self.hal.open(hal_packet_sender).await?;
+
+        let closing = self.closing.clone();
+        let on_disconnected = self.on_disconnected.clone();
+        tokio::spawn(async move {
+            while let Some(packet) = hal_packet_receiver.recv().await {
+                if packet_sender.send(packet).is_err() {
+                    // The UciManager is gone, the HAL is not monitored anymore.
+                    return;
+                }
+            }
+            if !closing.load(Ordering::SeqCst) {
+                error!("UCI JNI: the HAL disconnected unexpectedly");
+                on_disconnected();
+            }
+        });
+        Ok(())
+    }
+
+    async fn close(&mut self) -> Result<()> {
+        self.closing.store(true, Ordering::SeqCst);
+        self.hal.close().await
+    }
+
+    async fn send_packet(&mut self, packet: UciHalPacket) -> Result<()> {
+        self.hal.send_packet(packet).await
+    }
+
+    async fn notify_session_initialized(&mut self, session_id: u32) -> Result<()> {
+        self.hal.notify_session_initialized(session_id).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use std::sync::Mutex;
+    use std::time::Duration;
+
+    use tokio::runtime::Builder;
+    use tokio::time::timeout;
+
+    // Time waited for a notification which is not expected.
+    const NO_NOTIFICATION_TIMEOUT: Duration = Duration::from_millis(100);
+
+    // HAL whose packet sender is shared with the test, which drops it to simulate the death of the
+    // HAL.
+    struct FakeUciHal {
+        packet_sender: Arc<Mutex<Option<mpsc::UnboundedSender<UciHalPacket>>>>,
+    }
+
+    #[async_trait]
+    impl UciHal for FakeUciHal {
+        async fn open(&mut self, packet_sender: mpsc::UnboundedSender<UciHalPacket>) -> Result<()> {
+            *self.packet_sender.lock().unwrap() = Some(packet_sender);
+            Ok(())
+        }
+
+        async fn close(&mut self) -> Result<()> {
+            self.packet_sender.lock().unwrap().take();
+            Ok(())
+        }
+
+        async fn send_packet(&mut self, _packet: UciHalPacket) -> Result<()> {
+            Ok(())
+        }
+    }
+
+    // Builds a monitored FakeUciHal, along with its packet sender and the receiver of its
+    // disconnections.
+    fn monitored_hal() -> (
+        MonitoredUciHal<FakeUciHal>,
+        Arc<Mutex<Option<mpsc::UnboundedSender<UciHalPacket>>>>,
+        mpsc::UnboundedReceiver<()>,
+    ) {
+        let packet_sender = Arc::new(Mutex::new(None));
+        let (disconnect_sender, disconnect_receiver) = mpsc::unbounded_channel();
+        let hal = MonitoredUciHal::new(
+            FakeUciHal { packet_sender: packet_sender.clone() },
+            Arc::new(move || disconnect_sender.send(()).unwrap()),
+        );
+        (hal, packet_sender, disconnect_receiver)
+    }
+
+    #[test]
+    fn test_hal_disconnected() {
+        let rt = Builder::new_multi_thread().enable_all().build().unwrap();
+        rt.block_on(async {
+            let (mut hal, hal_packet_sender, mut disconnections) = monitored_hal();
+            let (packet_sender, mut packet_receiver) = mpsc::unbounded_channel();
+            hal.open(packet_sender).await.unwrap();
+
+            let hal_sender = hal_packet_sender.lock().unwrap().clone().unwrap();
+            hal_sender.send(vec![0x60, 0x01, 0x00, 0x01, 0x01]).unwrap();
+            assert_eq!(packet_receiver.recv().await, Some(vec![0x60, 0x01, 0x00, 0x01, 0x01]));
+
+            // The HAL dies, dropping its packet sender.
+            drop(hal_sender);
+            hal_packet_sender.lock().unwrap().take();
+            assert_eq!(disconnections.recv().await, Some(()));
+            // Notified once per disconnection.
+            assert!(timeout(NO_NOTIFICATION_TIMEOUT, disconnections.recv()).await.is_err());
+
+            // Notified again once reopened.
+            let (packet_sender, _packet_receiver) = mpsc::unbounded_channel();
+            hal.open(packet_sender).await.unwrap();
+            hal_packet_sender.lock().unwrap().take();
+            assert_eq!(disconnections.recv().await, Some(()));
+        });
+    }
+
+    #[test]
+    fn test_hal_closed() {
+        let rt = Builder::new_multi_thread().enable_all().build().unwrap();
+        rt.block_on(async {
+            let (mut hal, _hal_packet_sender, mut disconnections) = monitored_hal();
+            let (packet_sender, mut packet_receiver) = mpsc::unbounded_channel();
+            hal.open(packet_sender).await.unwrap();
+
+            hal.close().await.unwrap();
+            // The packets of a closed HAL end without any disconnection.
+            assert_eq!(packet_receiver.recv().await, None);
+            assert!(timeout(NO_NOTIFICATION_TIMEOUT, disconnections.recv()).await.is_err());
+        });
+    }
+}
@@ -14,12 +14,41 @@
 
 //! Helper functions and macros
 
+use std::cell::Cell;
+use std::mem::{discriminant, Discriminant};
+use std::time::{SystemTime, UNIX_EPOCH};
+
 use jni::sys::{jboolean, jbyte};
 use log::error;
 use uwb_core::error::{Error, Result};
 use uwb_uci_packets::StatusCode;
 
+// Status returned to the Java side when the requested chip is not registered, as
+// UwbUciConstants.STATUS_CODE_JNI_NO_SUCH_CHIP. The value is reserved for future use by FiRa, so
+// that no UWBS reports it.
+pub(crate) const STATUS_NO_SUCH_CHIP: u8 = 0x10;
+
+thread_local! {
+    /// Status of the last failure of the JNI layer on this thread, when more specific than the
+    /// Error it failed with, which can't carry it since Error is defined by uwb_core. The status is
+    /// reset once the result of the JNI call is converted for the Java side, and only applies to an
+    /// Error of the same kind.
+    static FAILURE_STATUS: Cell<Option<(Discriminant<Error>, u8)>> = Cell::new(None);
+}
+
+/// Records `status` as the status of the failure with `error`, returned to the Java side in place
+/// of the status `error` maps to, and returns `error`.
+pub(crate) fn fail_with_status(error: Error, status: u8) -> Error {
+    FAILURE_STATUS.with(|failure_status| failure_status.set(Some((discriminant(&error), status))));
+    error
+}
+
+fn take_failure_status() -> Option<(Discriminant<Error>, u8)> {
+    FAILURE_STATUS.with(Cell::take)
+}
+
 pub(crate) fn boolean_result_helper<T>(result: Result<T>, error_msg: &str) -> jboolean {
+    take_failure_status();
     match result {
         Ok(_) => true,
         Err(e) => {
@@ -32,27 +61,43 @@ pub(crate) fn boolean_result_helper<T>(result: Result<T>, error_msg: &str) -> jb
 
 pub(crate) fn byte_result_helper<T>(result: Result<T>, error_msg: &str) -> jbyte {
     // StatusCode do not overflow i8
-    u8::from(result_to_status_code(result, error_msg)) as i8
+    result_to_status_code(result, error_msg) as i8
 }
 
-/// helper function to convert Result to StatusCode
-fn result_to_status_code<T>(result: Result<T>, error_msg: &str) -> StatusCode {
+/// helper function to convert Result to the status of the Java side: the status recorded with
+/// fail_with_status() if any, and the StatusCode the Error maps to otherwise.
+fn result_to_status_code<T>(result: Result<T>, error_msg: &str) -> u8 {
+    let failure_status = take_failure_status();
     let result = result.map_err(|e| {
         error!("{} failed with {:?}", error_msg, &e);
         e
     });
     match result {
-        Ok(_) => StatusCode::UciStatusOk,
-        Err(Error::BadParameters) => StatusCode::UciStatusInvalidParam,
-        Err(Error::MaxSessionsExceeded) => StatusCode::UciStatusMaxSessionsExceeded,
-        Err(Error::CommandRetry) => StatusCode::UciStatusCommandRetry,
-        Err(Error::RegulationUwbOff) => StatusCode::UciStatusRegulationUwbOff,
-        // For other Error, only generic fail can be given.
-        Err(_) => StatusCode::UciStatusFailed,
+        Ok(_) => u8::from(StatusCode::UciStatusOk),
+        Err(e) => match failure_status {
+            Some((kind, status)) if kind == discriminant(&e) => status,
+            _ => u8::from(error_to_status_code(&e)),
+        },
+    }
+}
+
+/// helper function to convert Error to StatusCode
+pub(crate) fn error_to_status_code(error: &Error) -> StatusCode {
+    match error {
+        Error::BadParameters => StatusCode::UciStatusInvalidParam,
+        Error::MaxSessionsExceeded => StatusCode::UciStatusMaxSessionsExceeded,
+        Error::CommandRetry => StatusCode::UciStatusCommandRetry,
+        Error::RegulationUwbOff => StatusCode::UciStatusRegulationUwbOff,
+        Error::DuplicatedSessionId => StatusCode::UciStatusSessionDuplicate,
+        // Error does not carry the status of the device response, and uwb_core folds most device
+        // statuses into Error::Unknown. Unless recorded with fail_with_status(), only generic fail
+        // can be given for other Error.
+        _ => StatusCode::UciStatusFailed,
     }
 }
 
 pub(crate) fn option_result_helper<T>(result: Result<T>, error_msg: &str) -> Option<T> {
+    take_failure_status();
     result
         .map_err(|e| {
             error!("{} failed with {:?}", error_msg, &e);
@@ -60,3 +105,72 @@ pub(crate) fn option_result_helper<T>(result: Result<T>, error_msg: &str) -> Opt
         })
         .ok()
 }
+
+/// Returns the current time in ms since the UNIX epoch.
+pub(crate) fn now_ms() -> u64 {
+    SystemTime::now().duration_since(UNIX_EPOCH).map_or(0, |duration| duration.as_millis() as u64)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::test_utils::new_mock_uci_manager_sync;
+
+    use uwb_core::uci::mock_uci_manager::MockUciManager;
+    use uwb_uci_packets::SessionType;
+
+    fn session_init_status(out: Result<()>) -> jbyte {
+        let mut uci_manager_impl = MockUciManager::new();
+        uci_manager_impl.expect_session_init(42, SessionType::FiraRangingSession, vec![], out);
+        let (_rt, uci_manager_sync) = new_mock_uci_manager_sync(uci_manager_impl);
+
+        byte_result_helper(
+            uci_manager_sync.session_init(42, SessionType::FiraRangingSession),
+            "session_init",
+        )
+    }
+
+    #[test]
+    fn test_byte_result_helper() {
+        assert_eq!(session_init_status(Ok(())), u8::from(StatusCode::UciStatusOk) as i8);
+        assert_eq!(
+            session_init_status(Err(Error::DuplicatedSessionId)),
+            u8::from(StatusCode::UciStatusSessionDuplicate) as i8
+        );
+        assert_eq!(
+            session_init_status(Err(Error::MaxSessionsExceeded)),
+            u8::from(StatusCode::UciStatusMaxSessionsExceeded) as i8
+        );
+        assert_eq!(
+            session_init_status(Err(Error::BadParameters)),
+            u8::from(StatusCode::UciStatusInvalidParam) as i8
+        );
+        assert_eq!(
+            session_init_status(Err(Error::Timeout)),
+            u8::from(StatusCode::UciStatusFailed) as i8
+        );
+    }
+
+    #[test]
+    fn test_byte_result_helper_with_failure_status() {
+        let result: Result<()> = Err(fail_with_status(Error::BadParameters, STATUS_NO_SUCH_CHIP));
+        assert_eq!(byte_result_helper(result, "test"), STATUS_NO_SUCH_CHIP as i8);
+        // The status is only returned for the failure it was recorded with.
+        assert_eq!(
+            byte_result_helper(Err::<(), _>(Error::BadParameters), "test"),
+            u8::from(StatusCode::UciStatusInvalidParam) as i8
+        );
+        let _ = fail_with_status(Error::BadParameters, STATUS_NO_SUCH_CHIP);
+        assert_eq!(option_result_helper(Ok(1), "test"), Some(1));
+        assert_eq!(
+            byte_result_helper(Err::<(), _>(Error::BadParameters), "test"),
+            u8::from(StatusCode::UciStatusInvalidParam) as i8
+        );
+        let _ = fail_with_status(Error::BadParameters, STATUS_NO_SUCH_CHIP);
+        assert_eq!(
+            byte_result_helper(Err::<(), _>(Error::Timeout), "test"),
+            u8::from(StatusCode::UciStatusFailed) as i8
+        );
+    }
+}
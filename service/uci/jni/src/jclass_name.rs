@@ -17,9 +17,13 @@ pub(crate) const CONFIG_STATUS_DATA_CLASS: &str = "com/android/server/uwb/data/U
 pub(crate) const MULTICAST_LIST_UPDATE_STATUS_CLASS: &str =
     "com/android/server/uwb/data/UwbMulticastListUpdateStatus";
 pub(crate) const POWER_STATS_CLASS: &str = "com/android/server/uwb/info/UwbPowerStats";
+pub(crate) const POWER_STATS_LONG_CLASS: &str = "com/android/server/uwb/info/UwbPowerStatsLong";
 pub(crate) const TLV_DATA_CLASS: &str = "com/android/server/uwb/data/UwbTlvData";
+pub(crate) const UWB_DEVICE_CAPABILITIES_CLASS: &str =
+    "com/android/server/uwb/info/UwbDeviceCapabilities";
 pub(crate) const UWB_DEVICE_INFO_RESPONSE_CLASS: &str =
     "com/android/server/uwb/data/UwbDeviceInfoResponse";
+pub(crate) const UWB_TIMESTAMP_CLASS: &str = "com/android/server/uwb/data/UwbTimestamp";
 pub(crate) const UWB_RANGING_DATA_CLASS: &str = "com/android/server/uwb/data/UwbRangingData";
 pub(crate) const UWB_TWO_WAY_MEASUREMENT_CLASS: &str =
     "com/android/server/uwb/data/UwbTwoWayMeasurement";
@@ -18,10 +18,24 @@
 //! UciManager. In conjunction with libuci_hal_android and libuwb_core, this provides a replacement
 //! for libuwb_uci_jni_rust.
 
+mod aoa_filter;
+mod app_config;
+mod caps;
+mod chip_state;
+mod data_transfer;
 mod dispatcher;
+mod hal_monitor;
 mod helper;
 mod jclass_name;
+mod nlos_recovery;
 mod notification_manager_android;
+mod response_logger;
+mod retry_policy;
+mod session_state;
+mod timestamp;
 mod unique_jvm;
 
+#[cfg(test)]
+mod test_utils;
+
 pub mod uci_jni_android_new;
@@ -0,0 +1,177 @@
+// Copyright 2026, The Android Open Source Project
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Recovery of the two way ranging measurements reported in non-line-of-sight.
+
+use std::collections::HashMap;
+
+use crate::notification_manager_android::MacAddress;
+
+use uwb_core::error::{Error, Result};
+use uwb_core::uci::RangingMeasurements;
+use uwb_uci_packets::StatusCode;
+
+// Recovery modes: the NLOS measurements are reported as is, or the last measurement in line of
+// sight of the controlee is held until the line of sight is recovered.
+pub(crate) const NLOS_RECOVERY_REPORT: u8 = 0;
+pub(crate) const NLOS_RECOVERY_HOLD_LAST_LOS: u8 = 1;
+
+// Values of the NLOS field of the measurements. The UWBS may also report that it is unable to
+// determine it, in which case the measurement is left unchanged.
+const LINE_OF_SIGHT: u8 = 0;
+const NON_LINE_OF_SIGHT: u8 = 1;
+
+/// Replaces the distance and AoA of the successful two way ranging measurements of a session
+/// reported in NLOS by the ones of the last measurement of the controlee in line of sight.
+#[derive(Default)]
+pub(crate) struct NlosRecovery {
+    // Last (distance, azimuth, elevation) of each controlee measured in line of sight.
+    last_los: HashMap<MacAddress, (u16, u16, u16)>,
+}
+
+impl NlosRecovery {
+    /// Constructs the recovery of a session for the recovery `mode`, None if the NLOS measurements
+    /// are reported as is. Unknown modes are rejected.
+    pub fn new(mode: u8) -> Result<Option<Self>> {
+        match mode {
+            NLOS_RECOVERY_REPORT => Ok(None),
+            NLOS_RECOVERY_HOLD_LAST_LOS => Ok(Some(Self::default())),
+            _ => Err(Error::BadParameters),
+        }
+    }
+
+    /// Holds the last measurement in line of sight of the controlees in place of their successful
+    /// NLOS two way ranging measurements. The other types of measurements are left unchanged.
+    pub fn recover(&mut self, measurements: &mut RangingMeasurements) {
+        match measurements {
+            RangingMeasurements::ShortAddressTwoWay(v) => {
+                for m in v.iter_mut().filter(|m| m.status == StatusCode::UciStatusOk) {
+                    (m.distance, m.aoa_azimuth, m.aoa_elevation) = self.hold(
+                        MacAddress::Short(m.mac_address),
+                        m.nlos,
+                        (m.distance, m.aoa_azimuth, m.aoa_elevation),
+                    );
+                }
+            }
+            RangingMeasurements::ExtendedAddressTwoWay(v) => {
+                for m in v.iter_mut().filter(|m| m.status == StatusCode::UciStatusOk) {
+                    (m.distance, m.aoa_azimuth, m.aoa_elevation) = self.hold(
+                        MacAddress::Extended(m.mac_address),
+                        m.nlos,
+                        (m.distance, m.aoa_azimuth, m.aoa_elevation),
+                    );
+                }
+            }
+            _ => {}
+        }
+    }
+
+    fn hold(
+        &mut self,
+        mac_address: MacAddress,
+        nlos: u8,
+        value: (u16, u16, u16),
+    ) -> (u16, u16, u16) {
+        match nlos {
+            LINE_OF_SIGHT => {
+                self.last_los.insert(mac_address, value);
+                value
+            }
+            // Without a previous measurement in line of sight, the NLOS one is the best known.
+            NON_LINE_OF_SIGHT => self.last_los.get(&mac_address).copied().unwrap_or(value),
+            _ => value,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use uwb_uci_packets::ShortAddressTwoWayRangingMeasurement;
+
+    fn measurements(mac_address: u16, nlos: u8, distance: u16) -> RangingMeasurements {
+        RangingMeasurements::ShortAddressTwoWay(vec![ShortAddressTwoWayRangingMeasurement {
+            mac_address,
+            status: StatusCode::UciStatusOk,
+            nlos,
+            distance,
+            aoa_azimuth: distance,
+            aoa_azimuth_fom: 0,
+            aoa_elevation: distance,
+            aoa_elevation_fom: 0,
+            aoa_destination_azimuth: 0,
+            aoa_destination_azimuth_fom: 0,
+            aoa_destination_elevation: 0,
+            aoa_destination_elevation_fom: 0,
+            slot_index: 0,
+            rssi: 0,
+        }])
+    }
+
+    // Feeds each (nlos, distance) to the recovery in its own notification and returns the
+    // recovered measurements.
+    fn recover_sequence(
+        recovery: &mut NlosRecovery,
+        sequence: &[(u8, u16)],
+    ) -> Vec<RangingMeasurements> {
+        sequence
+            .iter()
+            .map(|(nlos, distance)| {
+                let mut measurements = measurements(1, *nlos, *distance);
+                recovery.recover(&mut measurements);
+                measurements
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_nlos_recovery_mode() {
+        assert!(NlosRecovery::new(NLOS_RECOVERY_REPORT).unwrap().is_none());
+        assert!(NlosRecovery::new(NLOS_RECOVERY_HOLD_LAST_LOS).unwrap().is_some());
+        assert!(NlosRecovery::new(2).is_err());
+        assert!(NlosRecovery::new(0xFF).is_err());
+    }
+
+    #[test]
+    fn test_nlos_recovery_hold_last_los() {
+        let mut recovery = NlosRecovery::new(NLOS_RECOVERY_HOLD_LAST_LOS).unwrap().unwrap();
+        assert_eq!(
+            recover_sequence(&mut recovery, &[(1, 50), (0, 100), (1, 300), (1, 400), (0, 120)]),
+            vec![
+                // No measurement in line of sight yet.
+                measurements(1, 1, 50),
+                measurements(1, 0, 100),
+                measurements(1, 1, 100),
+                measurements(1, 1, 100),
+                measurements(1, 0, 120),
+            ]
+        );
+        // NLOS unknown to the UWBS.
+        assert_eq!(
+            recover_sequence(&mut recovery, &[(0xFF, 500)]),
+            vec![measurements(1, 0xFF, 500)]
+        );
+    }
+
+    #[test]
+    fn test_nlos_recovery_per_controlee() {
+        let mut recovery = NlosRecovery::new(NLOS_RECOVERY_HOLD_LAST_LOS).unwrap().unwrap();
+        let mut first = measurements(1, 0, 100);
+        recovery.recover(&mut first);
+        let mut second = measurements(2, 1, 300);
+        recovery.recover(&mut second);
+        assert_eq!(second, measurements(2, 1, 300));
+    }
+}
@@ -14,6 +14,8 @@
 
 //! Implementation of NotificationManagerAndroid and its builder.
 
+use crate::chip_state::ChipState;
+use crate::helper::now_ms;
 use crate::jclass_name::{
     MULTICAST_LIST_UPDATE_STATUS_CLASS, UWB_DL_TDOA_MEASUREMENT_CLASS,
     UWB_OWR_AOA_MEASUREMENT_CLASS, UWB_RADAR_DATA_CLASS, UWB_RADAR_SWEEP_DATA_CLASS,
@@ -21,7 +23,7 @@ use crate::jclass_name::{
 };
 
 use std::collections::HashMap;
-use std::sync::Arc;
+use std::sync::{Arc, Mutex};
 
 use jni::errors::Error as JNIError;
 use jni::objects::{GlobalRef, JClass, JMethodID, JObject, JValue};
@@ -54,12 +56,13 @@ const MAX_RADAR_VENDOR_DATA_LEN: i32 = 256;
 // Maximum allowed number of Java Object to be allocated inside with_local_frame
 const MAX_JAVA_OBJECTS_CAPACITY: i32 = 50;
 
-enum MacAddress {
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub(crate) enum MacAddress {
     Short(u16),
     Extended(u64),
 }
 impl MacAddress {
-    fn into_ne_bytes(self) -> Vec<u8> {
+    pub(crate) fn into_ne_bytes(self) -> Vec<u8> {
         match self {
             MacAddress::Short(val) => val.to_ne_bytes().into(),
             MacAddress::Extended(val) => val.to_ne_bytes().into(),
@@ -67,21 +70,22 @@ impl MacAddress {
     }
 }
 
-struct TwoWayRangingMeasurement {
-    mac_address: MacAddress,
-    status: StatusCode,
-    nlos: u8,
-    distance: u16,
-    aoa_azimuth: u16,
-    aoa_azimuth_fom: u8,
-    aoa_elevation: u16,
-    aoa_elevation_fom: u8,
-    aoa_destination_azimuth: u16,
-    aoa_destination_azimuth_fom: u8,
-    aoa_destination_elevation: u16,
-    aoa_destination_elevation_fom: u8,
-    slot_index: u8,
-    rssi: u8,
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub(crate) struct TwoWayRangingMeasurement {
+    pub mac_address: MacAddress,
+    pub status: StatusCode,
+    pub nlos: u8,
+    pub distance: u16,
+    pub aoa_azimuth: u16,
+    pub aoa_azimuth_fom: u8,
+    pub aoa_elevation: u16,
+    pub aoa_elevation_fom: u8,
+    pub aoa_destination_azimuth: u16,
+    pub aoa_destination_azimuth_fom: u8,
+    pub aoa_destination_elevation: u16,
+    pub aoa_destination_elevation_fom: u8,
+    pub slot_index: u8,
+    pub rssi: u8,
 }
 
 struct OwrAoaRangingMeasurement {
@@ -264,6 +268,8 @@ pub(crate) struct NotificationManagerAndroid {
     pub jmethod_id_map: HashMap<String, JMethodID>,
     // jclass are cached for faster callback
     pub jclass_map: HashMap<String, GlobalRef>,
+    /// State of the chip, shared with the JNI calls.
+    pub chip_state: Arc<Mutex<ChipState>>,
 }
 
 // TODO(b/246678053): Need to add callbacks for Data Packet Rx, and Data Packet Tx events (like
@@ -317,6 +323,38 @@ impl NotificationManagerAndroid {
         Ok(jclass_map.get(class_name).unwrap().as_obj().into())
     }
 
+    /// Applies `update` on the state of the chip. A poisoned lock is logged, not propagated, so
+    /// that the notification still reaches the Java side.
+    fn update_chip_state<F: FnOnce(&mut ChipState)>(&self, update: F) {
+        match self.chip_state.lock() {
+            Ok(mut chip_state) => update(&mut chip_state),
+            Err(e) => error!("UCI JNI: failed to lock chip state: {:?}", e),
+        }
+    }
+
+    /// Updates the state of the chip on a ranging notification, and returns the range data to
+    /// deliver to the Java side, recovered from NLOS, with its AoA filtered and its distances
+    /// rounded, once its batch is complete and unless throttled. A poisoned lock is logged and the
+    /// range data delivered as is.
+    fn on_range_data(&self, mut range_data: SessionRangeData) -> Option<SessionRangeData> {
+        match self.chip_state.lock() {
+            Ok(mut chip_state) => {
+                let timestamp_ms = now_ms();
+                chip_state.recover_nlos(&mut range_data);
+                chip_state.filter_aoa(&mut range_data);
+                chip_state.round_distances(&mut range_data);
+                chip_state.on_range_data(&range_data);
+                chip_state.on_range_data_received(&range_data, timestamp_ms);
+                let range_data = chip_state.batch_range_data(range_data)?;
+                chip_state.throttle_range_data(range_data, timestamp_ms)
+            }
+            Err(e) => {
+                error!("UCI JNI: failed to lock chip state: {:?}", e);
+                Some(range_data)
+            }
+        }
+    }
+
     fn cached_jni_call(
         &mut self,
         name: &str,
@@ -977,6 +1015,21 @@ impl NotificationManagerAndroid {
         )
     }
 
+    fn on_data_credit_notification(
+        &mut self,
+        session_id: u32,
+        credit_availability: u8,
+    ) -> Result<JObject, JNIError> {
+        self.cached_jni_call(
+            "onDataCreditNotificationReceived",
+            "(JI)V",
+            &[
+                jvalue::from(JValue::Long(session_id as i64)),
+                jvalue::from(JValue::Int(credit_availability as i32)),
+            ],
+        )
+    }
+
     fn on_data_transfer_phase_config_notification(
         &mut self,
         session_id: u32,
@@ -996,6 +1049,9 @@ impl NotificationManagerAndroid {
 impl NotificationManager for NotificationManagerAndroid {
     fn on_core_notification(&mut self, core_notification: CoreNotification) -> UwbResult<()> {
         debug!("UCI JNI: core notification callback.");
+        if let CoreNotification::GenericError(generic_error) = core_notification {
+            self.update_chip_state(|s| s.on_generic_error(generic_error, now_ms()));
+        }
         let env = *self.env;
         env.with_local_frame(MAX_JAVA_OBJECTS_CAPACITY, || {
             let env_chip_id_jobject = *env.new_string(&self.chip_id).map_err(|e| {
@@ -1037,6 +1093,9 @@ impl NotificationManager for NotificationManagerAndroid {
             match session_notification {
                 // session_token below has already been mapped to session_id by uci layer.
                 SessionNotification::Status { session_token, session_state, reason_code } => {
+                    if session_state == SessionState::SessionStateDeinit {
+                        self.update_chip_state(|s| s.on_session_deinit(session_token));
+                    }
                     self.on_session_status_notification(session_token, session_state, reason_code)
                 }
                 SessionNotification::UpdateControllerMulticastList {
@@ -1050,6 +1109,11 @@ impl NotificationManager for NotificationManagerAndroid {
                 ),
                 // TODO(b/246678053): Match here on range_data.ranging_measurement_type instead.
                 SessionNotification::SessionInfo(range_data) => {
+                    let range_data = match self.on_range_data(range_data) {
+                        Some(range_data) => range_data,
+                        // The range data is delivered with the rest of its batch.
+                        None => return Ok(JObject::null()),
+                    };
                     match range_data.ranging_measurements {
                         uwb_core::uci::RangingMeasurements::ShortAddressTwoWay(_) => {
                             self.on_session_two_way_range_data_notification(range_data)
@@ -1082,15 +1146,11 @@ impl NotificationManager for NotificationManagerAndroid {
                     u8::from(status),
                     tx_count,
                 ),
-                // This session notification should not come here, as it's handled within
-                // UciManager, for internal state management related to sending data packet(s).
+                // The data credit is tracked by UciManager to send the data packets. The
+                // notifications it forwards, of either the grant or the exhaustion of the credit,
+                // are delivered for the flow control of large transfers.
                 SessionNotification::DataCredit { session_token, credit_availability } => {
-                    error!(
-                        "UCI JNI: Received unexpected DataCredit notification for \
-                            session_token {}, credit_availability {:?}",
-                        session_token, credit_availability
-                    );
-                    Err(JNIError::InvalidCtorReturn)
+                    self.on_data_credit_notification(session_token, u8::from(credit_availability))
                 }
                 SessionNotification::DataTransferPhaseConfig { session_token, status } => {
                     self.on_data_transfer_phase_config_notification(session_token, u8::from(status))
@@ -1143,6 +1203,16 @@ impl NotificationManager for NotificationManagerAndroid {
         data_rcv_notification: DataRcvNotification,
     ) -> UwbResult<()> {
         debug!("UCI JNI: Data Rcv notification callback.");
+        if data_rcv_notification.status == StatusCode::UciStatusOk {
+            // session_token has already been mapped to session_id by uci layer.
+            self.update_chip_state(|s| {
+                s.on_data_received(
+                    data_rcv_notification.session_token,
+                    data_rcv_notification.payload.len(),
+                    now_ms(),
+                )
+            });
+        }
         let env = *self.env;
         env.with_local_frame(MAX_JAVA_OBJECTS_CAPACITY, || {
             let source_address_jbytearray = match &data_rcv_notification.source_address {
@@ -1321,6 +1391,7 @@ pub(crate) struct NotificationManagerAndroidBuilder {
     pub vm: &'static Arc<JavaVM>,
     pub class_loader_obj: GlobalRef,
     pub callback_obj: GlobalRef,
+    pub chip_state: Arc<Mutex<ChipState>>,
 }
 
 impl NotificationManagerBuilder for NotificationManagerAndroidBuilder {
@@ -1335,9 +1406,41 @@ impl NotificationManagerBuilder for NotificationManagerAndroidBuilder {
                 callback_obj: self.callback_obj,
                 jmethod_id_map: HashMap::new(),
                 jclass_map: HashMap::new(),
+                chip_state: self.chip_state,
             })
         } else {
             None
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use uwb_core::uci::UciNotification;
+    use uwb_uci_packets::{CreditAvailability, UciControlPacket, UciNotificationPacket};
+
+    fn parse_session_notification(raw: &[u8]) -> SessionNotification {
+        let packet =
+            UciNotificationPacket::try_from(UciControlPacket::parse(raw).unwrap()).unwrap();
+        match UciNotification::try_from(packet).unwrap() {
+            UciNotification::Session(notification) => notification,
+            notification => panic!("Unexpected notification: {:?}", notification),
+        }
+    }
+
+    #[test]
+    fn test_parse_data_credit_notification() {
+        for (raw_availability, credit_availability) in [
+            (0x00, CreditAvailability::CreditNotAvailable),
+            (0x01, CreditAvailability::CreditAvailable),
+        ] {
+            // DATA_CREDIT_NTF (GID 0x01, OID 0x04) for the session token 0x01020304.
+            let raw = [0x61, 0x04, 0x00, 0x05, 0x04, 0x03, 0x02, 0x01, raw_availability];
+            assert_eq!(
+                parse_session_notification(&raw),
+                SessionNotification::DataCredit { session_token: 0x01020304, credit_availability }
+            );
+        }
+    }
+}
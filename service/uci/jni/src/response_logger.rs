@@ -0,0 +1,108 @@
+// Copyright 2026, The Android Open Source Project
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Capture of the raw UCI responses, whichever command produced them.
+
+use std::sync::{Arc, Mutex};
+
+use crate::chip_state::ChipState;
+
+use log::error;
+use uwb_core::error::Result;
+use uwb_core::uci::uci_logger::{UciLogger, UciLoggerMode};
+use uwb_uci_packets::{MessageType, Packet, UciControlPacket, UciDataPacket};
+
+/// UciLogger recording the raw UCI responses in the state of the chip, before handing all the
+/// packets to the wrapped logger. Since the UciManager only hands the packets allowed by its
+/// UciLoggerMode to its logger, no response is recorded while the logging is disabled, and the
+/// responses are recorded as filtered while the logging is filtered.
+pub(crate) struct ResponseCapturingLogger<L: UciLogger> {
+    logger: L,
+    chip_state: Arc<Mutex<ChipState>>,
+}
+
+/// Whether the raw UCI responses are recorded while the UCI logging is in `mode`, see
+/// ResponseCapturingLogger.
+pub(crate) fn captures_raw_responses(mode: &UciLoggerMode) -> bool {
+    !matches!(mode, UciLoggerMode::Disabled)
+}
+
+impl<L: UciLogger> ResponseCapturingLogger<L> {
+    /// Constructs the logger, wrapping `logger`.
+    pub fn new(logger: L, chip_state: Arc<Mutex<ChipState>>) -> Self {
+        Self { logger, chip_state }
+    }
+}
+
+impl<L: UciLogger> UciLogger for ResponseCapturingLogger<L> {
+    fn log_uci_control_packet(&mut self, packet: UciControlPacket) {
+        if packet.get_message_type() == MessageType::Response {
+            match self.chip_state.lock() {
+                Ok(mut chip_state) => chip_state.on_raw_response(packet.clone().to_vec()),
+                Err(e) => error!("UCI JNI: failed to lock chip state: {:?}", e),
+            }
+        }
+        self.logger.log_uci_control_packet(packet);
+    }
+
+    fn log_uci_data_packet(&mut self, packet: &UciDataPacket) {
+        self.logger.log_uci_data_packet(packet);
+    }
+
+    fn log_hal_open(&mut self, result: Result<()>) {
+        self.logger.log_hal_open(result);
+    }
+
+    fn log_hal_close(&mut self, result: Result<()>) {
+        self.logger.log_hal_close(result);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use uwb_core::uci::uci_logger::NopUciLogger;
+    use uwb_uci_packets::{DeviceResetCmdBuilder, DeviceResetRspBuilder, ResetConfig, StatusCode};
+
+    #[test]
+    fn test_response_capturing_logger() {
+        let chip_state = Arc::new(Mutex::new(ChipState::default()));
+        let mut logger = ResponseCapturingLogger::new(NopUciLogger::default(), chip_state.clone());
+
+        logger.log_uci_control_packet(
+            DeviceResetCmdBuilder { reset_config: ResetConfig::UwbsReset }.build().into(),
+        );
+        logger.log_uci_control_packet(
+            DeviceResetRspBuilder { status: StatusCode::UciStatusOk }.build().into(),
+        );
+        logger.log_uci_control_packet(
+            DeviceResetRspBuilder { status: StatusCode::UciStatusFailed }.build().into(),
+        );
+
+        // Only the responses are recorded: DEVICE_RESET_RSP, with the OK then FAILED status.
+        assert_eq!(
+            chip_state.lock().unwrap().last_raw_responses(3),
+            vec![vec![0x40, 0x00, 0x00, 0x01, 0x00], vec![0x40, 0x00, 0x00, 0x01, 0x02]]
+        );
+    }
+
+    #[test]
+    fn test_captures_raw_responses() {
+        // The UciManager doesn't hand any packet to its logger while the logging is disabled.
+        assert!(!captures_raw_responses(&UciLoggerMode::Disabled));
+        assert!(captures_raw_responses(&UciLoggerMode::Filtered));
+        assert!(captures_raw_responses(&UciLoggerMode::Unfiltered));
+    }
+}
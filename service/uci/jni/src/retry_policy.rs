@@ -0,0 +1,127 @@
+// Copyright 2026, The Android Open Source Project
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Automatic retry of the UCI commands the UWBS asks to retry.
+
+use std::thread;
+use std::time::Duration;
+
+use log::debug;
+use uwb_core::error::{Error, Result};
+
+/// Policy of the retry of the UCI commands answered with UCI_STATUS_COMMAND_RETRY. The other
+/// statuses are never retried. Only the commands sent with Dispatcher::record_command are retried.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(crate) struct RetryPolicy {
+    /// Number of times a command is sent at most, including the first attempt.
+    pub max_attempts: u32,
+    /// Delay before resending a command.
+    pub backoff: Duration,
+}
+
+impl Default for RetryPolicy {
+    /// Commands are not retried by default.
+    fn default() -> Self {
+        Self { max_attempts: 1, backoff: Duration::ZERO }
+    }
+}
+
+impl RetryPolicy {
+    /// Constructs the policy. A command is sent at least once.
+    pub fn new(max_attempts: u32, backoff: Duration) -> Result<Self> {
+        if max_attempts == 0 {
+            return Err(Error::BadParameters);
+        }
+        Ok(Self { max_attempts, backoff })
+    }
+
+    /// Sends the command, and resends it after the backoff as long as the UWBS asks to retry it,
+    /// up to `max_attempts` times in total.
+    pub fn run<T>(&self, mut command: impl FnMut() -> Result<T>) -> Result<T> {
+        let mut attempt = 1;
+        loop {
+            match command() {
+                Err(Error::CommandRetry) if attempt < self.max_attempts => {
+                    debug!("UCI JNI: retrying command, attempt {} failed", attempt);
+                    thread::sleep(self.backoff);
+                    attempt += 1;
+                }
+                result => return result,
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::test_utils::new_mock_uci_manager_sync;
+
+    use uwb_core::uci::mock_uci_manager::MockUciManager;
+    use uwb_uci_packets::SessionType;
+
+    #[test]
+    fn test_retry_policy_retries_command() {
+        let mut uci_manager_impl = MockUciManager::new();
+        for out in [Err(Error::CommandRetry), Err(Error::CommandRetry), Ok(())] {
+            uci_manager_impl.expect_session_init(42, SessionType::FiraRangingSession, vec![], out);
+        }
+        let (_rt, uci_manager_sync) = new_mock_uci_manager_sync(uci_manager_impl);
+
+        let policy = RetryPolicy::new(3, Duration::from_millis(1)).unwrap();
+        let mut attempts = 0;
+        let result = policy.run(|| {
+            attempts += 1;
+            uci_manager_sync.session_init(42, SessionType::FiraRangingSession)
+        });
+        assert!(result.is_ok());
+        assert_eq!(attempts, 3);
+    }
+
+    #[test]
+    fn test_retry_policy_bounds_attempts() {
+        let policy = RetryPolicy::new(2, Duration::ZERO).unwrap();
+        let mut attempts = 0;
+        let result: Result<()> = policy.run(|| {
+            attempts += 1;
+            Err(Error::CommandRetry)
+        });
+        assert!(matches!(result, Err(Error::CommandRetry)));
+        assert_eq!(attempts, 2);
+
+        assert!(RetryPolicy::new(0, Duration::ZERO).is_err());
+    }
+
+    #[test]
+    fn test_retry_policy_ignores_other_errors() {
+        let policy = RetryPolicy::new(3, Duration::ZERO).unwrap();
+        let mut attempts = 0;
+        let result: Result<()> = policy.run(|| {
+            attempts += 1;
+            Err(Error::BadParameters)
+        });
+        assert!(matches!(result, Err(Error::BadParameters)));
+        assert_eq!(attempts, 1);
+
+        // The default policy does not retry.
+        let mut attempts = 0;
+        let result: Result<()> = RetryPolicy::default().run(|| {
+            attempts += 1;
+            Err(Error::CommandRetry)
+        });
+        assert!(result.is_err());
+        assert_eq!(attempts, 1);
+    }
+}
@@ -0,0 +1,313 @@
+// Copyright 2026, The Android Open Source Project
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Queries and deinitialization of all the sessions at once, and waiting for the state transitions
+//! of the sessions beyond the UCI command timeout.
+//!
+//! Some UWBS take longer than the command timeout of UciManager to transition the state of a
+//! session, e.g. under heavy multicast loads, and still complete the transition after the command
+//! timed out. The JNI calls taking a timeout poll the session state until then.
+
+use std::thread;
+use std::time::{Duration, Instant};
+
+use log::error;
+use uwb_core::error::{Error, Result};
+use uwb_core::uci::uci_manager_sync::UciManagerSync;
+use uwb_core::uci::UciManager;
+use uwb_uci_packets::SessionState;
+
+/// Gets the state of each of the sessions, in the order of their ids. A session whose query fails
+/// is skipped, without failing the other queries.
+pub(crate) fn session_get_all_states<U: UciManager>(
+    uci_manager: &UciManagerSync<U>,
+    mut session_ids: Vec<u32>,
+) -> Vec<(u32, SessionState)> {
+    session_ids.sort_unstable();
+    session_ids
+        .into_iter()
+        .filter_map(|session_id| match uci_manager.session_get_state(session_id) {
+            Ok(state) => Some((session_id, state)),
+            Err(e) => {
+                error!("Failed to get the state of session {}: {:?}", session_id, e);
+                None
+            }
+        })
+        .collect()
+}
+
+/// Queries the max application data size of each of the sessions, in the order of their ids. The
+/// size of a session whose query fails is reported as 0, without failing the other queries.
+pub(crate) fn session_query_max_data_size_all<U: UciManager>(
+    uci_manager: &UciManagerSync<U>,
+    mut session_ids: Vec<u32>,
+) -> Vec<(u32, u16)> {
+    session_ids.sort_unstable();
+    session_ids
+        .into_iter()
+        .map(|session_id| match uci_manager.session_query_max_data_size(session_id) {
+            Ok(size) => (session_id, size),
+            Err(e) => {
+                error!("Failed to query the max data size of session {}: {:?}", session_id, e);
+                (session_id, 0)
+            }
+        })
+        .collect()
+}
+
+/// Deinitializes each of the sessions with `session_deinit`, in the order of their ids, carrying on
+/// with the next sessions when one fails. Returns the result of each session.
+pub(crate) fn session_deinit_all(
+    mut session_ids: Vec<u32>,
+    mut session_deinit: impl FnMut(u32) -> Result<()>,
+) -> Vec<(u32, Result<()>)> {
+    session_ids.sort_unstable();
+    session_ids
+        .into_iter()
+        .map(|session_id| {
+            let result = session_deinit(session_id);
+            if let Err(e) = &result {
+                error!("Failed to deinitialize session {}: {:?}", session_id, e);
+            }
+            (session_id, result)
+        })
+        .collect()
+}
+
+/// Completes the `result` of the initialization of the session with the token the UWBS allocated
+/// to it.
+pub(crate) fn session_token_after_init<U: UciManager>(
+    uci_manager: &UciManagerSync<U>,
+    session_id: u32,
+    result: Result<()>,
+) -> Result<u32> {
+    result?;
+    uci_manager.get_session_token(session_id)
+}
+
+// Interval between two polls of the session state.
+const SESSION_STATE_POLL_INTERVAL: Duration = Duration::from_millis(20);
+
+/// Completes the `result` of a command transitioning the session to `state`, sent at `start`. If
+/// the command timed out and `timeout_ms` is positive, polls the state of the session with
+/// `get_state` until `timeout_ms` after `start`, and at least once. Otherwise the result of the
+/// command is returned as is.
+///
+/// The command itself can not be given a timeout, so the call lasts at least the command timeout
+/// of UciManager, even if longer than `timeout_ms`. `get_state` is called without any lock held
+/// between the polls, so that other commands can be sent meanwhile.
+///
+/// Fails with Error::Unknown, rather than the Error::Timeout of the command, if the session did
+/// not reach `state` in time.
+pub(crate) fn session_transition_with_timeout(
+    start: Instant,
+    state: SessionState,
+    timeout_ms: i32,
+    result: Result<()>,
+    mut get_state: impl FnMut() -> Result<SessionState>,
+) -> Result<()> {
+    match result {
+        Err(Error::Timeout) if timeout_ms > 0 => {
+            let deadline = start + Duration::from_millis(timeout_ms as u64);
+            loop {
+                if get_state()? == state {
+                    return Ok(());
+                }
+                let now = Instant::now();
+                if now >= deadline {
+                    error!("Session did not reach {:?} in {} ms", state, timeout_ms);
+                    return Err(Error::Unknown);
+                }
+                thread::sleep(SESSION_STATE_POLL_INTERVAL.min(deadline - now));
+            }
+        }
+        result => result,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::test_utils::new_mock_uci_manager_sync;
+
+    use uwb_core::uci::mock_uci_manager::MockUciManager;
+    use uwb_uci_packets::SessionType;
+
+    #[test]
+    fn test_session_transition_with_timeout() {
+        let mut uci_manager_impl = MockUciManager::new();
+        uci_manager_impl.expect_range_start(42, Err(Error::Timeout));
+        uci_manager_impl.expect_session_get_state(42, Ok(SessionState::SessionStateIdle));
+        uci_manager_impl.expect_session_get_state(42, Ok(SessionState::SessionStateActive));
+        let (_rt, uci_manager_sync) = new_mock_uci_manager_sync(uci_manager_impl);
+
+        let start = Instant::now();
+        let result = uci_manager_sync.range_start(42);
+        assert_eq!(
+            session_transition_with_timeout(
+                start,
+                SessionState::SessionStateActive,
+                1000,
+                result,
+                || uci_manager_sync.session_get_state(42),
+            ),
+            Ok(())
+        );
+    }
+
+    #[test]
+    fn test_session_transition_with_expired_timeout() {
+        let mut uci_manager_impl = MockUciManager::new();
+        uci_manager_impl.expect_range_stop(42, Err(Error::Timeout));
+        uci_manager_impl.expect_session_get_state(42, Ok(SessionState::SessionStateActive));
+        let (_rt, uci_manager_sync) = new_mock_uci_manager_sync(uci_manager_impl);
+
+        // The timeout already expired when the command timed out: the state is polled only once.
+        let start = Instant::now() - Duration::from_millis(200);
+        let result = uci_manager_sync.range_stop(42);
+        assert_eq!(
+            session_transition_with_timeout(
+                start,
+                SessionState::SessionStateIdle,
+                100,
+                result,
+                || uci_manager_sync.session_get_state(42),
+            ),
+            Err(Error::Unknown)
+        );
+
+        // The polls stop at the timeout.
+        let mut polls = 0;
+        let start = Instant::now();
+        assert_eq!(
+            session_transition_with_timeout(
+                start,
+                SessionState::SessionStateIdle,
+                50,
+                Err(Error::Timeout),
+                || {
+                    polls += 1;
+                    Ok(SessionState::SessionStateActive)
+                },
+            ),
+            Err(Error::Unknown)
+        );
+        assert!(start.elapsed() >= Duration::from_millis(50));
+        assert!((1..=4).contains(&polls));
+    }
+
+    #[test]
+    fn test_session_transition_with_default_timeout() {
+        let uci_manager_impl = MockUciManager::new();
+        let (_rt, uci_manager_sync) = new_mock_uci_manager_sync(uci_manager_impl);
+
+        // The session state is not polled without a positive timeout, nor if the command did not
+        // time out.
+        for timeout_ms in [0, -1] {
+            assert_eq!(
+                session_transition_with_timeout(
+                    Instant::now(),
+                    SessionState::SessionStateActive,
+                    timeout_ms,
+                    Err(Error::Timeout),
+                    || uci_manager_sync.session_get_state(42),
+                ),
+                Err(Error::Timeout)
+            );
+        }
+        assert_eq!(
+            session_transition_with_timeout(
+                Instant::now(),
+                SessionState::SessionStateActive,
+                100,
+                Err(Error::BadParameters),
+                || uci_manager_sync.session_get_state(42),
+            ),
+            Err(Error::BadParameters)
+        );
+    }
+
+    #[test]
+    fn test_session_get_all_states() {
+        let mut uci_manager_impl = MockUciManager::new();
+        uci_manager_impl.expect_session_get_state(1, Ok(SessionState::SessionStateActive));
+        uci_manager_impl.expect_session_get_state(2, Err(Error::BadParameters));
+        uci_manager_impl.expect_session_get_state(3, Ok(SessionState::SessionStateIdle));
+        let (_rt, uci_manager_sync) = new_mock_uci_manager_sync(uci_manager_impl);
+
+        // The session failing its query is skipped.
+        assert_eq!(
+            session_get_all_states(&uci_manager_sync, vec![3, 1, 2]),
+            vec![(1, SessionState::SessionStateActive), (3, SessionState::SessionStateIdle)]
+        );
+        assert_eq!(session_get_all_states(&uci_manager_sync, vec![]), vec![]);
+    }
+
+    #[test]
+    fn test_session_query_max_data_size_all() {
+        let mut uci_manager_impl = MockUciManager::new();
+        uci_manager_impl.expect_session_query_max_data_size(1, Ok(1024));
+        uci_manager_impl.expect_session_query_max_data_size(2, Err(Error::BadParameters));
+        uci_manager_impl.expect_session_query_max_data_size(3, Ok(255));
+        let (_rt, uci_manager_sync) = new_mock_uci_manager_sync(uci_manager_impl);
+
+        assert_eq!(
+            session_query_max_data_size_all(&uci_manager_sync, vec![3, 1, 2]),
+            vec![(1, 1024), (2, 0), (3, 255)]
+        );
+    }
+
+    #[test]
+    fn test_session_deinit_all() {
+        let mut uci_manager_impl = MockUciManager::new();
+        uci_manager_impl.expect_session_deinit(1, Ok(()));
+        uci_manager_impl.expect_session_deinit(2, Err(Error::Timeout));
+        uci_manager_impl.expect_session_deinit(3, Ok(()));
+        let (_rt, uci_manager_sync) = new_mock_uci_manager_sync(uci_manager_impl);
+
+        let results = session_deinit_all(vec![3, 1, 2], |session_id| {
+            uci_manager_sync.session_deinit(session_id)
+        });
+        assert_eq!(
+            results.iter().map(|(session_id, _)| *session_id).collect::<Vec<_>>(),
+            vec![1, 2, 3]
+        );
+        assert!(results[0].1.is_ok());
+        assert!(matches!(results[1].1, Err(Error::Timeout)));
+        assert!(results[2].1.is_ok());
+
+        // Without active session, nothing is deinitialized.
+        assert!(session_deinit_all(vec![], |_| panic!("no session to deinitialize")).is_empty());
+    }
+
+    #[test]
+    fn test_session_token_after_init() {
+        let mut uci_manager_impl = MockUciManager::new();
+        uci_manager_impl.expect_session_init(42, SessionType::FiraRangingSession, vec![], Ok(()));
+        uci_manager_impl.expect_session_init(
+            43,
+            SessionType::FiraRangingSession,
+            vec![],
+            Err(Error::BadParameters),
+        );
+        let (_rt, uci_manager_sync) = new_mock_uci_manager_sync(uci_manager_impl);
+
+        // The mock UciManager uses the session id as the session token.
+        let result = uci_manager_sync.session_init(42, SessionType::FiraRangingSession);
+        assert_eq!(session_token_after_init(&uci_manager_sync, 42, result).unwrap(), 42);
+        let result = uci_manager_sync.session_init(43, SessionType::FiraRangingSession);
+        assert!(session_token_after_init(&uci_manager_sync, 43, result).is_err());
+    }
+}
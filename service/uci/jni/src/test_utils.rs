@@ -0,0 +1,83 @@
+// Copyright 2026, The Android Open Source Project
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Utilities shared by the unit tests of this crate.
+
+use tokio::runtime::{Builder, Runtime};
+use uwb_core::error::Result;
+use uwb_core::params::RawUciMessage;
+use uwb_core::uci::mock_uci_manager::MockUciManager;
+use uwb_core::uci::uci_manager_sync::{
+    NotificationManager, NotificationManagerBuilder, UciManagerSync,
+};
+use uwb_core::uci::{
+    CoreNotification, DataRcvNotification, RadarDataRcvNotification, SessionNotification,
+};
+
+pub(crate) struct NullNotificationManager {}
+impl NotificationManager for NullNotificationManager {
+    fn on_core_notification(&mut self, _core_notification: CoreNotification) -> Result<()> {
+        Ok(())
+    }
+    fn on_session_notification(
+        &mut self,
+        _session_notification: SessionNotification,
+    ) -> Result<()> {
+        Ok(())
+    }
+    fn on_vendor_notification(&mut self, _vendor_notification: RawUciMessage) -> Result<()> {
+        Ok(())
+    }
+    fn on_data_rcv_notification(&mut self, _data_rcv_notf: DataRcvNotification) -> Result<()> {
+        Ok(())
+    }
+    /// Callback for RadarDataRcvNotification.
+    fn on_radar_data_rcv_notification(
+        &mut self,
+        _radar_data_rcv_notification: RadarDataRcvNotification,
+    ) -> Result<()> {
+        Ok(())
+    }
+}
+
+pub(crate) struct NullNotificationManagerBuilder {}
+
+impl NullNotificationManagerBuilder {
+    pub fn new() -> Self {
+        Self {}
+    }
+}
+
+impl NotificationManagerBuilder for NullNotificationManagerBuilder {
+    type NotificationManager = NullNotificationManager;
+
+    fn build(self) -> Option<Self::NotificationManager> {
+        Some(NullNotificationManager {})
+    }
+}
+
+/// Constructs a UciManagerSync backed by the given MockUciManager. The returned runtime must be
+/// kept alive for as long as the UciManagerSync is used.
+pub(crate) fn new_mock_uci_manager_sync(
+    uci_manager_impl: MockUciManager,
+) -> (Runtime, UciManagerSync<MockUciManager>) {
+    let test_rt = Builder::new_multi_thread().enable_all().build().unwrap();
+    let uci_manager_sync = UciManagerSync::new_mock(
+        uci_manager_impl,
+        test_rt.handle().to_owned(),
+        NullNotificationManagerBuilder::new(),
+    )
+    .unwrap();
+    (test_rt, uci_manager_sync)
+}
@@ -0,0 +1,162 @@
+// Copyright 2026, The Android Open Source Project
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Resolution of the UWBS timestamps reported by CORE_QUERY_UWBS_TIMESTAMP.
+
+use std::thread;
+use std::time::{Duration, Instant};
+
+use log::{debug, error};
+use uwb_core::error::{Error, Result};
+use uwb_core::uci::uci_manager_sync::UciManagerSync;
+use uwb_core::uci::UciManager;
+
+/// Time between the two timestamps queried to measure the resolution.
+pub(crate) const TIMESTAMP_CALIBRATION_INTERVAL: Duration = Duration::from_millis(20);
+
+// Relative error tolerated on the measured tick to recognize the us and ns units, covering the
+// latency of the UCI commands over the calibration interval.
+const TICK_TOLERANCE: f64 = 0.25;
+
+/// Resolution of the UWBS timestamps. FiRa defines the timestamps in us, but some UWBS report them
+/// in ns or in a device specific tick.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(crate) enum TimestampResolution {
+    Micros,
+    Nanos,
+    /// Device specific tick, of the given duration in ns, rounded and at least 1 ns.
+    Ticks(u32),
+}
+
+impl TimestampResolution {
+    /// Unit of the timestamps, as UwbTimestamp.UNIT_*.
+    pub fn unit(&self) -> i32 {
+        match self {
+            Self::Micros => 0,
+            Self::Nanos => 1,
+            Self::Ticks(_) => 2,
+        }
+    }
+
+    /// Duration, in ns, of a timestamp tick.
+    pub fn resolution_ns(&self) -> u32 {
+        match self {
+            Self::Micros => 1000,
+            Self::Nanos => 1,
+            Self::Ticks(resolution_ns) => *resolution_ns,
+        }
+    }
+}
+
+/// Derives the resolution of the UWBS timestamps from the `ticks` they advanced by over `elapsed`.
+/// Fails if the timestamps did not advance.
+pub(crate) fn timestamp_resolution_from_elapsed(
+    ticks: u64,
+    elapsed: Duration,
+) -> Result<TimestampResolution> {
+    if ticks == 0 {
+        error!("UCI JNI: the UWBS timestamp did not advance over {:?}", elapsed);
+        return Err(Error::Unknown);
+    }
+    let tick_ns = elapsed.as_nanos() as f64 / ticks as f64;
+    let is_close_to = |unit_ns: f64| (tick_ns - unit_ns).abs() <= unit_ns * TICK_TOLERANCE;
+    Ok(if is_close_to(1000.0) {
+        TimestampResolution::Micros
+    } else if is_close_to(1.0) {
+        TimestampResolution::Nanos
+    } else {
+        TimestampResolution::Ticks(tick_ns.round().clamp(1.0, u32::MAX as f64) as u32)
+    })
+}
+
+/// Measures the resolution of the UWBS timestamps, from two timestamps queried `interval` apart.
+pub(crate) fn query_timestamp_resolution<U: UciManager>(
+    uci_manager: &UciManagerSync<U>,
+    interval: Duration,
+) -> Result<TimestampResolution> {
+    let first = uci_manager.core_query_uwb_timestamp()?;
+    let start = Instant::now();
+    thread::sleep(interval);
+    let second = uci_manager.core_query_uwb_timestamp()?;
+    let resolution =
+        timestamp_resolution_from_elapsed(second.wrapping_sub(first), start.elapsed())?;
+    debug!("UCI JNI: UWBS timestamp resolution {:?}", resolution);
+    Ok(resolution)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::test_utils::new_mock_uci_manager_sync;
+
+    use uwb_core::uci::mock_uci_manager::MockUciManager;
+
+    #[test]
+    fn test_timestamp_resolution_from_elapsed() {
+        let elapsed = Duration::from_millis(20);
+        assert_eq!(
+            timestamp_resolution_from_elapsed(20_000, elapsed).unwrap(),
+            TimestampResolution::Micros
+        );
+        // Latency of the commands within the tolerance.
+        assert_eq!(
+            timestamp_resolution_from_elapsed(18_000, elapsed).unwrap(),
+            TimestampResolution::Micros
+        );
+        assert_eq!(
+            timestamp_resolution_from_elapsed(20_000_000, elapsed).unwrap(),
+            TimestampResolution::Nanos
+        );
+        assert_eq!(
+            timestamp_resolution_from_elapsed(1_280_000, elapsed).unwrap(),
+            TimestampResolution::Ticks(16)
+        );
+        // Ticks shorter than 1 ns.
+        assert_eq!(
+            timestamp_resolution_from_elapsed(1_277_952_000, elapsed).unwrap(),
+            TimestampResolution::Ticks(1)
+        );
+        assert!(timestamp_resolution_from_elapsed(0, elapsed).is_err());
+    }
+
+    #[test]
+    fn test_timestamp_resolution_unit() {
+        assert_eq!(TimestampResolution::Micros.unit(), 0);
+        assert_eq!(TimestampResolution::Micros.resolution_ns(), 1000);
+        assert_eq!(TimestampResolution::Nanos.unit(), 1);
+        assert_eq!(TimestampResolution::Nanos.resolution_ns(), 1);
+        assert_eq!(TimestampResolution::Ticks(16).unit(), 2);
+        assert_eq!(TimestampResolution::Ticks(16).resolution_ns(), 16);
+    }
+
+    #[test]
+    fn test_query_timestamp_resolution() {
+        let mut uci_manager_impl = MockUciManager::new();
+        uci_manager_impl.expect_core_query_uwb_timestamp(Ok(1_000_000));
+        uci_manager_impl.expect_core_query_uwb_timestamp(Ok(1_020_000));
+        uci_manager_impl.expect_core_query_uwb_timestamp(Ok(1_020_000));
+        uci_manager_impl.expect_core_query_uwb_timestamp(Err(Error::Timeout));
+        let (_rt, uci_manager_sync) = new_mock_uci_manager_sync(uci_manager_impl);
+
+        assert_eq!(
+            query_timestamp_resolution(&uci_manager_sync, TIMESTAMP_CALIBRATION_INTERVAL).unwrap(),
+            TimestampResolution::Micros
+        );
+        assert!(matches!(
+            query_timestamp_resolution(&uci_manager_sync, TIMESTAMP_CALIBRATION_INTERVAL),
+            Err(Error::Timeout)
+        ));
+    }
+}
@@ -14,35 +14,81 @@
 
 //! Implementation of JNI functions.
 
-use crate::dispatcher::Dispatcher;
-use crate::helper::{boolean_result_helper, byte_result_helper, option_result_helper};
+use crate::app_config::{
+    aoa_bound_tlv, block_duration_multiplier_tlv, block_timing_base_rstu,
+    data_retransmission_count_tlv, data_transfer_status_ntf_tlv, decode_block_duration_multiplier,
+    decode_le_value, decode_mac_mode, decode_sts_mode, find_mac_address_mode, fira_cfg_id,
+    hopping_sequence_tlv, is_data_encrypted, is_result_report_phase_enabled, mac_address_mode_tlv,
+    owr_aoa_ranging_interval_tlv, preamble_duration_symbols, preamble_duration_tlv,
+    psdu_data_rate_tlv, ranging_error_policy_tlv, ranging_time_offset_tlv, result_report_phase_tlv,
+    rframe_config_tlv, session_get_aoa_bound, session_get_app_config_value,
+    session_get_effective_block_duration_ms, session_get_max_inband_payload, session_get_role,
+    session_set_app_config_tlv, session_set_app_config_tlvs, session_set_mac_mode,
+    session_set_priority, set_app_configs_batch, slot_duration_tlv, slots_per_round_tlv,
+    sts_key_refresh_rounds_tlvs, sub_session_id_tlv, tx_per_round_tlv, validate_app_config_tlvs,
+    BLOCK_STRIDE_LENGTH, MAC_ADDRESS_MODE, MAC_ADDRESS_MODE_EXTENDED,
+    MAC_ADDRESS_MODE_EXTENDED_SHORT_HEADER, MAC_ADDRESS_MODE_SHORT, MIN_FRAMES_PER_RR,
+    SESSION_DATA_TRANSFER_STATUS_NTF_CONFIG,
+};
+use crate::caps::{
+    find_cap_u32, find_fira_phy_version_range, find_max_ranging_sessions,
+    find_supported_bandwidths_khz, find_supported_round_usages, find_supported_sts_key_lengths,
+    is_concurrent_ranging_radar_supported, is_hprf_supported, is_provisioned_sts_supported,
+    is_sub_session_key_supported, DeviceCapabilities, SUPPORTED_MIN_SLOT_DURATION_RSTU,
+};
+use crate::chip_state::{
+    HalState, LatencyObserver, CORE_DEVICE_RESET, CORE_GET_CAPS_INFO, SESSION_DEINIT,
+    SESSION_GET_APP_CONFIG, SESSION_INIT, SESSION_SET_APP_CONFIG, SESSION_START, SESSION_STOP,
+};
+use crate::data_transfer::{fragmented_send_outcome, send_data_fragmented};
+use crate::dispatcher::{Dispatcher, GuardedUciManager, VendorCmdAllowlist};
+use crate::helper::{
+    boolean_result_helper, byte_result_helper, error_to_status_code, now_ms, option_result_helper,
+};
 use crate::jclass_name::{
-    CONFIG_STATUS_DATA_CLASS, DT_RANGING_ROUNDS_STATUS_CLASS, POWER_STATS_CLASS, TLV_DATA_CLASS,
-    UWB_DEVICE_INFO_RESPONSE_CLASS, UWB_RANGING_DATA_CLASS, VENDOR_RESPONSE_CLASS,
+    CONFIG_STATUS_DATA_CLASS, DT_RANGING_ROUNDS_STATUS_CLASS, POWER_STATS_CLASS,
+    POWER_STATS_LONG_CLASS, TLV_DATA_CLASS, UWB_DEVICE_CAPABILITIES_CLASS,
+    UWB_DEVICE_INFO_RESPONSE_CLASS, UWB_RANGING_DATA_CLASS, UWB_TIMESTAMP_CLASS,
+    UWB_TWO_WAY_MEASUREMENT_CLASS, VENDOR_RESPONSE_CLASS,
+};
+use crate::notification_manager_android::TwoWayRangingMeasurement;
+use crate::response_logger::captures_raw_responses;
+use crate::retry_policy::RetryPolicy;
+use crate::session_state::{
+    session_deinit_all, session_get_all_states, session_query_max_data_size_all,
+    session_token_after_init, session_transition_with_timeout,
+};
+use crate::timestamp::{
+    query_timestamp_resolution, TimestampResolution, TIMESTAMP_CALIBRATION_INTERVAL,
 };
 use crate::unique_jvm;
 
 use std::convert::TryInto;
 use std::iter::zip;
+use std::time::{Duration, Instant};
 
 use jni::errors::Error as JNIError;
 use jni::objects::{GlobalRef, JObject, JString, JValue};
 use jni::signature::ReturnType;
 use jni::sys::{
-    jboolean, jbyte, jbyteArray, jint, jintArray, jlong, jobject, jobjectArray, jshort, jvalue,
+    jboolean, jbyte, jbyteArray, jfloat, jint, jintArray, jlong, jlongArray, jobject, jobjectArray,
+    jshort, jstring, jvalue,
 };
 use jni::JNIEnv;
-use log::{debug, error};
+use log::{debug, error, info, warn};
 use uwb_core::error::{Error, Result};
 use uwb_core::params::{
     AndroidRadarConfigResponse, AppConfigTlv, CountryCode, GetDeviceInfoResponse, PhaseList,
     RadarConfigTlv, RawAppConfigTlv, RawUciMessage, SessionUpdateDtTagRangingRoundsResponse,
     SetAppConfigResponse, UpdateTime,
 };
+use uwb_core::uci::uci_logger::UciLoggerMode;
+use uwb_core::uci::uci_manager_sync::UciManagerSync;
+use uwb_core::uci::UciManager;
 use uwb_uci_packets::{
     AppConfigTlvType, CapTlv, Controlee, Controlee_V2_0_16_Byte_Version,
-    Controlee_V2_0_32_Byte_Version, Controlees, PowerStats, ResetConfig, SessionState, SessionType,
-    StatusCode, UpdateMulticastListAction,
+    Controlee_V2_0_32_Byte_Version, Controlees, PowerStats, RadarConfigTlvType, ResetConfig,
+    SessionState, SessionType, StatusCode, UpdateMulticastListAction,
 };
 
 /// Macro capturing the name of the function calling this macro.
@@ -141,7 +187,89 @@ fn native_do_initialize(
     chip_id: JString,
 ) -> Result<GetDeviceInfoResponse> {
     let uci_manager = Dispatcher::get_uci_manager(env, obj, chip_id)?;
-    uci_manager.open_hal()
+    let device_info =
+        open_hal_with_state(&*uci_manager, |state| update_hal_state(&uci_manager, state))?;
+    uci_manager.chip_state()?.on_device_info(device_info.clone());
+    Ok(device_info)
+}
+
+/// Opens the HAL, reporting each transition of its state to `on_hal_state`.
+fn open_hal_with_state<U: UciManager>(
+    uci_manager: &UciManagerSync<U>,
+    mut on_hal_state: impl FnMut(HalState),
+) -> Result<GetDeviceInfoResponse> {
+    on_hal_state(HalState::Opening);
+    let result = uci_manager.open_hal();
+    on_hal_state(if result.is_ok() { HalState::Open } else { HalState::Error });
+    result
+}
+
+/// Closes the HAL, reporting the transition of its state to `on_hal_state`.
+fn close_hal_with_state<U: UciManager>(
+    uci_manager: &UciManagerSync<U>,
+    mut on_hal_state: impl FnMut(HalState),
+) -> Result<()> {
+    let result = uci_manager.close_hal(true);
+    on_hal_state(if result.is_ok() { HalState::Closed } else { HalState::Error });
+    result
+}
+
+fn update_hal_state(uci_manager: &GuardedUciManager, hal_state: HalState) {
+    match uci_manager.chip_state() {
+        Ok(mut chip_state) => chip_state.set_hal_state(hal_state),
+        Err(e) => error!("Failed to update HAL state to {:?}: {:?}", hal_state, e),
+    }
+}
+
+/// Get the state of the HAL channel of a single UWB device: 0 for Closed, 1 for Opening, 2 for Open
+/// and 3 for Error. Return -1 if failed.
+#[no_mangle]
+pub extern "system" fn Java_com_android_server_uwb_jni_NativeUwbManager_nativeGetHalState(
+    env: JNIEnv,
+    obj: JObject,
+    chip_id: JString,
+) -> jint {
+    debug!("{}: enter", function_name!());
+    match option_result_helper(native_get_hal_state(env, obj, chip_id), function_name!()) {
+        Some(hal_state) => hal_state as jint,
+        None => -1,
+    }
+}
+
+fn native_get_hal_state(env: JNIEnv, obj: JObject, chip_id: JString) -> Result<HalState> {
+    let uci_manager = Dispatcher::get_uci_manager(env, obj, chip_id)?;
+    let hal_state = uci_manager.chip_state()?.hal_state();
+    Ok(hal_state)
+}
+
+/// Get the device info reported by the UWBS when the HAL was last opened, without opening the HAL
+/// again. Return null JObject if not cached yet or if failed.
+#[no_mangle]
+pub extern "system" fn Java_com_android_server_uwb_jni_NativeUwbManager_nativeGetCachedDeviceInfo(
+    env: JNIEnv,
+    obj: JObject,
+    chip_id: JString,
+) -> jobject {
+    debug!("{}: enter", function_name!());
+    match option_result_helper(native_get_cached_device_info(env, obj, chip_id), function_name!()) {
+        Some(Some(rsp)) => create_device_info_response(rsp, env)
+            .map_err(|e| {
+                error!("{} failed with {:?}", function_name!(), &e);
+                e
+            })
+            .unwrap_or(*JObject::null()),
+        _ => *JObject::null(),
+    }
+}
+
+fn native_get_cached_device_info(
+    env: JNIEnv,
+    obj: JObject,
+    chip_id: JString,
+) -> Result<Option<GetDeviceInfoResponse>> {
+    let uci_manager = Dispatcher::get_uci_manager(env, obj, chip_id)?;
+    let device_info = uci_manager.chip_state()?.cached_device_info();
+    Ok(device_info)
 }
 
 /// Turn off single UWB chip.
@@ -157,17 +285,39 @@ pub extern "system" fn Java_com_android_server_uwb_jni_NativeUwbManager_nativeDo
 
 fn native_do_deinitialize(env: JNIEnv, obj: JObject, chip_id: JString) -> Result<()> {
     let uci_manager = Dispatcher::get_uci_manager(env, obj, chip_id)?;
-    uci_manager.close_hal(true)
+    close_hal_with_state(&*uci_manager, |state| update_hal_state(&uci_manager, state))?;
+    // The sessions do not outlive the HAL.
+    uci_manager.chip_state()?.on_all_sessions_deinit();
+    Ok(())
 }
 
-/// Get nanos. Not currently used and returns placeholder value.
+/// Get the resolution, in ns, of the UWBS timestamps of the default UWB device. Return 0 if failed.
 #[no_mangle]
 pub extern "system" fn Java_com_android_server_uwb_jni_NativeUwbManager_nativeGetTimestampResolutionNanos(
-    _env: JNIEnv,
-    _obj: JObject,
+    env: JNIEnv,
+    obj: JObject,
 ) -> jlong {
     debug!("{}: enter", function_name!());
-    0
+    match option_result_helper(native_get_timestamp_resolution_nanos(env, obj), function_name!()) {
+        Some(resolution) => resolution.resolution_ns().into(),
+        None => 0,
+    }
+}
+
+fn native_get_timestamp_resolution_nanos(env: JNIEnv, obj: JObject) -> Result<TimestampResolution> {
+    let uci_manager = Dispatcher::get_default_uci_manager(env, obj)?;
+    uwbs_timestamp_resolution(&uci_manager)
+}
+
+/// Gets the resolution of the UWBS timestamps of the chip, measured on first use and cached.
+fn uwbs_timestamp_resolution(uci_manager: &GuardedUciManager) -> Result<TimestampResolution> {
+    let cached_resolution = uci_manager.chip_state()?.timestamp_resolution();
+    if let Some(resolution) = cached_resolution {
+        return Ok(resolution);
+    }
+    let resolution = query_timestamp_resolution(&**uci_manager, TIMESTAMP_CALIBRATION_INTERVAL)?;
+    uci_manager.chip_state()?.on_timestamp_resolution(resolution);
+    Ok(resolution)
 }
 
 /// Reset a single UWB device by sending UciDeviceReset command. Return value defined by
@@ -176,16 +326,35 @@ pub extern "system" fn Java_com_android_server_uwb_jni_NativeUwbManager_nativeGe
 pub extern "system" fn Java_com_android_server_uwb_jni_NativeUwbManager_nativeDeviceReset(
     env: JNIEnv,
     obj: JObject,
-    _reset_config: jbyte,
+    reset_config: jbyte,
     chip_id: JString,
 ) -> jbyte {
     debug!("{}: enter", function_name!());
-    byte_result_helper(native_device_reset(env, obj, chip_id), function_name!())
+    byte_result_helper(native_device_reset(env, obj, reset_config, chip_id), function_name!())
 }
 
-fn native_device_reset(env: JNIEnv, obj: JObject, chip_id: JString) -> Result<()> {
-    let uci_manager = Dispatcher::get_uci_manager(env, obj, chip_id)?;
-    uci_manager.device_reset(ResetConfig::UwbsReset)
+fn native_device_reset(
+    env: JNIEnv,
+    obj: JObject,
+    reset_config: jbyte,
+    chip_id: JString,
+) -> Result<()> {
+    let reset_config = parse_reset_config(reset_config)?;
+    Dispatcher::record_command(env, obj, chip_id, CORE_DEVICE_RESET, |uci_manager| {
+        uci_manager.device_reset(reset_config)
+    })?;
+    // The UWBS drops all its sessions on reset.
+    Dispatcher::get_uci_manager(env, obj, chip_id)?.chip_state()?.on_all_sessions_deinit();
+    Ok(())
+}
+
+/// Parses the reset configuration of the CORE_DEVICE_RESET command, as defined by
+/// uci_packets.pdl.
+fn parse_reset_config(reset_config: jbyte) -> Result<ResetConfig> {
+    ResetConfig::try_from(reset_config as u8).map_err(|_| {
+        error!("Unknown reset config {}", reset_config);
+        Error::BadParameters
+    })
 }
 
 /// Init the session on a single UWB device. Return value defined by uci_packets.pdl
@@ -213,8 +382,52 @@ fn native_session_init(
 ) -> Result<()> {
     let session_type =
         SessionType::try_from(session_type as u8).map_err(|_| Error::BadParameters)?;
+    Dispatcher::record_command(env, obj, chip_id, SESSION_INIT, |uci_manager| {
+        uci_manager.session_init(session_id as u32, session_type)
+    })?;
+    Dispatcher::get_uci_manager(env, obj, chip_id)?
+        .chip_state()?
+        .on_session_init(session_id as u32, u8::from(session_type));
+    Ok(())
+}
+
+/// Init the session on a single UWB device, and get the session token allocated to it, saving a
+/// call to nativeGetSessionToken. Return -1 if failed.
+#[no_mangle]
+pub extern "system" fn Java_com_android_server_uwb_jni_NativeUwbManager_nativeSessionInitAndGetToken(
+    env: JNIEnv,
+    obj: JObject,
+    session_id: jint,
+    session_type: jbyte,
+    chip_id: JString,
+) -> jlong {
+    debug!("{}: enter", function_name!());
+    match option_result_helper(
+        native_session_init_and_get_token(env, obj, session_id, session_type, chip_id),
+        function_name!(),
+    ) {
+        Some(session_token) => session_token.into(),
+        None => -1,
+    }
+}
+
+fn native_session_init_and_get_token(
+    env: JNIEnv,
+    obj: JObject,
+    session_id: jint,
+    session_type: jbyte,
+    chip_id: JString,
+) -> Result<u32> {
+    let session_type =
+        SessionType::try_from(session_type as u8).map_err(|_| Error::BadParameters)?;
+    let result = Dispatcher::record_command(env, obj, chip_id, SESSION_INIT, |uci_manager| {
+        uci_manager.session_init(session_id as u32, session_type)
+    });
     let uci_manager = Dispatcher::get_uci_manager(env, obj, chip_id)?;
-    uci_manager.session_init(session_id as u32, session_type)
+    if result.is_ok() {
+        uci_manager.chip_state()?.on_session_init(session_id as u32, u8::from(session_type));
+    }
+    session_token_after_init(&*uci_manager, session_id as u32, result)
 }
 
 /// DeInit the session on a single UWB device. Return value defined by uci_packets.pdl
@@ -235,8 +448,13 @@ fn native_session_deinit(
     session_id: jint,
     chip_id: JString,
 ) -> Result<()> {
-    let uci_manager = Dispatcher::get_uci_manager(env, obj, chip_id)?;
-    uci_manager.session_deinit(session_id as u32)
+    Dispatcher::record_command(env, obj, chip_id, SESSION_DEINIT, |uci_manager| {
+        uci_manager.session_deinit(session_id as u32)
+    })?;
+    Dispatcher::get_uci_manager(env, obj, chip_id)?
+        .chip_state()?
+        .on_session_deinit(session_id as u32);
+    Ok(())
 }
 
 /// Get session count on a single UWB device. return -1 if failed
@@ -277,8 +495,9 @@ fn native_ranging_start(
     session_id: jint,
     chip_id: JString,
 ) -> Result<()> {
-    let uci_manager = Dispatcher::get_uci_manager(env, obj, chip_id)?;
-    uci_manager.range_start(session_id as u32)
+    Dispatcher::record_command(env, obj, chip_id, SESSION_START, |uci_manager| {
+        uci_manager.range_start(session_id as u32)
+    })
 }
 
 /// Stop ranging on a single UWB device. Return value defined by uci_packets.pdl
@@ -299,8 +518,177 @@ fn native_ranging_stop(
     session_id: jint,
     chip_id: JString,
 ) -> Result<()> {
+    Dispatcher::record_command(env, obj, chip_id, SESSION_STOP, |uci_manager| {
+        uci_manager.range_stop(session_id as u32)
+    })
+}
+
+/// Start ranging on a single UWB device. If the command times out, the session state is polled
+/// until `timeout_ms` after the command was sent, for the session to become active. The call lasts
+/// at least the default command timeout, and a zero or negative timeout keeps it. Return value
+/// defined by uci_packets.pdl
+#[no_mangle]
+pub extern "system" fn Java_com_android_server_uwb_jni_NativeUwbManager_nativeRangingStartWithTimeout(
+    env: JNIEnv,
+    obj: JObject,
+    session_id: jint,
+    timeout_ms: jint,
+    chip_id: JString,
+) -> jbyte {
+    debug!("{}: enter", function_name!());
+    byte_result_helper(
+        native_ranging_start_with_timeout(env, obj, session_id, timeout_ms, chip_id),
+        function_name!(),
+    )
+}
+
+fn native_ranging_start_with_timeout(
+    env: JNIEnv,
+    obj: JObject,
+    session_id: jint,
+    timeout_ms: jint,
+    chip_id: JString,
+) -> Result<()> {
+    let start = Instant::now();
+    let result = Dispatcher::record_command(env, obj, chip_id, SESSION_START, |uci_manager| {
+        uci_manager.range_start(session_id as u32)
+    });
+    // The dispatcher is locked again for each poll, to let other calls through meanwhile.
+    session_transition_with_timeout(
+        start,
+        SessionState::SessionStateActive,
+        timeout_ms,
+        result,
+        || Dispatcher::get_uci_manager(env, obj, chip_id)?.session_get_state(session_id as u32),
+    )
+}
+
+/// Stop ranging on a single UWB device. If the command times out, the session state is polled
+/// until `timeout_ms` after the command was sent, for the session to become idle. The call lasts
+/// at least the default command timeout, and a zero or negative timeout keeps it. Return value
+/// defined by uci_packets.pdl
+#[no_mangle]
+pub extern "system" fn Java_com_android_server_uwb_jni_NativeUwbManager_nativeRangingStopWithTimeout(
+    env: JNIEnv,
+    obj: JObject,
+    session_id: jint,
+    timeout_ms: jint,
+    chip_id: JString,
+) -> jbyte {
+    debug!("{}: enter", function_name!());
+    byte_result_helper(
+        native_ranging_stop_with_timeout(env, obj, session_id, timeout_ms, chip_id),
+        function_name!(),
+    )
+}
+
+fn native_ranging_stop_with_timeout(
+    env: JNIEnv,
+    obj: JObject,
+    session_id: jint,
+    timeout_ms: jint,
+    chip_id: JString,
+) -> Result<()> {
+    let start = Instant::now();
+    let result = Dispatcher::record_command(env, obj, chip_id, SESSION_STOP, |uci_manager| {
+        uci_manager.range_stop(session_id as u32)
+    });
+    // The dispatcher is locked again for each poll, to let other calls through meanwhile.
+    session_transition_with_timeout(
+        start,
+        SessionState::SessionStateIdle,
+        timeout_ms,
+        result,
+        || Dispatcher::get_uci_manager(env, obj, chip_id)?.session_get_state(session_id as u32),
+    )
+}
+
+/// Get the states of all the sessions on a single UWB device, as an array of the ids of the
+/// sessions, in increasing order, followed by an array of their states. A session whose state can
+/// not be queried is left out. Return null if failed.
+#[no_mangle]
+pub extern "system" fn Java_com_android_server_uwb_jni_NativeUwbManager_nativeGetAllSessionStates(
+    env: JNIEnv,
+    obj: JObject,
+    chip_id: JString,
+) -> jobjectArray {
+    debug!("{}: enter", function_name!());
+    match option_result_helper(native_get_all_session_states(env, obj, chip_id), function_name!()) {
+        Some(session_states) => session_states,
+        None => *JObject::null(),
+    }
+}
+
+fn native_get_all_session_states(
+    env: JNIEnv,
+    obj: JObject,
+    chip_id: JString,
+) -> Result<jobjectArray> {
     let uci_manager = Dispatcher::get_uci_manager(env, obj, chip_id)?;
-    uci_manager.range_stop(session_id as u32)
+    let session_ids = uci_manager.chip_state()?.session_ids();
+    let (session_ids, session_states): (Vec<_>, Vec<_>) =
+        session_get_all_states(&*uci_manager, session_ids)
+            .into_iter()
+            .map(|(session_id, session_state)| {
+                (session_id as jint, u8::from(session_state) as jint)
+            })
+            .unzip();
+    create_int_array_pair(env, &session_ids, &session_states)
+}
+
+/// Creates an array of the two int arrays `first` and `second`.
+fn create_int_array_pair(env: JNIEnv, first: &[jint], second: &[jint]) -> Result<jobjectArray> {
+    let first = create_int_array(env, first)?;
+    let second = create_int_array(env, second)?;
+
+    // Safety: first is safely instantiated above.
+    let first = unsafe { JObject::from_raw(first) };
+    let pair_jobjectarray =
+        env.new_object_array(2, "[I", first).map_err(|_| Error::ForeignFunctionInterface)?;
+    // Safety: second is safely instantiated above.
+    let second = unsafe { JObject::from_raw(second) };
+    env.set_object_array_element(pair_jobjectarray, 1, second)
+        .map_err(|_| Error::ForeignFunctionInterface)?;
+    Ok(pair_jobjectarray)
+}
+
+/// Deinitialize all the sessions of a single UWB device in a single sweep, carrying on with the
+/// next sessions when one fails. Return an array of the ids of the sessions, in increasing order,
+/// followed by an array of the status of their deinitialization, defined by uci_packets.pdl. Both
+/// arrays are empty without active session. Return null if failed.
+#[no_mangle]
+pub extern "system" fn Java_com_android_server_uwb_jni_NativeUwbManager_nativeSessionDeInitAll(
+    env: JNIEnv,
+    obj: JObject,
+    chip_id: JString,
+) -> jobjectArray {
+    debug!("{}: enter", function_name!());
+    match option_result_helper(native_session_deinit_all(env, obj, chip_id), function_name!()) {
+        Some(results) => results,
+        None => *JObject::null(),
+    }
+}
+
+fn native_session_deinit_all(env: JNIEnv, obj: JObject, chip_id: JString) -> Result<jobjectArray> {
+    let session_ids = Dispatcher::get_uci_manager(env, obj, chip_id)?.chip_state()?.session_ids();
+    let results = session_deinit_all(session_ids, |session_id| {
+        Dispatcher::record_command(env, obj, chip_id, SESSION_DEINIT, |uci_manager| {
+            uci_manager.session_deinit(session_id)
+        })?;
+        Dispatcher::get_uci_manager(env, obj, chip_id)?.chip_state()?.on_session_deinit(session_id);
+        Ok(())
+    });
+    let (session_ids, statuses): (Vec<_>, Vec<_>) = results
+        .into_iter()
+        .map(|(session_id, result)| {
+            let status = match result {
+                Ok(()) => StatusCode::UciStatusOk,
+                Err(e) => error_to_status_code(&e),
+            };
+            (session_id as jint, u8::from(status) as jint)
+        })
+        .unzip();
+    create_int_array_pair(env, &session_ids, &statuses)
 }
 
 /// Get session stateon a single UWB device. Return -1 if failed
@@ -332,21 +720,45 @@ fn native_get_session_state(
     uci_manager.session_get_state(session_id as u32)
 }
 
-fn parse_app_config_tlv_vec(no_of_params: i32, mut byte_array: &[u8]) -> Result<Vec<AppConfigTlv>> {
-    let mut parsed_tlvs_len = 0;
-    let received_tlvs_len = byte_array.len();
+/// Location of a TLV which could not be parsed: its index in the TLVs and the offset of its first
+/// byte. Unexpected bytes after the TLVs are reported at the index following the last TLV.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+struct TlvParseError {
+    index: usize,
+    offset: usize,
+}
+
+impl From<TlvParseError> for Error {
+    fn from(e: TlvParseError) -> Self {
+        error!("UCI JNI: failed to parse TLV {} at offset {}", e.index, e.offset);
+        Error::BadParameters
+    }
+}
+
+// The tlv consists of the type of payload in 1 byte, the length of payload as u8 in 1 byte, and
+// the payload.
+const TLV_HEADER_SIZE: usize = 2;
+
+/// Parses `no_of_params` app configuration TLVs. Up to `max_zero_padding` zero bytes, which some
+/// vendor HALs append to align the TLVs, are allowed after them; any other trailing byte is
+/// rejected.
+fn parse_app_config_tlv_vec(
+    no_of_params: i32,
+    mut byte_array: &[u8],
+    max_zero_padding: usize,
+) -> std::result::Result<Vec<AppConfigTlv>, TlvParseError> {
+    let mut offset = 0;
     let mut tlvs = Vec::<AppConfigTlv>::new();
-    for _ in 0..no_of_params {
-        // The tlv consists of the type of payload in 1 byte, the length of payload as u8
-        // in 1 byte, and the payload.
-        const TLV_HEADER_SIZE: usize = 2;
-        let tlv = RawAppConfigTlv::parse(byte_array).map_err(|_| Error::BadParameters)?;
-        byte_array = byte_array.get(tlv.v.len() + TLV_HEADER_SIZE..).ok_or(Error::BadParameters)?;
-        parsed_tlvs_len += tlv.v.len() + TLV_HEADER_SIZE;
+    for index in 0..no_of_params.max(0) as usize {
+        let parse_error = TlvParseError { index, offset };
+        let tlv = RawAppConfigTlv::parse(byte_array).map_err(|_| parse_error)?;
+        let tlv_len = tlv.v.len() + TLV_HEADER_SIZE;
+        byte_array = byte_array.get(tlv_len..).ok_or(parse_error)?;
+        offset += tlv_len;
         tlvs.push(tlv.into());
     }
-    if parsed_tlvs_len != received_tlvs_len {
-        return Err(Error::BadParameters);
+    if byte_array.len() > max_zero_padding || byte_array.iter().any(|byte| *byte != 0) {
+        return Err(TlvParseError { index: tlvs.len(), offset });
     };
     Ok(tlvs)
 }
@@ -354,21 +766,19 @@ fn parse_app_config_tlv_vec(no_of_params: i32, mut byte_array: &[u8]) -> Result<
 fn parse_radar_config_tlv_vec(
     no_of_params: i32,
     mut byte_array: &[u8],
-) -> Result<Vec<RadarConfigTlv>> {
-    let mut parsed_tlvs_len = 0;
-    let received_tlvs_len = byte_array.len();
+) -> std::result::Result<Vec<RadarConfigTlv>, TlvParseError> {
+    let mut offset = 0;
     let mut tlvs = Vec::<RadarConfigTlv>::new();
-    for _ in 0..no_of_params {
-        // The tlv consists of the type of payload in 1 byte, the length of payload as u8
-        // in 1 byte, and the payload.
-        const TLV_HEADER_SIZE: usize = 2;
-        let tlv = RadarConfigTlv::parse(byte_array).map_err(|_| Error::BadParameters)?;
-        byte_array = byte_array.get(tlv.v.len() + TLV_HEADER_SIZE..).ok_or(Error::BadParameters)?;
-        parsed_tlvs_len += tlv.v.len() + TLV_HEADER_SIZE;
+    for index in 0..no_of_params.max(0) as usize {
+        let parse_error = TlvParseError { index, offset };
+        let tlv = RadarConfigTlv::parse(byte_array).map_err(|_| parse_error)?;
+        let tlv_len = tlv.v.len() + TLV_HEADER_SIZE;
+        byte_array = byte_array.get(tlv_len..).ok_or(parse_error)?;
+        offset += tlv_len;
         tlvs.push(tlv);
     }
-    if parsed_tlvs_len != received_tlvs_len {
-        return Err(Error::BadParameters);
+    if !byte_array.is_empty() {
+        return Err(TlvParseError { index: tlvs.len(), offset });
     };
     Ok(tlvs)
 }
@@ -471,11 +881,174 @@ fn native_set_app_configurations(
     app_config_params: jbyteArray,
     chip_id: JString,
 ) -> Result<SetAppConfigResponse> {
+    let config_byte_array =
+        env.convert_byte_array(app_config_params).map_err(|_| Error::ForeignFunctionInterface)?;
+    let max_zero_padding =
+        Dispatcher::get_uci_manager(env, obj, chip_id)?.chip_state()?.app_config_max_zero_padding();
+    let tlvs = parse_app_config_tlv_vec(no_of_params, &config_byte_array, max_zero_padding)?;
+    Dispatcher::record_command(env, obj, chip_id, SESSION_SET_APP_CONFIG, |uci_manager| {
+        let response = uci_manager.session_set_app_config(session_id as u32, tlvs.clone())?;
+        on_app_config_set(uci_manager, session_id as u32, &tlvs, response.status)?;
+        Ok(response)
+    })
+}
+
+/// Updates the MAC_ADDRESS_MODE of the session cached by the chip state if set by `tlvs`, dropping
+/// it if the UWBS rejected the configuration with `status`.
+fn on_app_config_set(
+    uci_manager: &GuardedUciManager,
+    session_id: u32,
+    tlvs: &[AppConfigTlv],
+    status: StatusCode,
+) -> Result<()> {
+    let cfg_id = fira_cfg_id(MAC_ADDRESS_MODE)?;
+    if tlvs.iter().any(|tlv| tlv.cfg_id == cfg_id) {
+        let mode = find_mac_address_mode(tlvs).filter(|_| status == StatusCode::UciStatusOk);
+        uci_manager.chip_state()?.on_mac_address_mode_set(session_id, mode);
+    }
+    Ok(())
+}
+
+/// Set the number of zero bytes allowed after the TLVs given to nativeSetAppConfigurations and
+/// nativeSetAppConfigurationsBatch, for the vendor HALs which pad them. None are allowed by
+/// default. Return value defined by uci_packets.pdl
+#[no_mangle]
+pub extern "system" fn Java_com_android_server_uwb_jni_NativeUwbManager_nativeSetAppConfigMaxZeroPadding(
+    env: JNIEnv,
+    obj: JObject,
+    max_zero_padding: jint,
+    chip_id: JString,
+) -> jbyte {
+    debug!("{}: enter", function_name!());
+    byte_result_helper(
+        native_set_app_config_max_zero_padding(env, obj, max_zero_padding, chip_id),
+        function_name!(),
+    )
+}
+
+fn native_set_app_config_max_zero_padding(
+    env: JNIEnv,
+    obj: JObject,
+    max_zero_padding: jint,
+    chip_id: JString,
+) -> Result<()> {
+    let max_zero_padding = usize::try_from(max_zero_padding).map_err(|_| Error::BadParameters)?;
     let uci_manager = Dispatcher::get_uci_manager(env, obj, chip_id)?;
+    uci_manager.chip_state()?.set_app_config_max_zero_padding(max_zero_padding);
+    Ok(())
+}
+
+/// Validate app configurations without sending them to the UWB device: the TLVs are parsed, and the
+/// size of the value of each FiRa app configuration is checked. Return value defined by
+/// uci_packets.pdl
+#[no_mangle]
+pub extern "system" fn Java_com_android_server_uwb_jni_NativeUwbManager_nativeValidateAppConfig(
+    env: JNIEnv,
+    _obj: JObject,
+    no_of_params: jint,
+    app_config_params: jbyteArray,
+) -> jbyte {
+    debug!("{}: enter", function_name!());
+    byte_result_helper(
+        native_validate_app_config(env, no_of_params, app_config_params),
+        function_name!(),
+    )
+}
+
+fn native_validate_app_config(
+    env: JNIEnv,
+    no_of_params: jint,
+    app_config_params: jbyteArray,
+) -> Result<()> {
     let config_byte_array =
         env.convert_byte_array(app_config_params).map_err(|_| Error::ForeignFunctionInterface)?;
-    let tlvs = parse_app_config_tlv_vec(no_of_params, &config_byte_array)?;
-    uci_manager.session_set_app_config(session_id as u32, tlvs)
+    validate_app_config_bytes(no_of_params, &config_byte_array)
+}
+
+fn validate_app_config_bytes(no_of_params: i32, byte_array: &[u8]) -> Result<()> {
+    validate_app_config_tlvs(parse_app_config_tlv_vec(no_of_params, byte_array, 0)?)
+}
+
+/// Set app configurations on several sessions of a single UWB device at once, from the parallel
+/// arrays of the session ids, of their number of TLVs and of their TLVs. A failing session does not
+/// prevent the configuration of the next ones. Return an array of the config status of each
+/// session, or null JObject if failed.
+#[no_mangle]
+pub extern "system" fn Java_com_android_server_uwb_jni_NativeUwbManager_nativeSetAppConfigurationsBatch(
+    env: JNIEnv,
+    obj: JObject,
+    session_ids: jintArray,
+    no_of_params: jintArray,
+    app_config_params: jobjectArray,
+    chip_id: JString,
+) -> jobjectArray {
+    debug!("{}: enter", function_name!());
+    match option_result_helper(
+        native_set_app_configurations_batch(
+            env,
+            obj,
+            session_ids,
+            no_of_params,
+            app_config_params,
+            chip_id,
+        ),
+        function_name!(),
+    ) {
+        Some(config_responses) => config_responses,
+        None => *JObject::null(),
+    }
+}
+
+fn native_set_app_configurations_batch(
+    env: JNIEnv,
+    obj: JObject,
+    session_ids: jintArray,
+    no_of_params: jintArray,
+    app_config_params: jobjectArray,
+    chip_id: JString,
+) -> Result<jobjectArray> {
+    let session_ids = get_int_array(env, session_ids)?;
+    let no_of_params = get_int_array(env, no_of_params)?;
+    let configs_len =
+        env.get_array_length(app_config_params).map_err(|_| Error::ForeignFunctionInterface)?;
+    if session_ids.len() != no_of_params.len() || session_ids.len() != configs_len as usize {
+        return Err(Error::BadParameters);
+    }
+    let max_zero_padding =
+        Dispatcher::get_uci_manager(env, obj, chip_id)?.chip_state()?.app_config_max_zero_padding();
+    let configs = zip(session_ids, no_of_params)
+        .enumerate()
+        .map(|(i, (session_id, no_of_params))| {
+            let tlvs = env
+                .get_object_array_element(app_config_params, i as i32)
+                .and_then(|config_params| env.convert_byte_array(*config_params))
+                .map_err(|_| Error::ForeignFunctionInterface)
+                .and_then(|config_byte_array| {
+                    parse_app_config_tlv_vec(no_of_params, &config_byte_array, max_zero_padding)
+                        .map_err(Error::from)
+                });
+            (session_id as u32, tlvs)
+        })
+        .collect();
+    let responses = set_app_configs_batch(configs, |session_id, tlvs| {
+        Dispatcher::record_command(env, obj, chip_id, SESSION_SET_APP_CONFIG, |uci_manager| {
+            let response = uci_manager.session_set_app_config(session_id, tlvs.clone())?;
+            on_app_config_set(uci_manager, session_id, &tlvs, response.status)?;
+            Ok(response)
+        })
+    });
+
+    let config_responses = env
+        .new_object_array(responses.len() as i32, CONFIG_STATUS_DATA_CLASS, JObject::null())
+        .map_err(|_| Error::ForeignFunctionInterface)?;
+    for (i, response) in responses.into_iter().enumerate() {
+        let config_response = create_set_config_response(response, env)?;
+        // Safety: config_response is safely instantiated above.
+        let config_response = unsafe { JObject::from_raw(config_response) };
+        env.set_object_array_element(config_responses, i as i32, config_response)
+            .map_err(|_| Error::ForeignFunctionInterface)?;
+    }
+    Ok(config_responses)
 }
 
 /// Set radar app configurations on a single UWB device. Return null JObject if failed.
@@ -526,16 +1099,130 @@ fn native_set_radar_app_configurations(
     uci_manager.android_set_radar_config(session_id as u32, tlvs)
 }
 
-fn parse_hybrid_config_phase_list_vec(
-    number_of_phases: usize,
-    byte_array: &[u8],
-) -> Result<Vec<PhaseList>> {
-    let mut parsed_phase_lists_len = 0;
-    let received_phase_list_len = byte_array.len();
-    let mut phase_lists = Vec::with_capacity(number_of_phases);
-    // The PhaseList consists of session handle as u32 in 4 bytes, Start Slot Index as u16
-    // in 2 byte and End Slot Index as u16 in 2 bytes
-    const PHASE_LIST_SIZE: usize = 8;
+fn create_get_radar_config_response(tlvs: Vec<RadarConfigTlv>, env: JNIEnv) -> Result<jbyteArray> {
+    let tlv_data_class =
+        env.find_class(TLV_DATA_CLASS).map_err(|_| Error::ForeignFunctionInterface)?;
+    let tlvs_len = tlvs.len();
+    let mut buf = Vec::<u8>::new();
+    for tlv in tlvs.into_iter() {
+        buf.push(u8::from(tlv.cfg_id));
+        buf.push(tlv.v.len() as u8);
+        buf.extend(&tlv.v);
+    }
+    let tlvs_jbytearray =
+        env.byte_array_from_slice(&buf).map_err(|_| Error::ForeignFunctionInterface)?;
+
+    // Safety: tlvs_jbytearray is safely instantiated above.
+    let tlvs_jobject = unsafe { JObject::from_raw(tlvs_jbytearray) };
+    let tlvs_jobject_env = env
+        .new_object(
+            tlv_data_class,
+            "(II[B)V",
+            &[
+                JValue::Int(i32::from(StatusCode::UciStatusOk)),
+                JValue::Int(tlvs_len as i32),
+                JValue::Object(tlvs_jobject),
+            ],
+        )
+        .map_err(|_| Error::ForeignFunctionInterface)?;
+    Ok(*tlvs_jobject_env)
+}
+
+/// Get radar app configurations on a single UWB device, as read back from the UWBS. Return null
+/// JObject if failed.
+#[no_mangle]
+pub extern "system" fn Java_com_android_server_uwb_jni_NativeUwbManager_nativeGetRadarAppConfigurations(
+    env: JNIEnv,
+    obj: JObject,
+    session_id: jint,
+    radar_config_params: jbyteArray,
+    chip_id: JString,
+) -> jbyteArray {
+    debug!("{}: enter", function_name!());
+    match option_result_helper(
+        native_get_radar_app_configurations(env, obj, session_id, radar_config_params, chip_id),
+        function_name!(),
+    ) {
+        Some(v) => create_get_radar_config_response(v, env)
+            .map_err(|e| {
+                error!("{} failed with {:?}", function_name!(), &e);
+                e
+            })
+            .unwrap_or(*JObject::null()),
+        None => *JObject::null(),
+    }
+}
+
+fn native_get_radar_app_configurations(
+    env: JNIEnv,
+    obj: JObject,
+    session_id: jint,
+    radar_config_params: jbyteArray,
+    chip_id: JString,
+) -> Result<Vec<RadarConfigTlv>> {
+    let uci_manager = Dispatcher::get_uci_manager(env, obj, chip_id)?;
+    let radar_config_bytearray =
+        env.convert_byte_array(radar_config_params).map_err(|_| Error::ForeignFunctionInterface)?;
+    let cfg_ids = radar_config_bytearray
+        .into_iter()
+        .map(RadarConfigTlvType::try_from)
+        .map(std::result::Result::ok)
+        .collect::<Option<Vec<_>>>()
+        .ok_or(Error::BadParameters)?;
+    android_get_radar_config(&*uci_manager, session_id as u32, &cfg_ids)
+}
+
+// ANDROID_GET_RADAR_CONFIG command of the Android UCI extensions.
+const UCI_MT_COMMAND: u32 = 0x01;
+const UCI_GID_ANDROID: u32 = 0x0C;
+const ANDROID_GET_RADAR_CONFIG_OID: u32 = 0x12;
+
+/// Reads back the radar configurations `cfg_ids` of the session from the UWBS, with the
+/// ANDROID_GET_RADAR_CONFIG command sent as a raw UCI command since UciManager doesn't provide it.
+/// Fails if the UWBS rejects the command, or if it doesn't return the requested configurations, in
+/// the requested order.
+fn android_get_radar_config<U: UciManager>(
+    uci_manager: &UciManagerSync<U>,
+    session_id: u32,
+    cfg_ids: &[RadarConfigTlvType],
+) -> Result<Vec<RadarConfigTlv>> {
+    let mut payload = uci_manager.get_session_token(session_id)?.to_le_bytes().to_vec();
+    payload.push(u8::try_from(cfg_ids.len()).map_err(|_| Error::BadParameters)?);
+    payload.extend(cfg_ids.iter().map(|cfg_id| u8::from(*cfg_id)));
+    let response = uci_manager.raw_uci_cmd(
+        UCI_MT_COMMAND,
+        UCI_GID_ANDROID,
+        ANDROID_GET_RADAR_CONFIG_OID,
+        payload,
+    )?;
+
+    // The response carries the status, the number of TLVs and the TLVs.
+    let (status, no_of_params, tlvs) = match &response.payload[..] {
+        [status, no_of_params, tlvs @ ..] => (*status, *no_of_params, tlvs),
+        _ => return Err(Error::Unknown),
+    };
+    if status != u8::from(StatusCode::UciStatusOk) {
+        error!("UCI JNI: get radar config rejected with status {:#x}", status);
+        return Err(Error::Unknown);
+    }
+    let tlvs = parse_radar_config_tlv_vec(no_of_params.into(), tlvs)?;
+    if !tlvs.iter().map(|tlv| tlv.cfg_id).eq(cfg_ids.iter().copied()) {
+        error!("UCI JNI: get radar config returned {:?} for {:?}", tlvs, cfg_ids);
+        return Err(Error::Unknown);
+    }
+    Ok(tlvs)
+}
+
+fn parse_hybrid_config_phase_list_vec(
+    number_of_phases: usize,
+    byte_array: &[u8],
+) -> Result<Vec<PhaseList>> {
+    let mut parsed_phase_lists_len = 0;
+    let received_phase_list_len = byte_array.len();
+    let mut phase_lists = Vec::with_capacity(number_of_phases);
+    // The PhaseList consists of session handle as u32 in 4 bytes, Start Slot Index as u16
+    // in 2 byte and End Slot Index as u16 in 2 bytes
+    const PHASE_LIST_SIZE: usize = 8;
     for chunk in byte_array.chunks_exact(PHASE_LIST_SIZE) {
         let phase_list = PhaseList::parse(chunk).map_err(|_| Error::BadParameters)?;
         parsed_phase_lists_len += PHASE_LIST_SIZE;
@@ -665,32 +1352,52 @@ fn native_get_app_configurations(
     app_config_params: jbyteArray,
     chip_id: JString,
 ) -> Result<Vec<AppConfigTlv>> {
-    let uci_manager = Dispatcher::get_uci_manager(env, obj, chip_id)
-        .map_err(|_| Error::ForeignFunctionInterface)?;
     let app_config_bytearray =
         env.convert_byte_array(app_config_params).map_err(|_| Error::ForeignFunctionInterface)?;
-    uci_manager.session_get_app_config(
-        session_id as u32,
-        app_config_bytearray
-            .into_iter()
-            .map(AppConfigTlvType::try_from)
-            .map(std::result::Result::ok)
-            .collect::<Option<Vec<_>>>()
-            .ok_or(Error::BadParameters)?,
-    )
+    let cfg_ids = app_config_bytearray
+        .into_iter()
+        .map(AppConfigTlvType::try_from)
+        .map(std::result::Result::ok)
+        .collect::<Option<Vec<_>>>()
+        .ok_or(Error::BadParameters)?;
+    Dispatcher::record_command(env, obj, chip_id, SESSION_GET_APP_CONFIG, |uci_manager| {
+        uci_manager.session_get_app_config(session_id as u32, cfg_ids.clone())
+    })
 }
 
-fn create_cap_response(tlvs: Vec<CapTlv>, env: JNIEnv) -> Result<jbyteArray> {
-    let tlv_data_class =
-        env.find_class(TLV_DATA_CLASS).map_err(|_| Error::ForeignFunctionInterface)?;
+fn get_int_array(env: JNIEnv, values: jintArray) -> Result<Vec<jint>> {
+    let len = env.get_array_length(values).map_err(|_| Error::ForeignFunctionInterface)?;
+    let mut values_vec = vec![0; len.try_into().map_err(|_| Error::BadParameters)?];
+    env.get_int_array_region(values, 0, &mut values_vec)
+        .map_err(|_| Error::ForeignFunctionInterface)?;
+    Ok(values_vec)
+}
+
+fn create_int_array(env: JNIEnv, values: &[jint]) -> Result<jintArray> {
+    let values_jintarray =
+        env.new_int_array(values.len() as i32).map_err(|_| Error::ForeignFunctionInterface)?;
+    env.set_int_array_region(values_jintarray, 0, values)
+        .map_err(|_| Error::ForeignFunctionInterface)?;
+    Ok(values_jintarray)
+}
+
+/// Flattens the capability TLVs back into their UCI encoding.
+fn encode_cap_tlvs(tlvs: &[CapTlv]) -> Vec<u8> {
     let mut buf = Vec::<u8>::new();
-    for tlv in &tlvs {
+    for tlv in tlvs {
         buf.push(u8::from(tlv.t));
         buf.push(tlv.v.len() as u8);
         buf.extend(&tlv.v);
     }
-    let tlvs_jbytearray =
-        env.byte_array_from_slice(&buf).map_err(|_| Error::ForeignFunctionInterface)?;
+    buf
+}
+
+fn create_cap_response(tlvs: Vec<CapTlv>, env: JNIEnv) -> Result<jbyteArray> {
+    let tlv_data_class =
+        env.find_class(TLV_DATA_CLASS).map_err(|_| Error::ForeignFunctionInterface)?;
+    let tlvs_jbytearray = env
+        .byte_array_from_slice(&encode_cap_tlvs(&tlvs))
+        .map_err(|_| Error::ForeignFunctionInterface)?;
 
     // Safety: tlvs_jbytearray is safely instantiated above.
     let tlvs_jobject = unsafe { JObject::from_raw(tlvs_jbytearray) };
@@ -728,8 +1435,71 @@ pub extern "system" fn Java_com_android_server_uwb_jni_NativeUwbManager_nativeGe
 }
 
 fn native_get_caps_info(env: JNIEnv, obj: JObject, chip_id: JString) -> Result<Vec<CapTlv>> {
-    let uci_manager = Dispatcher::get_uci_manager(env, obj, chip_id)?;
-    uci_manager.core_get_caps_info()
+    Dispatcher::record_command(env, obj, chip_id, CORE_GET_CAPS_INFO, |uci_manager| {
+        uci_manager.core_get_caps_info()
+    })
+}
+
+// Lengths of the short and extended MAC addresses of the controlees.
+const SHORT_ADDRESS_LEN: usize = 2;
+const EXTENDED_ADDRESS_LEN: usize = 8;
+
+/// Returns the length of the MAC addresses of a session in the MAC_ADDRESS_MODE `mode`.
+fn mac_address_len(mode: u32) -> Result<usize> {
+    match u8::try_from(mode) {
+        Ok(MAC_ADDRESS_MODE_SHORT) => Ok(SHORT_ADDRESS_LEN),
+        Ok(MAC_ADDRESS_MODE_EXTENDED_SHORT_HEADER | MAC_ADDRESS_MODE_EXTENDED) => {
+            Ok(EXTENDED_ADDRESS_LEN)
+        }
+        _ => Err(Error::BadParameters),
+    }
+}
+
+/// Gets the MAC_ADDRESS_MODE of the session: the `cached` one if known, and the one queried from
+/// the UWBS otherwise.
+fn session_mac_address_mode<U: UciManager>(
+    uci_manager: &UciManagerSync<U>,
+    session_id: u32,
+    cached: Option<u8>,
+) -> Result<u8> {
+    if let Some(mode) = cached {
+        return Ok(mode);
+    }
+    let mode =
+        session_get_app_config_value(uci_manager, session_id, fira_cfg_id(MAC_ADDRESS_MODE)?)?;
+    u8::try_from(decode_le_value(&mode)?).map_err(|_| Error::BadParameters)
+}
+
+/// Parses the MAC addresses of the controlees for the multicast list update, of `address_len` bytes
+/// each as set by the MAC_ADDRESS_MODE of the session. The update only carries short addresses, so
+/// an extended address must hold a short address, in its 2 LSB with the 6 MSB zeroed out, and is
+/// rejected otherwise rather than truncated. Fails if the addresses do not add up to exactly
+/// `no_of_controlee` addresses of this length, as when short and extended addresses are mixed.
+fn parse_controlee_addresses(
+    addresses_bytes: &[u8],
+    no_of_controlee: usize,
+    address_len: usize,
+) -> Result<Vec<[u8; SHORT_ADDRESS_LEN]>> {
+    if addresses_bytes.len() != no_of_controlee * address_len {
+        error!(
+            "UCI JNI: {} bytes of addresses for {} controlees of {} bytes addresses",
+            addresses_bytes.len(),
+            no_of_controlee,
+            address_len
+        );
+        return Err(Error::BadParameters);
+    }
+    addresses_bytes
+        .chunks_exact(address_len)
+        .map(|address| {
+            let (short_address, msb) = address.split_at(SHORT_ADDRESS_LEN);
+            if msb.iter().any(|b| *b != 0) {
+                error!("UCI JNI: extended address {:02x?} has no short address", address);
+                return Err(Error::BadParameters);
+            }
+            Ok([short_address[0], short_address[1]])
+        })
+        .collect()
 }
 
 /// Update multicast list on a single UWB device. Return value defined by uci_packets.pdl
@@ -775,31 +1545,22 @@ fn native_controller_multicast_list_update(
     sub_session_keys: jbyteArray,
     chip_id: JString,
 ) -> Result<()> {
+    let action =
+        UpdateMulticastListAction::try_from(action as u8).map_err(|_| Error::BadParameters)?;
     let uci_manager = Dispatcher::get_uci_manager(env, obj, chip_id)?;
 
-    let addresses_bytes =
-        env.convert_byte_array(addresses).map_err(|_| Error::ForeignFunctionInterface)?;
-
-    let address_list: Vec<[u8; 2]> =
-        addresses_bytes.chunks_exact(2).map(|chunk| [chunk[0], chunk[1]]).collect();
-
-    let mut sub_session_id_list = vec![
-        0i32;
-        env.get_array_length(sub_session_ids)
-            .map_err(|_| Error::ForeignFunctionInterface)?
-            .try_into()
-            .map_err(|_| Error::BadParameters)?
-    ];
-    env.get_int_array_region(sub_session_ids, 0, &mut sub_session_id_list)
-        .map_err(|_| Error::ForeignFunctionInterface)?;
-    if address_list.len() != sub_session_id_list.len()
-        || address_list.len() != no_of_controlee as usize
-    {
-        return Err(Error::BadParameters);
-    }
-    let controlee_list = match UpdateMulticastListAction::try_from(action as u8)
-        .map_err(|_| Error::BadParameters)?
-    {
+    let (address_list, sub_session_id_list) = parse_controlee_list(
+        env,
+        &uci_manager,
+        session_id,
+        no_of_controlee,
+        addresses,
+        sub_session_ids,
+    )?;
+    let updated_controlees: Vec<Controlee> = zip(&address_list, &sub_session_id_list)
+        .map(|(a, s)| Controlee { short_address: *a, subsession_id: *s as u32 })
+        .collect();
+    let controlee_list = match action {
         UpdateMulticastListAction::AddControlee | UpdateMulticastListAction::RemoveControlee => {
             Controlees::NoSessionKey(
                 zip(address_list, sub_session_id_list)
@@ -862,11 +1623,155 @@ fn native_controller_multicast_list_update(
     };
     uci_manager.session_update_controller_multicast_list(
         session_id as u32,
-        UpdateMulticastListAction::try_from(action as u8).map_err(|_| Error::BadParameters)?,
+        action,
         controlee_list,
+    )?;
+    uci_manager.chip_state()?.on_multicast_list_updated(
+        session_id as u32,
+        action,
+        &updated_controlees,
+    );
+    Ok(())
+}
+
+/// Parses the short addresses and the sub-session ids of the controlees of a multicast list
+/// update, the addresses being as long as set by the MAC_ADDRESS_MODE of the session.
+fn parse_controlee_list(
+    env: JNIEnv,
+    uci_manager: &GuardedUciManager,
+    session_id: jint,
+    no_of_controlee: jbyte,
+    addresses: jbyteArray,
+    sub_session_ids: jintArray,
+) -> Result<(Vec<[u8; SHORT_ADDRESS_LEN]>, Vec<i32>)> {
+    let cached_mode = uci_manager.chip_state()?.mac_address_mode(session_id as u32);
+    let mode = session_mac_address_mode(&**uci_manager, session_id as u32, cached_mode)?;
+    uci_manager.chip_state()?.on_mac_address_mode_set(session_id as u32, Some(mode));
+    let addresses_bytes =
+        env.convert_byte_array(addresses).map_err(|_| Error::ForeignFunctionInterface)?;
+    let address_list = parse_controlee_addresses(
+        &addresses_bytes,
+        no_of_controlee as usize,
+        mac_address_len(mode.into())?,
+    )?;
+
+    let mut sub_session_id_list = vec![
+        0i32;
+        env.get_array_length(sub_session_ids)
+            .map_err(|_| Error::ForeignFunctionInterface)?
+            .try_into()
+            .map_err(|_| Error::BadParameters)?
+    ];
+    env.get_int_array_region(sub_session_ids, 0, &mut sub_session_id_list)
+        .map_err(|_| Error::ForeignFunctionInterface)?;
+    if address_list.len() != sub_session_id_list.len()
+        || address_list.len() != no_of_controlee as usize
+    {
+        return Err(Error::BadParameters);
+    }
+    Ok((address_list, sub_session_id_list))
+}
+
+/// Add controlees to the multicast list of a session, only sending the ones not yet in the list.
+/// Return value defined by uci_packets.pdl
+#[no_mangle]
+pub extern "system" fn Java_com_android_server_uwb_jni_NativeUwbManager_nativeAddControlees(
+    env: JNIEnv,
+    obj: JObject,
+    session_id: jint,
+    no_of_controlee: jbyte,
+    addresses: jbyteArray,
+    sub_session_ids: jintArray,
+    chip_id: JString,
+) -> jbyte {
+    debug!("{}: enter", function_name!());
+    byte_result_helper(
+        native_update_controlees(
+            env,
+            obj,
+            session_id,
+            UpdateMulticastListAction::AddControlee,
+            no_of_controlee,
+            addresses,
+            sub_session_ids,
+            chip_id,
+        ),
+        function_name!(),
+    )
+}
+
+/// Remove controlees from the multicast list of a session, only sending the ones in the list. The
+/// other controlees are ignored. Return value defined by uci_packets.pdl
+#[no_mangle]
+pub extern "system" fn Java_com_android_server_uwb_jni_NativeUwbManager_nativeRemoveControlees(
+    env: JNIEnv,
+    obj: JObject,
+    session_id: jint,
+    no_of_controlee: jbyte,
+    addresses: jbyteArray,
+    sub_session_ids: jintArray,
+    chip_id: JString,
+) -> jbyte {
+    debug!("{}: enter", function_name!());
+    byte_result_helper(
+        native_update_controlees(
+            env,
+            obj,
+            session_id,
+            UpdateMulticastListAction::RemoveControlee,
+            no_of_controlee,
+            addresses,
+            sub_session_ids,
+            chip_id,
+        ),
+        function_name!(),
     )
 }
 
+// Function is used only twice that copies arguments from JNI
+#[allow(clippy::too_many_arguments)]
+fn native_update_controlees(
+    env: JNIEnv,
+    obj: JObject,
+    session_id: jint,
+    action: UpdateMulticastListAction,
+    no_of_controlee: jbyte,
+    addresses: jbyteArray,
+    sub_session_ids: jintArray,
+    chip_id: JString,
+) -> Result<()> {
+    let uci_manager = Dispatcher::get_uci_manager(env, obj, chip_id)?;
+    let (address_list, sub_session_id_list) = parse_controlee_list(
+        env,
+        &uci_manager,
+        session_id,
+        no_of_controlee,
+        addresses,
+        sub_session_ids,
+    )?;
+    let controlees: Vec<Controlee> = zip(address_list, sub_session_id_list)
+        .map(|(a, s)| Controlee { short_address: a, subsession_id: s as u32 })
+        .collect();
+    let session_id = session_id as u32;
+    let controlees = match action {
+        UpdateMulticastListAction::RemoveControlee => {
+            uci_manager.chip_state()?.controlees_to_remove(session_id, controlees)
+        }
+        _ => uci_manager.chip_state()?.controlees_to_add(session_id, controlees),
+    };
+    if controlees.is_empty() {
+        debug!("UCI JNI: multicast list of session {} already up to date", session_id);
+        return Ok(());
+    }
+    uci_manager.session_update_controller_multicast_list(
+        session_id,
+        action,
+        Controlees::NoSessionKey(controlees.clone()),
+    )?;
+    uci_manager.chip_state()?.on_multicast_list_updated(session_id, action, &controlees);
+    Ok(())
+}
+
 /// Set country code on a single UWB device. Return value defined by uci_packets.pdl
 #[no_mangle]
 pub extern "system" fn Java_com_android_server_uwb_jni_NativeUwbManager_nativeSetCountryCode(
@@ -886,36 +1791,172 @@ fn native_set_country_code(
     chip_id: JString,
 ) -> Result<()> {
     let uci_manager = Dispatcher::get_uci_manager(env, obj, chip_id)?;
+    uci_manager.android_set_country_code(convert_country_code(env, country_code)?)
+}
+
+fn convert_country_code(env: JNIEnv, country_code: jbyteArray) -> Result<CountryCode> {
     let country_code =
         env.convert_byte_array(country_code).map_err(|_| Error::ForeignFunctionInterface)?;
     debug!("Country code: {:?}", country_code);
     if country_code.len() != 2 {
         return Err(Error::BadParameters);
     }
-    uci_manager.android_set_country_code(
-        CountryCode::new(&[country_code[0], country_code[1]]).ok_or(Error::BadParameters)?,
-    )
+    CountryCode::new(&[country_code[0], country_code[1]]).ok_or(Error::BadParameters)
 }
 
-/// Set log mode.
+/// Set country code on all the UWB devices, carrying on with the next devices when one fails, so
+/// that the regulatory update reaches as many devices as possible. Return the ids of the chips
+/// which failed, empty if all succeeded, or null if the country code is invalid or if failed.
 #[no_mangle]
-pub extern "system" fn Java_com_android_server_uwb_jni_NativeUwbManager_nativeSetLogMode(
+pub extern "system" fn Java_com_android_server_uwb_jni_NativeUwbManager_nativeSetCountryCodeAllChips(
     env: JNIEnv,
     obj: JObject,
-    log_mode_jstring: JString,
-) -> jboolean {
+    country_code: jbyteArray,
+) -> jobjectArray {
     debug!("{}: enter", function_name!());
-    boolean_result_helper(native_set_log_mode(env, obj, log_mode_jstring), function_name!())
+    match option_result_helper(
+        native_set_country_code_all_chips(env, obj, country_code),
+        function_name!(),
+    ) {
+        Some(failed_chip_ids) => failed_chip_ids,
+        None => *JObject::null(),
+    }
 }
 
-fn native_set_log_mode(env: JNIEnv, obj: JObject, log_mode_jstring: JString) -> Result<()> {
+fn native_set_country_code_all_chips(
+    env: JNIEnv,
+    obj: JObject,
+    country_code: jbyteArray,
+) -> Result<jobjectArray> {
+    let country_code = convert_country_code(env, country_code)?;
     let dispatcher = Dispatcher::get_dispatcher(env, obj)?;
-    let logger_mode_str = String::from(
-        env.get_string(log_mode_jstring).map_err(|_| Error::ForeignFunctionInterface)?,
-    );
-    debug!("UCI log: log started in {} mode", &logger_mode_str);
-    let logger_mode = logger_mode_str.try_into()?;
-    dispatcher.set_logger_mode(logger_mode)
+    let failed_chip_ids = dispatcher.set_country_code_all(country_code);
+
+    let failed_chip_ids_jobjectarray = env
+        .new_object_array(failed_chip_ids.len() as i32, "java/lang/String", JObject::null())
+        .map_err(|_| Error::ForeignFunctionInterface)?;
+    for (i, chip_id) in failed_chip_ids.iter().enumerate() {
+        let chip_id = env.new_string(chip_id).map_err(|_| Error::ForeignFunctionInterface)?;
+        env.set_object_array_element(failed_chip_ids_jobjectarray, i as i32, chip_id)
+            .map_err(|_| Error::ForeignFunctionInterface)?;
+    }
+    Ok(failed_chip_ids_jobjectarray)
+}
+
+/// Set country code on a single UWB device from its ISO 3166-1 alpha-2 string, e.g. "US", or "00"
+/// for the world mode. Return value defined by uci_packets.pdl
+#[no_mangle]
+pub extern "system" fn Java_com_android_server_uwb_jni_NativeUwbManager_nativeSetCountryCodeStr(
+    env: JNIEnv,
+    obj: JObject,
+    country_code: JString,
+    chip_id: JString,
+) -> jbyte {
+    debug!("{}: enter", function_name!());
+    byte_result_helper(
+        native_set_country_code_str(env, obj, country_code, chip_id),
+        function_name!(),
+    )
+}
+
+fn native_set_country_code_str(
+    env: JNIEnv,
+    obj: JObject,
+    country_code: JString,
+    chip_id: JString,
+) -> Result<()> {
+    let uci_manager = Dispatcher::get_uci_manager(env, obj, chip_id)?;
+    let country_code: String =
+        env.get_string(country_code).map_err(|_| Error::ForeignFunctionInterface)?.into();
+    debug!("Country code: {:?}", country_code);
+    uci_manager.android_set_country_code(parse_country_code(&country_code)?)
+}
+
+/// Parses an ISO 3166-1 alpha-2 country code, or "00" for the world mode. The code must be exactly
+/// two uppercase ASCII letters; lowercase input is rejected rather than normalized so that the
+/// caller's bug surfaces.
+fn parse_country_code(country_code: &str) -> Result<CountryCode> {
+    match *country_code.as_bytes() {
+        [b0 @ b'0', b1 @ b'0'] | [b0 @ b'A'..=b'Z', b1 @ b'A'..=b'Z'] => {
+            CountryCode::new(&[b0, b1]).ok_or(Error::BadParameters)
+        }
+        _ => {
+            error!(
+                "Invalid country code {:?}: expected two uppercase ASCII letters or \"00\"",
+                country_code
+            );
+            Err(Error::BadParameters)
+        }
+    }
+}
+
+/// Set log mode.
+#[no_mangle]
+pub extern "system" fn Java_com_android_server_uwb_jni_NativeUwbManager_nativeSetLogMode(
+    env: JNIEnv,
+    obj: JObject,
+    log_mode_jstring: JString,
+) -> jboolean {
+    debug!("{}: enter", function_name!());
+    boolean_result_helper(native_set_log_mode(env, obj, log_mode_jstring), function_name!())
+}
+
+fn native_set_log_mode(env: JNIEnv, obj: JObject, log_mode_jstring: JString) -> Result<()> {
+    let dispatcher = Dispatcher::get_dispatcher(env, obj)?;
+    let logger_mode_str = String::from(
+        env.get_string(log_mode_jstring).map_err(|_| Error::ForeignFunctionInterface)?,
+    );
+    debug!("UCI log: log started in {} mode", &logger_mode_str);
+    let logger_mode = logger_mode_str.try_into()?;
+    dispatcher.set_logger_mode(logger_mode)
+}
+
+/// Get the current log mode. Return null if failed.
+#[no_mangle]
+pub extern "system" fn Java_com_android_server_uwb_jni_NativeUwbManager_nativeGetLogMode(
+    env: JNIEnv,
+    obj: JObject,
+) -> jstring {
+    debug!("{}: enter", function_name!());
+    match option_result_helper(native_get_log_mode(env, obj), function_name!()) {
+        Some(log_mode) => log_mode,
+        None => *JObject::null(),
+    }
+}
+
+fn native_get_log_mode(env: JNIEnv, obj: JObject) -> Result<jstring> {
+    let dispatcher = Dispatcher::get_dispatcher(env, obj)?;
+    let logger_mode_str = log_mode_str(&dispatcher.logger_mode()?);
+    Ok(env.new_string(logger_mode_str).map_err(|_| Error::ForeignFunctionInterface)?.into_inner())
+}
+
+/// Names the log mode as accepted by nativeSetLogMode.
+fn log_mode_str(logger_mode: &UciLoggerMode) -> &'static str {
+    match logger_mode {
+        UciLoggerMode::Disabled => "Disabled",
+        UciLoggerMode::Unfiltered => "Unfiltered",
+        UciLoggerMode::Filtered => "Filtered",
+    }
+}
+
+/// Get whether the log mode is supported, to only offer the supported log modes.
+#[no_mangle]
+pub extern "system" fn Java_com_android_server_uwb_jni_NativeUwbManager_nativeIsLogModeSupported(
+    env: JNIEnv,
+    _obj: JObject,
+    log_mode_jstring: JString,
+) -> jboolean {
+    debug!("{}: enter", function_name!());
+    option_result_helper(
+        env.get_string(log_mode_jstring).map_err(|_| Error::ForeignFunctionInterface),
+        function_name!(),
+    )
+    .map_or(false, |logger_mode_str| is_log_mode_supported(String::from(logger_mode_str)))
+    .into()
+}
+
+fn is_log_mode_supported(logger_mode_str: String) -> bool {
+    UciLoggerMode::try_from(logger_mode_str).is_ok()
 }
 
 // # Safety
@@ -1042,24 +2083,48 @@ fn native_send_raw_vendor_cmd(
     chip_id: JString,
 ) -> Result<RawUciMessage> {
     let uci_manager = Dispatcher::get_uci_manager(env, obj, chip_id)?;
+    uci_manager.check_vendor_cmd(gid as u32, oid as u32)?;
     let payload =
         env.convert_byte_array(payload_jarray).map_err(|_| Error::ForeignFunctionInterface)?;
     uci_manager.raw_uci_cmd(mt as u32, gid as u32, oid as u32, payload)
 }
 
+// Counters of the power stats: idle time, TX time and RX time in ms, and total wake count.
+fn power_stats_counters(power_stats: &PowerStats) -> [u32; 4] {
+    [
+        power_stats.idle_time_ms,
+        power_stats.tx_time_ms,
+        power_stats.rx_time_ms,
+        power_stats.total_wake_count,
+    ]
+}
+
+// Counters of the power stats as jint, saturated at jint::MAX rather than wrapped to negative
+// values once they overflow.
+fn power_stats_counters_int(power_stats: &PowerStats) -> [jint; 4] {
+    power_stats_counters(power_stats).map(|counter| counter.try_into().unwrap_or(jint::MAX))
+}
+
+// Counters of the power stats as jlong, without loss.
+fn power_stats_counters_long(power_stats: &PowerStats) -> [jlong; 4] {
+    power_stats_counters(power_stats).map(jlong::from)
+}
+
 fn create_power_stats(power_stats: PowerStats, env: JNIEnv) -> Result<jobject> {
     let power_stats_class =
         env.find_class(POWER_STATS_CLASS).map_err(|_| Error::ForeignFunctionInterface)?;
-    match env.new_object(
-        power_stats_class,
-        "(IIII)V",
-        &[
-            JValue::Int(power_stats.idle_time_ms as i32),
-            JValue::Int(power_stats.tx_time_ms as i32),
-            JValue::Int(power_stats.rx_time_ms as i32),
-            JValue::Int(power_stats.total_wake_count as i32),
-        ],
-    ) {
+    let counters = power_stats_counters_int(&power_stats).map(JValue::Int);
+    match env.new_object(power_stats_class, "(IIII)V", &counters) {
+        Ok(o) => Ok(*o),
+        Err(_) => Err(Error::ForeignFunctionInterface),
+    }
+}
+
+fn create_power_stats_long(power_stats: PowerStats, env: JNIEnv) -> Result<jobject> {
+    let power_stats_class =
+        env.find_class(POWER_STATS_LONG_CLASS).map_err(|_| Error::ForeignFunctionInterface)?;
+    let counters = power_stats_counters_long(&power_stats).map(JValue::Long);
+    match env.new_object(power_stats_class, "(JJJJ)V", &counters) {
         Ok(o) => Ok(*o),
         Err(_) => Err(Error::ForeignFunctionInterface),
     }
@@ -1084,6 +2149,26 @@ pub extern "system" fn Java_com_android_server_uwb_jni_NativeUwbManager_nativeGe
     }
 }
 
+/// Get UWB power stats on a single UWB device, with 64-bit counters which do not overflow on long
+/// running devices. Returns a null object if failed.
+#[no_mangle]
+pub extern "system" fn Java_com_android_server_uwb_jni_NativeUwbManager_nativeGetPowerStatsLong(
+    env: JNIEnv,
+    obj: JObject,
+    chip_id: JString,
+) -> jobject {
+    debug!("{}: enter", function_name!());
+    match option_result_helper(native_get_power_stats(env, obj, chip_id), function_name!()) {
+        Some(ps) => create_power_stats_long(ps, env)
+            .map_err(|e| {
+                error!("{} failed with {:?}", function_name!(), &e);
+                e
+            })
+            .unwrap_or(*JObject::null()),
+        None => *JObject::null(),
+    }
+}
+
 fn native_get_power_stats(env: JNIEnv, obj: JObject, chip_id: JString) -> Result<PowerStats> {
     let uci_manager = Dispatcher::get_uci_manager(env, obj, chip_id)?;
     uci_manager.android_get_power_stats()
@@ -1179,12 +2264,84 @@ fn native_send_data(
         env.convert_byte_array(address).map_err(|_| Error::ForeignFunctionInterface)?;
     let app_payload_data_bytearray =
         env.convert_byte_array(app_payload_data).map_err(|_| Error::ForeignFunctionInterface)?;
+    let len = app_payload_data_bytearray.len();
     uci_manager.send_data_packet(
         session_id as u32,
         address_bytearray,
         uci_sequence_number as u16,
         app_payload_data_bytearray,
-    )
+    )?;
+    uci_manager.chip_state()?.on_data_sent(session_id as u32, len, now_ms());
+    Ok(())
+}
+
+/// Send the application payload in fragments of the max data size of the session, with consecutive
+/// UCI sequence numbers starting at uci_sequence_number. The transfer stops at the first fragment
+/// which fails to be sent. Return the number of fragments sent if the whole payload was sent, and
+/// -(1 + the number of fragments sent) if failed, so -1 if failed before sending any fragment.
+#[no_mangle]
+pub extern "system" fn Java_com_android_server_uwb_jni_NativeUwbManager_nativeSendDataFragmented(
+    env: JNIEnv,
+    obj: JObject,
+    session_id: jint,
+    address: jbyteArray,
+    uci_sequence_number: jshort,
+    app_payload_data: jbyteArray,
+    chip_id: JString,
+) -> jint {
+    debug!("{}: enter", function_name!());
+    match option_result_helper(
+        native_send_data_fragmented(
+            env,
+            obj,
+            session_id,
+            address,
+            uci_sequence_number,
+            app_payload_data,
+            chip_id,
+        ),
+        function_name!(),
+    ) {
+        Some(outcome) => outcome,
+        None => -1,
+    }
+}
+
+#[allow(clippy::too_many_arguments)]
+fn native_send_data_fragmented(
+    env: JNIEnv,
+    obj: JObject,
+    session_id: jint,
+    address: jbyteArray,
+    uci_sequence_number: jshort,
+    app_payload_data: jbyteArray,
+    chip_id: JString,
+) -> Result<i32> {
+    let uci_manager = Dispatcher::get_uci_manager(env, obj, chip_id)?;
+    let address_bytearray =
+        env.convert_byte_array(address).map_err(|_| Error::ForeignFunctionInterface)?;
+    let app_payload_data_bytearray =
+        env.convert_byte_array(app_payload_data).map_err(|_| Error::ForeignFunctionInterface)?;
+    let (fragment_lens, result) = send_data_fragmented(
+        &*uci_manager,
+        session_id as u32,
+        address_bytearray,
+        uci_sequence_number as u16,
+        app_payload_data_bytearray,
+    );
+    let mut chip_state = uci_manager.chip_state()?;
+    for len in fragment_lens.iter() {
+        chip_state.on_data_sent(session_id as u32, *len, now_ms());
+    }
+    if let Err(e) = &result {
+        error!(
+            "Sent {} fragments of session {} before failing: {:?}",
+            fragment_lens.len(),
+            session_id,
+            e
+        );
+    }
+    Ok(fragmented_send_outcome(fragment_lens.len(), &result))
 }
 
 /// Get max application data size, that can be sent by the UWBS. Return 0 if failed.
@@ -1216,6 +2373,33 @@ fn native_query_data_size(
     uci_manager.session_query_max_data_size(session_id as u32)
 }
 
+/// Get the max application data size of each of the sessions on a single UWB device, as an array
+/// of the ids of the sessions, in increasing order, followed by an array of their sizes. The size
+/// of a session whose query failed is 0. Return null if failed.
+#[no_mangle]
+pub extern "system" fn Java_com_android_server_uwb_jni_NativeUwbManager_nativeQueryDataSizeAll(
+    env: JNIEnv,
+    obj: JObject,
+    chip_id: JString,
+) -> jobjectArray {
+    debug!("{}: enter", function_name!());
+    match option_result_helper(native_query_data_size_all(env, obj, chip_id), function_name!()) {
+        Some(sizes) => sizes,
+        None => *JObject::null(),
+    }
+}
+
+fn native_query_data_size_all(env: JNIEnv, obj: JObject, chip_id: JString) -> Result<jobjectArray> {
+    let uci_manager = Dispatcher::get_uci_manager(env, obj, chip_id)?;
+    let session_ids = uci_manager.chip_state()?.session_ids();
+    let (session_ids, sizes): (Vec<_>, Vec<_>) =
+        session_query_max_data_size_all(&*uci_manager, session_ids)
+            .into_iter()
+            .map(|(session_id, size)| (session_id as jint, jint::from(size)))
+            .unzip();
+    create_int_array_pair(env, &session_ids, &sizes)
+}
+
 /// Set data transfer phase configuration
 #[no_mangle]
 pub extern "system" fn Java_com_android_server_uwb_jni_NativeUwbManager_nativeSessionDataTransferPhaseConfig(
@@ -1290,6 +2474,62 @@ fn native_query_time_stamp(env: JNIEnv, obj: JObject, chip_id: JString) -> Resul
     uci_manager.core_query_uwb_timestamp()
 }
 
+/// Get UWBS timestamp along with its resolution, as a UwbTimestamp carrying the timestamp, its
+/// unit, 0 for us, 1 for ns and 2 for device specific ticks, and the resolution in ns. The
+/// resolution is measured from the timestamps of the UWBS the first time it is queried. Return null
+/// if failed.
+#[no_mangle]
+pub extern "system" fn Java_com_android_server_uwb_jni_NativeUwbManager_nativeQueryUwbTimestampWithResolution(
+    env: JNIEnv,
+    obj: JObject,
+    chip_id: JString,
+) -> jobject {
+    debug!("{}: enter", function_name!());
+    match option_result_helper(
+        native_query_uwb_timestamp_with_resolution(env, obj, chip_id),
+        function_name!(),
+    ) {
+        Some((timestamp, resolution)) => create_uwb_timestamp(timestamp, resolution, env)
+            .map_err(|e| {
+                error!("{} failed with {:?}", function_name!(), &e);
+                e
+            })
+            .unwrap_or(*JObject::null()),
+        None => *JObject::null(),
+    }
+}
+
+fn native_query_uwb_timestamp_with_resolution(
+    env: JNIEnv,
+    obj: JObject,
+    chip_id: JString,
+) -> Result<(u64, TimestampResolution)> {
+    let uci_manager = Dispatcher::get_uci_manager(env, obj, chip_id)?;
+    let resolution = uwbs_timestamp_resolution(&uci_manager)?;
+    Ok((uci_manager.core_query_uwb_timestamp()?, resolution))
+}
+
+fn create_uwb_timestamp(
+    timestamp: u64,
+    resolution: TimestampResolution,
+    env: JNIEnv,
+) -> Result<jobject> {
+    let uwb_timestamp_class =
+        env.find_class(UWB_TIMESTAMP_CLASS).map_err(|_| Error::ForeignFunctionInterface)?;
+    match env.new_object(
+        uwb_timestamp_class,
+        "(JIJ)V",
+        &[
+            JValue::Long(timestamp.try_into().map_err(|_| Error::BadParameters)?),
+            JValue::Int(resolution.unit()),
+            JValue::Long(resolution.resolution_ns().into()),
+        ],
+    ) {
+        Ok(o) => Ok(*o),
+        Err(_) => Err(Error::ForeignFunctionInterface),
+    }
+}
+
 /// Get session token for the UWB session.
 #[no_mangle]
 pub extern "system" fn Java_com_android_server_uwb_jni_NativeUwbManager_nativeGetSessionToken(
@@ -1319,51 +2559,2176 @@ fn native_get_session_token(
     uci_manager.get_session_token(session_id as u32)
 }
 
-/// Get the class loader object. Has to be called from a JNIEnv where the local java classes are
-/// loaded. Results in a global reference to the class loader object that can be used to look for
-/// classes in other native thread.
-fn get_class_loader_obj(env: &JNIEnv) -> Result<GlobalRef> {
-    let ranging_data_class =
-        env.find_class(UWB_RANGING_DATA_CLASS).map_err(|_| Error::ForeignFunctionInterface)?;
-    let ranging_data_class_class =
-        env.get_object_class(ranging_data_class).map_err(|_| Error::ForeignFunctionInterface)?;
-    let get_class_loader_method = env
-        .get_method_id(ranging_data_class_class, "getClassLoader", "()Ljava/lang/ClassLoader;")
-        .map_err(|_| Error::ForeignFunctionInterface)?;
-    let class_loader = env
-        .call_method_unchecked(
-            ranging_data_class,
-            get_class_loader_method,
-            ReturnType::Object,
-            &[jvalue::from(JValue::Void)],
-        )
-        .map_err(|_| Error::ForeignFunctionInterface)?;
-    let class_loader_jobject = class_loader.l().map_err(|_| Error::ForeignFunctionInterface)?;
-    env.new_global_ref(class_loader_jobject).map_err(|_| Error::ForeignFunctionInterface)
+/// Sets a single app configuration TLV on a single UWB device.
+fn native_set_app_config_tlv(
+    env: JNIEnv,
+    obj: JObject,
+    session_id: jint,
+    tlv: AppConfigTlv,
+    chip_id: JString,
+) -> Result<()> {
+    let uci_manager = Dispatcher::get_uci_manager(env, obj, chip_id)?;
+    session_set_app_config_tlv(&*uci_manager, session_id as u32, tlv)
 }
 
-/// Create the dispatcher. Returns pointer to Dispatcher casted as jlong that owns the dispatcher.
+/// Reads back a single integer app configuration of a single UWB device.
+fn native_get_app_config_value(
+    env: JNIEnv,
+    obj: JObject,
+    session_id: jint,
+    cfg_id: AppConfigTlvType,
+    chip_id: JString,
+) -> Result<u32> {
+    let uci_manager = Dispatcher::get_uci_manager(env, obj, chip_id)?;
+    decode_le_value(&session_get_app_config_value(&*uci_manager, session_id as u32, cfg_id)?)
+}
+
+/// Set the number of transmissions per ranging round, as the MIN_FRAMES_PER_RR of the session.
+/// Return value defined by uci_packets.pdl
 #[no_mangle]
-pub extern "system" fn Java_com_android_server_uwb_jni_NativeUwbManager_nativeDispatcherNew(
+pub extern "system" fn Java_com_android_server_uwb_jni_NativeUwbManager_nativeSetTxPerRound(
     env: JNIEnv,
     obj: JObject,
-    chip_ids_jarray: jobjectArray,
-) -> jlong {
+    session_id: jint,
+    count: jint,
+    chip_id: JString,
+) -> jbyte {
     debug!("{}: enter", function_name!());
-    match option_result_helper(native_dispatcher_new(env, obj, chip_ids_jarray), function_name!()) {
-        Some(ptr) => ptr as jlong,
-        None => *JObject::null() as jlong,
-    }
+    byte_result_helper(
+        native_set_tx_per_round(env, obj, session_id, count, chip_id),
+        function_name!(),
+    )
 }
 
-fn native_dispatcher_new(
+fn native_set_tx_per_round(
     env: JNIEnv,
     obj: JObject,
-    chip_ids_jarray: jobjectArray,
-) -> Result<*const Dispatcher> {
-    let chip_ids_len: i32 =
-        env.get_array_length(chip_ids_jarray).map_err(|_| Error::ForeignFunctionInterface)?;
-    let chip_ids = (0..chip_ids_len)
+    session_id: jint,
+    count: jint,
+    chip_id: JString,
+) -> Result<()> {
+    native_set_app_config_tlv(env, obj, session_id, tx_per_round_tlv(count)?, chip_id)
+}
+
+/// Get the number of transmissions per ranging round, from the MIN_FRAMES_PER_RR of the session.
+/// Return -1 if failed.
+#[no_mangle]
+pub extern "system" fn Java_com_android_server_uwb_jni_NativeUwbManager_nativeGetTxPerRound(
+    env: JNIEnv,
+    obj: JObject,
+    session_id: jint,
+    chip_id: JString,
+) -> jint {
+    debug!("{}: enter", function_name!());
+    match option_result_helper(
+        fira_cfg_id(MIN_FRAMES_PER_RR)
+            .and_then(|cfg_id| native_get_app_config_value(env, obj, session_id, cfg_id, chip_id)),
+        function_name!(),
+    ) {
+        // MIN_FRAMES_PER_RR is 1 byte long, will not overflow i32
+        Some(count) => count as jint,
+        None => -1,
+    }
+}
+
+/// Set the slot duration of the session, in RSTU. Return value defined by uci_packets.pdl
+#[no_mangle]
+pub extern "system" fn Java_com_android_server_uwb_jni_NativeUwbManager_nativeSetSlotDuration(
+    env: JNIEnv,
+    obj: JObject,
+    session_id: jint,
+    slot_duration_rstu: jint,
+    chip_id: JString,
+) -> jbyte {
+    debug!("{}: enter", function_name!());
+    byte_result_helper(
+        native_set_slot_duration(env, obj, session_id, slot_duration_rstu, chip_id),
+        function_name!(),
+    )
+}
+
+fn native_set_slot_duration(
+    env: JNIEnv,
+    obj: JObject,
+    session_id: jint,
+    slot_duration_rstu: jint,
+    chip_id: JString,
+) -> Result<()> {
+    let uci_manager = Dispatcher::get_uci_manager(env, obj, chip_id)?;
+    let min_slot_duration_rstu =
+        find_cap_u32(&uci_manager.core_get_caps_info()?, SUPPORTED_MIN_SLOT_DURATION_RSTU);
+    session_set_app_config_tlv(
+        &*uci_manager,
+        session_id as u32,
+        slot_duration_tlv(slot_duration_rstu, min_slot_duration_rstu)?,
+    )
+}
+
+/// Get the slot duration of the session, in RSTU. Return -1 if failed.
+#[no_mangle]
+pub extern "system" fn Java_com_android_server_uwb_jni_NativeUwbManager_nativeGetSlotDuration(
+    env: JNIEnv,
+    obj: JObject,
+    session_id: jint,
+    chip_id: JString,
+) -> jint {
+    debug!("{}: enter", function_name!());
+    match option_result_helper(
+        native_get_app_config_value(env, obj, session_id, AppConfigTlvType::SlotDuration, chip_id),
+        function_name!(),
+    ) {
+        // SLOT_DURATION is 2 bytes long, will not overflow i32
+        Some(slot_duration) => slot_duration as jint,
+        None => -1,
+    }
+}
+
+/// Get the base unit of the session ranging interval, in RSTU. Return -1 if failed.
+#[no_mangle]
+pub extern "system" fn Java_com_android_server_uwb_jni_NativeUwbManager_nativeGetBlockTimingBase(
+    env: JNIEnv,
+    obj: JObject,
+    session_id: jint,
+    chip_id: JString,
+) -> jint {
+    debug!("{}: enter", function_name!());
+    match option_result_helper(
+        native_get_block_timing_base(env, obj, session_id, chip_id),
+        function_name!(),
+    ) {
+        Some(base) => base as jint,
+        None => -1,
+    }
+}
+
+fn native_get_block_timing_base(
+    env: JNIEnv,
+    obj: JObject,
+    session_id: jint,
+    chip_id: JString,
+) -> Result<u32> {
+    block_timing_base_rstu(native_get_app_config_value(
+        env,
+        obj,
+        session_id,
+        AppConfigTlvType::RangingTimeStruct,
+        chip_id,
+    )?)
+}
+
+/// Check if the UWB device supports provisioned STS. Return false if failed.
+#[no_mangle]
+pub extern "system" fn Java_com_android_server_uwb_jni_NativeUwbManager_nativeIsProvisionedStsSupported(
+    env: JNIEnv,
+    obj: JObject,
+    chip_id: JString,
+) -> jboolean {
+    debug!("{}: enter", function_name!());
+    option_result_helper(native_get_caps_info(env, obj, chip_id), function_name!())
+        .map_or(false, |tlvs| is_provisioned_sts_supported(&tlvs))
+        .into()
+}
+
+/// Check if the UWB device supports a sub-session key for each controlee, to be sent in the
+/// multicast list updates. The supported lengths of the keys are reported by
+/// nativeGetSupportedStsKeyLengths. Return false if unknown or if failed.
+#[no_mangle]
+pub extern "system" fn Java_com_android_server_uwb_jni_NativeUwbManager_nativeIsSubSessionKeySupported(
+    env: JNIEnv,
+    obj: JObject,
+    chip_id: JString,
+) -> jboolean {
+    debug!("{}: enter", function_name!());
+    option_result_helper(native_get_caps_info(env, obj, chip_id), function_name!())
+        .map_or(false, |tlvs| is_sub_session_key_supported(&tlvs))
+        .into()
+}
+
+/// Set the responder initiator time offset of the session, in ms. Return value defined by
+/// uci_packets.pdl
+#[no_mangle]
+pub extern "system" fn Java_com_android_server_uwb_jni_NativeUwbManager_nativeSetRangingTimeOffset(
+    env: JNIEnv,
+    obj: JObject,
+    session_id: jint,
+    offset: jint,
+    chip_id: JString,
+) -> jbyte {
+    debug!("{}: enter", function_name!());
+    byte_result_helper(
+        native_set_ranging_time_offset(env, obj, session_id, offset, chip_id),
+        function_name!(),
+    )
+}
+
+fn native_set_ranging_time_offset(
+    env: JNIEnv,
+    obj: JObject,
+    session_id: jint,
+    offset: jint,
+    chip_id: JString,
+) -> Result<()> {
+    native_set_app_config_tlv(env, obj, session_id, ranging_time_offset_tlv(offset)?, chip_id)
+}
+
+/// Get the responder initiator time offset of the session, in ms. Return -1 if failed.
+#[no_mangle]
+pub extern "system" fn Java_com_android_server_uwb_jni_NativeUwbManager_nativeGetRangingTimeOffset(
+    env: JNIEnv,
+    obj: JObject,
+    session_id: jint,
+    chip_id: JString,
+) -> jint {
+    debug!("{}: enter", function_name!());
+    match option_result_helper(
+        native_get_app_config_value(
+            env,
+            obj,
+            session_id,
+            AppConfigTlvType::UwbInitiationTime,
+            chip_id,
+        ),
+        function_name!(),
+    ) {
+        Some(offset) => offset.try_into().unwrap_or(-1),
+        None => -1,
+    }
+}
+
+/// Get the number of ranging rounds completed by the session. Return -1 if failed.
+#[no_mangle]
+pub extern "system" fn Java_com_android_server_uwb_jni_NativeUwbManager_nativeGetCompletedRoundCount(
+    env: JNIEnv,
+    obj: JObject,
+    session_id: jint,
+    chip_id: JString,
+) -> jlong {
+    debug!("{}: enter", function_name!());
+    match option_result_helper(
+        native_get_completed_round_count(env, obj, session_id, chip_id),
+        function_name!(),
+    ) {
+        Some(count) => count.try_into().unwrap_or(jlong::MAX),
+        None => -1,
+    }
+}
+
+fn native_get_completed_round_count(
+    env: JNIEnv,
+    obj: JObject,
+    session_id: jint,
+    chip_id: JString,
+) -> Result<u64> {
+    let uci_manager = Dispatcher::get_uci_manager(env, obj, chip_id)?;
+    let count = uci_manager.chip_state()?.completed_round_count(session_id as u32);
+    Ok(count)
+}
+
+/// Set the maximum number of slots per ranging round. Return value defined by uci_packets.pdl
+#[no_mangle]
+pub extern "system" fn Java_com_android_server_uwb_jni_NativeUwbManager_nativeSetSlotsPerRound(
+    env: JNIEnv,
+    obj: JObject,
+    session_id: jint,
+    slots: jint,
+    chip_id: JString,
+) -> jbyte {
+    debug!("{}: enter", function_name!());
+    byte_result_helper(
+        native_set_slots_per_round(env, obj, session_id, slots, chip_id),
+        function_name!(),
+    )
+}
+
+fn native_set_slots_per_round(
+    env: JNIEnv,
+    obj: JObject,
+    session_id: jint,
+    slots: jint,
+    chip_id: JString,
+) -> Result<()> {
+    native_set_app_config_tlv(env, obj, session_id, slots_per_round_tlv(slots)?, chip_id)
+}
+
+/// Get the maximum number of slots per ranging round. Return -1 if failed.
+#[no_mangle]
+pub extern "system" fn Java_com_android_server_uwb_jni_NativeUwbManager_nativeGetSlotsPerRound(
+    env: JNIEnv,
+    obj: JObject,
+    session_id: jint,
+    chip_id: JString,
+) -> jint {
+    debug!("{}: enter", function_name!());
+    match option_result_helper(
+        native_get_app_config_value(env, obj, session_id, AppConfigTlvType::SlotsPerRr, chip_id),
+        function_name!(),
+    ) {
+        // SLOTS_PER_RR is 1 byte long, will not overflow i32
+        Some(slots) => slots as jint,
+        None => -1,
+    }
+}
+
+/// Set the application defined sub-session id of the session. Return value defined by
+/// uci_packets.pdl
+#[no_mangle]
+pub extern "system" fn Java_com_android_server_uwb_jni_NativeUwbManager_nativeSetSubSessionId(
+    env: JNIEnv,
+    obj: JObject,
+    session_id: jint,
+    sub_session_id: jint,
+    chip_id: JString,
+) -> jbyte {
+    debug!("{}: enter", function_name!());
+    byte_result_helper(
+        native_set_app_config_tlv(
+            env,
+            obj,
+            session_id,
+            sub_session_id_tlv(sub_session_id),
+            chip_id,
+        ),
+        function_name!(),
+    )
+}
+
+/// Get the application defined sub-session id of the session. Return -1 if failed.
+#[no_mangle]
+pub extern "system" fn Java_com_android_server_uwb_jni_NativeUwbManager_nativeGetSubSessionId(
+    env: JNIEnv,
+    obj: JObject,
+    session_id: jint,
+    chip_id: JString,
+) -> jlong {
+    debug!("{}: enter", function_name!());
+    match option_result_helper(
+        native_get_app_config_value(env, obj, session_id, AppConfigTlvType::SubSessionId, chip_id),
+        function_name!(),
+    ) {
+        Some(sub_session_id) => sub_session_id as jlong,
+        None => -1,
+    }
+}
+
+/// Get the range of FiRa PHY versions supported by the UWBS, as an array of the major and minor
+/// numbers of the min version followed by those of the max version. Return null if failed.
+#[no_mangle]
+pub extern "system" fn Java_com_android_server_uwb_jni_NativeUwbManager_nativeGetSupportedFiraPhyVersions(
+    env: JNIEnv,
+    obj: JObject,
+    chip_id: JString,
+) -> jintArray {
+    debug!("{}: enter", function_name!());
+    match option_result_helper(
+        native_get_supported_fira_phy_versions(env, obj, chip_id),
+        function_name!(),
+    ) {
+        Some(versions) => versions,
+        None => *JObject::null(),
+    }
+}
+
+fn native_get_supported_fira_phy_versions(
+    env: JNIEnv,
+    obj: JObject,
+    chip_id: JString,
+) -> Result<jintArray> {
+    let range = find_fira_phy_version_range(&native_get_caps_info(env, obj, chip_id)?)
+        .ok_or(Error::BadParameters)?;
+    let versions =
+        [range.min.major, range.min.minor, range.max.major, range.max.minor].map(jint::from);
+    create_int_array(env, &versions)
+}
+
+/// Enable or disable the ranging result report phase in each ranging round of the session. Return
+/// value defined by uci_packets.pdl
+#[no_mangle]
+pub extern "system" fn Java_com_android_server_uwb_jni_NativeUwbManager_nativeSetResultReportPhase(
+    env: JNIEnv,
+    obj: JObject,
+    session_id: jint,
+    enable: jboolean,
+    chip_id: JString,
+) -> jbyte {
+    debug!("{}: enter", function_name!());
+    byte_result_helper(
+        native_set_result_report_phase(env, obj, session_id, enable != 0, chip_id),
+        function_name!(),
+    )
+}
+
+fn native_set_result_report_phase(
+    env: JNIEnv,
+    obj: JObject,
+    session_id: jint,
+    enable: bool,
+    chip_id: JString,
+) -> Result<()> {
+    let uci_manager = Dispatcher::get_uci_manager(env, obj, chip_id)?;
+    // The other phases of the ranging round are controlled by the same TLV.
+    let ranging_round_control = decode_le_value(&session_get_app_config_value(
+        &*uci_manager,
+        session_id as u32,
+        AppConfigTlvType::RangingRoundControl,
+    )?)?;
+    session_set_app_config_tlv(
+        &*uci_manager,
+        session_id as u32,
+        result_report_phase_tlv(ranging_round_control, enable)?,
+    )
+}
+
+/// Get whether the ranging result report phase is enabled in the session. Return 1 if enabled, 0
+/// if disabled and -1 if failed.
+#[no_mangle]
+pub extern "system" fn Java_com_android_server_uwb_jni_NativeUwbManager_nativeGetResultReportPhase(
+    env: JNIEnv,
+    obj: JObject,
+    session_id: jint,
+    chip_id: JString,
+) -> jint {
+    debug!("{}: enter", function_name!());
+    match option_result_helper(
+        native_get_app_config_value(
+            env,
+            obj,
+            session_id,
+            AppConfigTlvType::RangingRoundControl,
+            chip_id,
+        ),
+        function_name!(),
+    ) {
+        Some(ranging_round_control) => is_result_report_phase_enabled(ranging_round_control).into(),
+        None => -1,
+    }
+}
+
+/// Set the minimum interval in ms between two range data notifications of the session delivered to
+/// the Java side, 0 to disable the throttling. The notifications received in between are dropped.
+/// Return value defined by uci_packets.pdl
+#[no_mangle]
+pub extern "system" fn Java_com_android_server_uwb_jni_NativeUwbManager_nativeSetMinFrameReportInterval(
+    env: JNIEnv,
+    obj: JObject,
+    session_id: jint,
+    interval_ms: jint,
+    chip_id: JString,
+) -> jbyte {
+    debug!("{}: enter", function_name!());
+    byte_result_helper(
+        native_set_min_frame_report_interval(env, obj, session_id, interval_ms, chip_id),
+        function_name!(),
+    )
+}
+
+fn native_set_min_frame_report_interval(
+    env: JNIEnv,
+    obj: JObject,
+    session_id: jint,
+    interval_ms: jint,
+    chip_id: JString,
+) -> Result<()> {
+    let interval_ms = u64::try_from(interval_ms).map_err(|_| Error::BadParameters)?;
+    let uci_manager = Dispatcher::get_uci_manager(env, obj, chip_id)?;
+    uci_manager.chip_state()?.set_min_report_interval(session_id as u32, interval_ms);
+    Ok(())
+}
+
+/// Get the minimum interval in ms between two range data notifications of the session delivered to
+/// the Java side, 0 if not throttled. Return -1 if failed.
+#[no_mangle]
+pub extern "system" fn Java_com_android_server_uwb_jni_NativeUwbManager_nativeGetMinFrameReportInterval(
+    env: JNIEnv,
+    obj: JObject,
+    session_id: jint,
+    chip_id: JString,
+) -> jint {
+    debug!("{}: enter", function_name!());
+    match option_result_helper(
+        native_get_min_frame_report_interval(env, obj, session_id, chip_id),
+        function_name!(),
+    ) {
+        Some(interval_ms) => interval_ms.try_into().unwrap_or(-1),
+        None => -1,
+    }
+}
+
+/// Get whether the UWBS supports ranging and radar sessions at the same time. Return false if not
+/// advertised by the UWBS.
+#[no_mangle]
+pub extern "system" fn Java_com_android_server_uwb_jni_NativeUwbManager_nativeIsConcurrentRangingRadarSupported(
+    env: JNIEnv,
+    obj: JObject,
+    chip_id: JString,
+) -> jboolean {
+    debug!("{}: enter", function_name!());
+    option_result_helper(native_get_caps_info(env, obj, chip_id), function_name!())
+        .map_or(false, |tlvs| is_concurrent_ranging_radar_supported(&tlvs))
+        .into()
+}
+
+fn native_get_min_frame_report_interval(
+    env: JNIEnv,
+    obj: JObject,
+    session_id: jint,
+    chip_id: JString,
+) -> Result<u64> {
+    let uci_manager = Dispatcher::get_uci_manager(env, obj, chip_id)?;
+    let interval_ms = uci_manager.chip_state()?.min_report_interval(session_id as u32);
+    Ok(interval_ms)
+}
+
+/// Set how the ranging measurements of the session delivered to the Java side recover after a
+/// NLOS detection: 0 to report the NLOS measurements as is, and 1 to hold the last measurement of
+/// the controlee in line of sight until the line of sight is recovered. Return value defined by
+/// uci_packets.pdl
+#[no_mangle]
+pub extern "system" fn Java_com_android_server_uwb_jni_NativeUwbManager_nativeSetNlosRecoveryMode(
+    env: JNIEnv,
+    obj: JObject,
+    session_id: jint,
+    mode: jint,
+    chip_id: JString,
+) -> jbyte {
+    debug!("{}: enter", function_name!());
+    byte_result_helper(
+        native_set_nlos_recovery_mode(env, obj, session_id, mode, chip_id),
+        function_name!(),
+    )
+}
+
+fn native_set_nlos_recovery_mode(
+    env: JNIEnv,
+    obj: JObject,
+    session_id: jint,
+    mode: jint,
+    chip_id: JString,
+) -> Result<()> {
+    let mode = u8::try_from(mode).map_err(|_| Error::BadParameters)?;
+    let uci_manager = Dispatcher::get_uci_manager(env, obj, chip_id)?;
+    let result = uci_manager.chip_state()?.set_nlos_recovery_mode(session_id as u32, mode);
+    result
+}
+
+/// Get the NLOS recovery mode of the session. Return -1 if failed.
+#[no_mangle]
+pub extern "system" fn Java_com_android_server_uwb_jni_NativeUwbManager_nativeGetNlosRecoveryMode(
+    env: JNIEnv,
+    obj: JObject,
+    session_id: jint,
+    chip_id: JString,
+) -> jint {
+    debug!("{}: enter", function_name!());
+    match option_result_helper(
+        native_get_nlos_recovery_mode(env, obj, session_id, chip_id),
+        function_name!(),
+    ) {
+        Some(mode) => mode.into(),
+        None => -1,
+    }
+}
+
+fn native_get_nlos_recovery_mode(
+    env: JNIEnv,
+    obj: JObject,
+    session_id: jint,
+    chip_id: JString,
+) -> Result<u8> {
+    let uci_manager = Dispatcher::get_uci_manager(env, obj, chip_id)?;
+    let mode = uci_manager.chip_state()?.nlos_recovery_mode(session_id as u32);
+    Ok(mode)
+}
+
+/// Get the most recent UCI commands sent to the UWBS, from the oldest to the most recent one. Each
+/// command is encoded as the 2 bytes opcode, the 1 byte status and the 8 bytes timestamp in ms, in
+/// little endian. Return null if failed.
+#[no_mangle]
+pub extern "system" fn Java_com_android_server_uwb_jni_NativeUwbManager_nativeGetRecentCommands(
+    env: JNIEnv,
+    obj: JObject,
+    chip_id: JString,
+) -> jbyteArray {
+    debug!("{}: enter", function_name!());
+    match option_result_helper(native_get_recent_commands(env, obj, chip_id), function_name!()) {
+        Some(recent_commands) => env
+            .byte_array_from_slice(&recent_commands)
+            .map_err(|e| {
+                error!("{} failed with {:?}", function_name!(), &e);
+                e
+            })
+            .unwrap_or(*JObject::null()),
+        None => *JObject::null(),
+    }
+}
+
+fn native_get_recent_commands(env: JNIEnv, obj: JObject, chip_id: JString) -> Result<Vec<u8>> {
+    let uci_manager = Dispatcher::get_uci_manager(env, obj, chip_id)?;
+    let recent_commands = uci_manager.chip_state()?.encode_recent_commands();
+    Ok(recent_commands)
+}
+
+/// Set the RFRAME configuration of the session, 0 for SP0, 1 for SP1 and 3 for SP3. Return value
+/// defined by uci_packets.pdl
+#[no_mangle]
+pub extern "system" fn Java_com_android_server_uwb_jni_NativeUwbManager_nativeSetRframeConfig(
+    env: JNIEnv,
+    obj: JObject,
+    session_id: jint,
+    config: jint,
+    chip_id: JString,
+) -> jbyte {
+    debug!("{}: enter", function_name!());
+    byte_result_helper(
+        native_set_rframe_config(env, obj, session_id, config, chip_id),
+        function_name!(),
+    )
+}
+
+fn native_set_rframe_config(
+    env: JNIEnv,
+    obj: JObject,
+    session_id: jint,
+    config: jint,
+    chip_id: JString,
+) -> Result<()> {
+    native_set_app_config_tlv(env, obj, session_id, rframe_config_tlv(config)?, chip_id)
+}
+
+/// Get the RFRAME configuration of the session. Return -1 if failed.
+#[no_mangle]
+pub extern "system" fn Java_com_android_server_uwb_jni_NativeUwbManager_nativeGetRframeConfig(
+    env: JNIEnv,
+    obj: JObject,
+    session_id: jint,
+    chip_id: JString,
+) -> jint {
+    debug!("{}: enter", function_name!());
+    match option_result_helper(
+        native_get_app_config_value(env, obj, session_id, AppConfigTlvType::RframeConfig, chip_id),
+        function_name!(),
+    ) {
+        Some(config) => config.try_into().unwrap_or(-1),
+        None => -1,
+    }
+}
+
+/// Get the data stats of the session since they were last reset, as an array of the bytes sent,
+/// the bytes received and the throughput in bits per second. Return null if failed.
+#[no_mangle]
+pub extern "system" fn Java_com_android_server_uwb_jni_NativeUwbManager_nativeGetDataThroughputStats(
+    env: JNIEnv,
+    obj: JObject,
+    session_id: jint,
+    chip_id: JString,
+) -> jlongArray {
+    debug!("{}: enter", function_name!());
+    match option_result_helper(
+        native_get_data_throughput_stats(env, obj, session_id, chip_id),
+        function_name!(),
+    ) {
+        Some(stats) => stats,
+        None => *JObject::null(),
+    }
+}
+
+fn native_get_data_throughput_stats(
+    env: JNIEnv,
+    obj: JObject,
+    session_id: jint,
+    chip_id: JString,
+) -> Result<jlongArray> {
+    let uci_manager = Dispatcher::get_uci_manager(env, obj, chip_id)?;
+    let stats = uci_manager.chip_state()?.data_throughput_stats(session_id as u32);
+    let stats = [stats.bytes_sent, stats.bytes_received, stats.throughput_bps()]
+        .map(|value| value as jlong);
+    let stats_jlongarray =
+        env.new_long_array(stats.len() as i32).map_err(|_| Error::ForeignFunctionInterface)?;
+    env.set_long_array_region(stats_jlongarray, 0, &stats)
+        .map_err(|_| Error::ForeignFunctionInterface)?;
+    Ok(stats_jlongarray)
+}
+
+/// Reset the data stats of the session. Return value defined by uci_packets.pdl
+#[no_mangle]
+pub extern "system" fn Java_com_android_server_uwb_jni_NativeUwbManager_nativeResetDataThroughputStats(
+    env: JNIEnv,
+    obj: JObject,
+    session_id: jint,
+    chip_id: JString,
+) -> jbyte {
+    debug!("{}: enter", function_name!());
+    byte_result_helper(
+        native_reset_data_throughput_stats(env, obj, session_id, chip_id),
+        function_name!(),
+    )
+}
+
+fn native_reset_data_throughput_stats(
+    env: JNIEnv,
+    obj: JObject,
+    session_id: jint,
+    chip_id: JString,
+) -> Result<()> {
+    let uci_manager = Dispatcher::get_uci_manager(env, obj, chip_id)?;
+    uci_manager.chip_state()?.reset_data_throughput_stats(session_id as u32, now_ms());
+    Ok(())
+}
+
+/// Set the preamble duration of the session, either 32 or 64 symbols. Return value defined by
+/// uci_packets.pdl
+#[no_mangle]
+pub extern "system" fn Java_com_android_server_uwb_jni_NativeUwbManager_nativeSetPreambleDuration(
+    env: JNIEnv,
+    obj: JObject,
+    session_id: jint,
+    duration_symbols: jint,
+    chip_id: JString,
+) -> jbyte {
+    debug!("{}: enter", function_name!());
+    byte_result_helper(
+        native_set_preamble_duration(env, obj, session_id, duration_symbols, chip_id),
+        function_name!(),
+    )
+}
+
+fn native_set_preamble_duration(
+    env: JNIEnv,
+    obj: JObject,
+    session_id: jint,
+    duration_symbols: jint,
+    chip_id: JString,
+) -> Result<()> {
+    native_set_app_config_tlv(
+        env,
+        obj,
+        session_id,
+        preamble_duration_tlv(duration_symbols)?,
+        chip_id,
+    )
+}
+
+/// Get the preamble duration of the session, in symbols. Return -1 if failed.
+#[no_mangle]
+pub extern "system" fn Java_com_android_server_uwb_jni_NativeUwbManager_nativeGetPreambleDuration(
+    env: JNIEnv,
+    obj: JObject,
+    session_id: jint,
+    chip_id: JString,
+) -> jint {
+    debug!("{}: enter", function_name!());
+    match option_result_helper(
+        native_get_app_config_value(
+            env,
+            obj,
+            session_id,
+            AppConfigTlvType::PreambleDuration,
+            chip_id,
+        ),
+        function_name!(),
+    ) {
+        Some(preamble_duration) => preamble_duration_symbols(preamble_duration)
+            .map_or(-1, |duration_symbols| duration_symbols as jint),
+        None => -1,
+    }
+}
+
+/// Get the block duration in ms of the session, as adjusted by the UWBS once ranging started.
+/// Return -1 if the session is not active or if failed.
+#[no_mangle]
+pub extern "system" fn Java_com_android_server_uwb_jni_NativeUwbManager_nativeGetEffectiveBlockDuration(
+    env: JNIEnv,
+    obj: JObject,
+    session_id: jint,
+    chip_id: JString,
+) -> jint {
+    debug!("{}: enter", function_name!());
+    match option_result_helper(
+        native_get_effective_block_duration(env, obj, session_id, chip_id),
+        function_name!(),
+    ) {
+        Some(Some(block_duration_ms)) => block_duration_ms.try_into().unwrap_or(-1),
+        _ => -1,
+    }
+}
+
+fn native_get_effective_block_duration(
+    env: JNIEnv,
+    obj: JObject,
+    session_id: jint,
+    chip_id: JString,
+) -> Result<Option<u32>> {
+    let uci_manager = Dispatcher::get_uci_manager(env, obj, chip_id)?;
+    session_get_effective_block_duration_ms(&*uci_manager, session_id as u32)
+}
+
+/// Set the PSDU data rate of the session, 0 for 6.81 Mbps, 1 for 7.80 Mbps, 2 for 27.2 Mbps, 3 for
+/// 31.2 Mbps and 4 for 850 kbps. Return value defined by uci_packets.pdl
+#[no_mangle]
+pub extern "system" fn Java_com_android_server_uwb_jni_NativeUwbManager_nativeSetPsduDataRate(
+    env: JNIEnv,
+    obj: JObject,
+    session_id: jint,
+    rate: jint,
+    chip_id: JString,
+) -> jbyte {
+    debug!("{}: enter", function_name!());
+    byte_result_helper(
+        native_set_psdu_data_rate(env, obj, session_id, rate, chip_id),
+        function_name!(),
+    )
+}
+
+fn native_set_psdu_data_rate(
+    env: JNIEnv,
+    obj: JObject,
+    session_id: jint,
+    rate: jint,
+    chip_id: JString,
+) -> Result<()> {
+    let uci_manager = Dispatcher::get_uci_manager(env, obj, chip_id)?;
+    let hprf_supported = is_hprf_supported(&uci_manager.core_get_caps_info()?);
+    session_set_app_config_tlv(
+        &*uci_manager,
+        session_id as u32,
+        psdu_data_rate_tlv(rate, hprf_supported)?,
+    )
+}
+
+/// Get the PSDU data rate of the session. Return -1 if failed.
+#[no_mangle]
+pub extern "system" fn Java_com_android_server_uwb_jni_NativeUwbManager_nativeGetPsduDataRate(
+    env: JNIEnv,
+    obj: JObject,
+    session_id: jint,
+    chip_id: JString,
+) -> jint {
+    debug!("{}: enter", function_name!());
+    match option_result_helper(
+        native_get_app_config_value(env, obj, session_id, AppConfigTlvType::PsduDataRate, chip_id),
+        function_name!(),
+    ) {
+        Some(rate) => rate.try_into().unwrap_or(-1),
+        None => -1,
+    }
+}
+
+/// Get the channel bandwidths, in kHz, supported by the UWBS, e.g. 499200 for the 499.2 MHz
+/// channels. Return an empty array if not advertised by the UWBS, and null if failed.
+#[no_mangle]
+pub extern "system" fn Java_com_android_server_uwb_jni_NativeUwbManager_nativeGetSupportedBandwidths(
+    env: JNIEnv,
+    obj: JObject,
+    chip_id: JString,
+) -> jintArray {
+    debug!("{}: enter", function_name!());
+    match option_result_helper(native_get_supported_bandwidths(env, obj, chip_id), function_name!())
+    {
+        Some(bandwidths) => bandwidths,
+        None => *JObject::null(),
+    }
+}
+
+fn native_get_supported_bandwidths(
+    env: JNIEnv,
+    obj: JObject,
+    chip_id: JString,
+) -> Result<jintArray> {
+    let bandwidths = find_supported_bandwidths_khz(&native_get_caps_info(env, obj, chip_id)?)
+        .into_iter()
+        .map(|bandwidth_khz| bandwidth_khz as jint)
+        .collect::<Vec<_>>();
+    create_int_array(env, &bandwidths)
+}
+
+/// Set the hopping sequence of the controller of the session, 0 to disable hopping. Return value
+/// defined by uci_packets.pdl
+#[no_mangle]
+pub extern "system" fn Java_com_android_server_uwb_jni_NativeUwbManager_nativeSetHoppingSequence(
+    env: JNIEnv,
+    obj: JObject,
+    session_id: jint,
+    sequence: jint,
+    chip_id: JString,
+) -> jbyte {
+    debug!("{}: enter", function_name!());
+    byte_result_helper(
+        native_set_hopping_sequence(env, obj, session_id, sequence, chip_id),
+        function_name!(),
+    )
+}
+
+fn native_set_hopping_sequence(
+    env: JNIEnv,
+    obj: JObject,
+    session_id: jint,
+    sequence: jint,
+    chip_id: JString,
+) -> Result<()> {
+    native_set_app_config_tlv(env, obj, session_id, hopping_sequence_tlv(sequence)?, chip_id)
+}
+
+/// Get the hopping sequence of the controller of the session. Return -1 if failed.
+#[no_mangle]
+pub extern "system" fn Java_com_android_server_uwb_jni_NativeUwbManager_nativeGetHoppingSequence(
+    env: JNIEnv,
+    obj: JObject,
+    session_id: jint,
+    chip_id: JString,
+) -> jint {
+    debug!("{}: enter", function_name!());
+    match option_result_helper(
+        native_get_app_config_value(env, obj, session_id, AppConfigTlvType::HoppingMode, chip_id),
+        function_name!(),
+    ) {
+        Some(sequence) => sequence.try_into().unwrap_or(-1),
+        None => -1,
+    }
+}
+
+/// Set the number of ranging measurements of the session aggregated before a single range data
+/// notification is delivered, 1 to disable the batching. Return value defined by uci_packets.pdl
+#[no_mangle]
+pub extern "system" fn Java_com_android_server_uwb_jni_NativeUwbManager_nativeSetBatchNotificationSize(
+    env: JNIEnv,
+    obj: JObject,
+    session_id: jint,
+    count: jint,
+    chip_id: JString,
+) -> jbyte {
+    debug!("{}: enter", function_name!());
+    byte_result_helper(
+        native_set_batch_notification_size(env, obj, session_id, count, chip_id),
+        function_name!(),
+    )
+}
+
+fn native_set_batch_notification_size(
+    env: JNIEnv,
+    obj: JObject,
+    session_id: jint,
+    count: jint,
+    chip_id: JString,
+) -> Result<()> {
+    let count = usize::try_from(count).map_err(|_| Error::BadParameters)?;
+    let uci_manager = Dispatcher::get_uci_manager(env, obj, chip_id)?;
+    let result = uci_manager.chip_state()?.set_batch_notification_size(session_id as u32, count);
+    result
+}
+
+/// Get the state of a radar session. Return -1 if not a radar session or if failed.
+#[no_mangle]
+pub extern "system" fn Java_com_android_server_uwb_jni_NativeUwbManager_nativeGetRadarSessionState(
+    env: JNIEnv,
+    obj: JObject,
+    session_id: jint,
+    chip_id: JString,
+) -> jbyte {
+    debug!("{}: enter", function_name!());
+    match option_result_helper(
+        native_get_radar_session_state(env, obj, session_id, chip_id),
+        function_name!(),
+    ) {
+        // SessionState does not overflow i8
+        Some(Some(state)) => state as i8,
+        _ => -1,
+    }
+}
+
+fn native_get_radar_session_state(
+    env: JNIEnv,
+    obj: JObject,
+    session_id: jint,
+    chip_id: JString,
+) -> Result<Option<SessionState>> {
+    let uci_manager = Dispatcher::get_uci_manager(env, obj, chip_id)?;
+    let is_radar_session = uci_manager.chip_state()?.is_radar_session(session_id as u32);
+    if !is_radar_session {
+        return Ok(None);
+    }
+    uci_manager.session_get_state(session_id as u32).map(Some)
+}
+
+/// Set the number of ranging rounds after which the STS key of the session is refreshed, a power of
+/// 2 up to 2^15 or 0 to never refresh it. Return value defined by uci_packets.pdl
+#[no_mangle]
+pub extern "system" fn Java_com_android_server_uwb_jni_NativeUwbManager_nativeSetStsKeyRefreshRounds(
+    env: JNIEnv,
+    obj: JObject,
+    session_id: jint,
+    rounds: jint,
+    chip_id: JString,
+) -> jbyte {
+    debug!("{}: enter", function_name!());
+    byte_result_helper(
+        native_set_sts_key_refresh_rounds(env, obj, session_id, rounds, chip_id),
+        function_name!(),
+    )
+}
+
+fn native_set_sts_key_refresh_rounds(
+    env: JNIEnv,
+    obj: JObject,
+    session_id: jint,
+    rounds: jint,
+    chip_id: JString,
+) -> Result<()> {
+    let tlvs = sts_key_refresh_rounds_tlvs(rounds)?;
+    let uci_manager = Dispatcher::get_uci_manager(env, obj, chip_id)?;
+    session_set_app_config_tlvs(&*uci_manager, session_id as u32, tlvs)
+}
+
+/// Get the number of ranging rounds after which the STS key of the session is refreshed, 0 if
+/// never refreshed. Return -1 if failed.
+#[no_mangle]
+pub extern "system" fn Java_com_android_server_uwb_jni_NativeUwbManager_nativeGetStsKeyRefreshRounds(
+    env: JNIEnv,
+    obj: JObject,
+    session_id: jint,
+    chip_id: JString,
+) -> jint {
+    debug!("{}: enter", function_name!());
+    match option_result_helper(
+        native_get_sts_key_refresh_rounds(env, obj, session_id, chip_id),
+        function_name!(),
+    ) {
+        Some(rounds) => rounds.try_into().unwrap_or(-1),
+        None => -1,
+    }
+}
+
+fn native_get_sts_key_refresh_rounds(
+    env: JNIEnv,
+    obj: JObject,
+    session_id: jint,
+    chip_id: JString,
+) -> Result<u32> {
+    let uci_manager = Dispatcher::get_uci_manager(env, obj, chip_id)?;
+    let key_rotation = decode_le_value(&session_get_app_config_value(
+        &*uci_manager,
+        session_id as u32,
+        AppConfigTlvType::KeyRotation,
+    )?)?;
+    if key_rotation == 0 {
+        return Ok(0);
+    }
+    let key_rotation_rate = decode_le_value(&session_get_app_config_value(
+        &*uci_manager,
+        session_id as u32,
+        AppConfigTlvType::KeyRotationRate,
+    )?)?;
+    1u32.checked_shl(key_rotation_rate).ok_or(Error::BadParameters)
+}
+
+/// Get the STS mode of the session, 0 for static, 1 for dynamic and 2 for provisioned STS. Return
+/// -1 if unset or if failed.
+#[no_mangle]
+pub extern "system" fn Java_com_android_server_uwb_jni_NativeUwbManager_nativeGetStsMode(
+    env: JNIEnv,
+    obj: JObject,
+    session_id: jint,
+    chip_id: JString,
+) -> jint {
+    debug!("{}: enter", function_name!());
+    match option_result_helper(
+        native_get_app_config_value(env, obj, session_id, AppConfigTlvType::StsConfig, chip_id),
+        function_name!(),
+    ) {
+        Some(sts_config) => decode_sts_mode(sts_config).map_or(-1, jint::from),
+        None => -1,
+    }
+}
+
+/// Set the ranging error policy of the session: the number of consecutive failed ranging rounds
+/// after which the session is stopped by the UWBS, or 0 to keep ranging. Return value defined by
+/// uci_packets.pdl
+#[no_mangle]
+pub extern "system" fn Java_com_android_server_uwb_jni_NativeUwbManager_nativeSetRangingErrorPolicy(
+    env: JNIEnv,
+    obj: JObject,
+    session_id: jint,
+    policy: jint,
+    chip_id: JString,
+) -> jbyte {
+    debug!("{}: enter", function_name!());
+    byte_result_helper(
+        native_set_ranging_error_policy(env, obj, session_id, policy, chip_id),
+        function_name!(),
+    )
+}
+
+fn native_set_ranging_error_policy(
+    env: JNIEnv,
+    obj: JObject,
+    session_id: jint,
+    policy: jint,
+    chip_id: JString,
+) -> Result<()> {
+    native_set_app_config_tlv(env, obj, session_id, ranging_error_policy_tlv(policy)?, chip_id)
+}
+
+/// Set the multiplier of the block duration of the session in the hybrid scheduling, from 1 to
+/// 256, as its BLOCK_STRIDE_LENGTH: the session ranges in one of every `multiplier` blocks. Return
+/// value defined by uci_packets.pdl
+#[no_mangle]
+pub extern "system" fn Java_com_android_server_uwb_jni_NativeUwbManager_nativeSetBlockDurationMultiplier(
+    env: JNIEnv,
+    obj: JObject,
+    session_id: jint,
+    multiplier: jint,
+    chip_id: JString,
+) -> jbyte {
+    debug!("{}: enter", function_name!());
+    byte_result_helper(
+        native_set_block_duration_multiplier(env, obj, session_id, multiplier, chip_id),
+        function_name!(),
+    )
+}
+
+fn native_set_block_duration_multiplier(
+    env: JNIEnv,
+    obj: JObject,
+    session_id: jint,
+    multiplier: jint,
+    chip_id: JString,
+) -> Result<()> {
+    native_set_app_config_tlv(
+        env,
+        obj,
+        session_id,
+        block_duration_multiplier_tlv(multiplier)?,
+        chip_id,
+    )
+}
+
+/// Get the multiplier of the block duration of the session, from its BLOCK_STRIDE_LENGTH. Return
+/// -1 if failed.
+#[no_mangle]
+pub extern "system" fn Java_com_android_server_uwb_jni_NativeUwbManager_nativeGetBlockDurationMultiplier(
+    env: JNIEnv,
+    obj: JObject,
+    session_id: jint,
+    chip_id: JString,
+) -> jint {
+    debug!("{}: enter", function_name!());
+    match option_result_helper(
+        fira_cfg_id(BLOCK_STRIDE_LENGTH)
+            .and_then(|cfg_id| native_get_app_config_value(env, obj, session_id, cfg_id, chip_id))
+            .and_then(decode_block_duration_multiplier),
+        function_name!(),
+    ) {
+        Some(multiplier) => multiplier.try_into().unwrap_or(-1),
+        None => -1,
+    }
+}
+
+/// Get whether the data transferred in the session is encrypted, which depends on its STS
+/// configuration. Return 1 if encrypted, 0 if not and -1 if failed.
+#[no_mangle]
+pub extern "system" fn Java_com_android_server_uwb_jni_NativeUwbManager_nativeIsDataEncrypted(
+    env: JNIEnv,
+    obj: JObject,
+    session_id: jint,
+    chip_id: JString,
+) -> jint {
+    debug!("{}: enter", function_name!());
+    match option_result_helper(
+        native_get_app_config_value(env, obj, session_id, AppConfigTlvType::StsConfig, chip_id),
+        function_name!(),
+    ) {
+        Some(sts_config) => is_data_encrypted(sts_config).map_or(-1, jint::from),
+        None => -1,
+    }
+}
+
+/// Set the number of times a data message of the session is retransmitted, from 0 to 255, as the
+/// DATA_REPETITION_COUNT of the session. Return value defined by uci_packets.pdl
+#[no_mangle]
+pub extern "system" fn Java_com_android_server_uwb_jni_NativeUwbManager_nativeSetDataRetransmissionCount(
+    env: JNIEnv,
+    obj: JObject,
+    session_id: jint,
+    count: jint,
+    chip_id: JString,
+) -> jbyte {
+    debug!("{}: enter", function_name!());
+    byte_result_helper(
+        native_set_data_retransmission_count(env, obj, session_id, count, chip_id),
+        function_name!(),
+    )
+}
+
+fn native_set_data_retransmission_count(
+    env: JNIEnv,
+    obj: JObject,
+    session_id: jint,
+    count: jint,
+    chip_id: JString,
+) -> Result<()> {
+    native_set_app_config_tlv(env, obj, session_id, data_retransmission_count_tlv(count)?, chip_id)
+}
+
+/// Get the number of times a data message of the session is retransmitted, from the
+/// DATA_REPETITION_COUNT of the session. Return -1 if failed.
+#[no_mangle]
+pub extern "system" fn Java_com_android_server_uwb_jni_NativeUwbManager_nativeGetDataRetransmissionCount(
+    env: JNIEnv,
+    obj: JObject,
+    session_id: jint,
+    chip_id: JString,
+) -> jint {
+    debug!("{}: enter", function_name!());
+    match option_result_helper(
+        native_get_app_config_value(
+            env,
+            obj,
+            session_id,
+            AppConfigTlvType::DataRepetitionCount,
+            chip_id,
+        ),
+        function_name!(),
+    ) {
+        // DATA_REPETITION_COUNT is 1 byte long, will not overflow i32
+        Some(count) => count as jint,
+        None => -1,
+    }
+}
+
+/// Get the lengths, in bits, of the STS keys supported by the UWBS. Return an empty array if the
+/// UWBS does not support FiRa 2.0, and null if failed.
+#[no_mangle]
+pub extern "system" fn Java_com_android_server_uwb_jni_NativeUwbManager_nativeGetSupportedStsKeyLengths(
+    env: JNIEnv,
+    obj: JObject,
+    chip_id: JString,
+) -> jintArray {
+    debug!("{}: enter", function_name!());
+    match option_result_helper(
+        native_get_supported_sts_key_lengths(env, obj, chip_id),
+        function_name!(),
+    ) {
+        Some(key_lengths) => key_lengths,
+        None => *JObject::null(),
+    }
+}
+
+fn native_get_supported_sts_key_lengths(
+    env: JNIEnv,
+    obj: JObject,
+    chip_id: JString,
+) -> Result<jintArray> {
+    let key_lengths = find_supported_sts_key_lengths(&native_get_caps_info(env, obj, chip_id)?)
+        .into_iter()
+        .map(|key_length| key_length as jint)
+        .collect::<Vec<_>>();
+    create_int_array(env, &key_lengths)
+}
+
+/// Enable or disable the logging of the latency of each UCI command sent to the UWBS. Return value
+/// defined by uci_packets.pdl
+#[no_mangle]
+pub extern "system" fn Java_com_android_server_uwb_jni_NativeUwbManager_nativeSetLatencyLoggingEnabled(
+    env: JNIEnv,
+    obj: JObject,
+    enabled: jboolean,
+    chip_id: JString,
+) -> jbyte {
+    debug!("{}: enter", function_name!());
+    byte_result_helper(
+        native_set_latency_logging_enabled(env, obj, enabled != 0, chip_id),
+        function_name!(),
+    )
+}
+
+fn native_set_latency_logging_enabled(
+    env: JNIEnv,
+    obj: JObject,
+    enabled: bool,
+    chip_id: JString,
+) -> Result<()> {
+    let uci_manager = Dispatcher::get_uci_manager(env, obj, chip_id)?;
+    let latency_observer: Option<LatencyObserver> = if enabled {
+        Some(Box::new(|opcode, latency| {
+            info!("UCI command {:#06x} completed in {:?}", opcode, latency);
+        }))
+    } else {
+        None
+    };
+    uci_manager.chip_state()?.set_latency_observer(latency_observer);
+    Ok(())
+}
+
+/// Get the last two way ranging measurement of the controlee with the MAC address `address` in
+/// the session. Return null JObject if none or if failed.
+#[no_mangle]
+pub extern "system" fn Java_com_android_server_uwb_jni_NativeUwbManager_nativeGetControleeRangingResult(
+    env: JNIEnv,
+    obj: JObject,
+    session_id: jint,
+    address: jbyteArray,
+    chip_id: JString,
+) -> jobject {
+    debug!("{}: enter", function_name!());
+    match option_result_helper(
+        native_get_controlee_ranging_result(env, obj, session_id, address, chip_id),
+        function_name!(),
+    ) {
+        Some(Some(measurement)) => create_two_way_measurement(measurement, env)
+            .map_err(|e| {
+                error!("{} failed with {:?}", function_name!(), &e);
+                e
+            })
+            .unwrap_or(*JObject::null()),
+        _ => *JObject::null(),
+    }
+}
+
+fn native_get_controlee_ranging_result(
+    env: JNIEnv,
+    obj: JObject,
+    session_id: jint,
+    address: jbyteArray,
+    chip_id: JString,
+) -> Result<Option<TwoWayRangingMeasurement>> {
+    let uci_manager = Dispatcher::get_uci_manager(env, obj, chip_id)?;
+    let address = env.convert_byte_array(address).map_err(|_| Error::ForeignFunctionInterface)?;
+    let measurement = uci_manager.chip_state()?.controlee_measurement(session_id as u32, &address);
+    Ok(measurement)
+}
+
+fn create_two_way_measurement(
+    measurement: TwoWayRangingMeasurement,
+    env: JNIEnv,
+) -> Result<jobject> {
+    let measurement_class = env
+        .find_class(UWB_TWO_WAY_MEASUREMENT_CLASS)
+        .map_err(|_| Error::ForeignFunctionInterface)?;
+    let mac_address_jbytearray = env
+        .byte_array_from_slice(&measurement.mac_address.into_ne_bytes())
+        .map_err(|_| Error::ForeignFunctionInterface)?;
+
+    // Safety: mac_address_jbytearray is safely instantiated above.
+    let mac_address_jobject = unsafe { JObject::from_raw(mac_address_jbytearray) };
+    let measurement_jobject = env
+        .new_object(
+            measurement_class,
+            "([BIIIIIIIIIIIII)V",
+            &[
+                JValue::Object(mac_address_jobject),
+                JValue::Int(i32::from(measurement.status)),
+                JValue::Int(measurement.nlos as i32),
+                JValue::Int(measurement.distance as i32),
+                JValue::Int(measurement.aoa_azimuth as i32),
+                JValue::Int(measurement.aoa_azimuth_fom as i32),
+                JValue::Int(measurement.aoa_elevation as i32),
+                JValue::Int(measurement.aoa_elevation_fom as i32),
+                JValue::Int(measurement.aoa_destination_azimuth as i32),
+                JValue::Int(measurement.aoa_destination_azimuth_fom as i32),
+                JValue::Int(measurement.aoa_destination_elevation as i32),
+                JValue::Int(measurement.aoa_destination_elevation_fom as i32),
+                JValue::Int(measurement.slot_index as i32),
+                JValue::Int(measurement.rssi as i32),
+            ],
+        )
+        .map_err(|_| Error::ForeignFunctionInterface)?;
+    Ok(*measurement_jobject)
+}
+
+/// Set the number of ranging measurements of each controlee of the session averaged to smooth the
+/// AoA delivered to the Java side, 1 to disable the filter. Return value defined by uci_packets.pdl
+#[no_mangle]
+pub extern "system" fn Java_com_android_server_uwb_jni_NativeUwbManager_nativeSetAoaFilterWindow(
+    env: JNIEnv,
+    obj: JObject,
+    session_id: jint,
+    window: jint,
+    chip_id: JString,
+) -> jbyte {
+    debug!("{}: enter", function_name!());
+    byte_result_helper(
+        native_set_aoa_filter_window(env, obj, session_id, window, chip_id),
+        function_name!(),
+    )
+}
+
+fn native_set_aoa_filter_window(
+    env: JNIEnv,
+    obj: JObject,
+    session_id: jint,
+    window: jint,
+    chip_id: JString,
+) -> Result<()> {
+    let window = usize::try_from(window).map_err(|_| Error::BadParameters)?;
+    let uci_manager = Dispatcher::get_uci_manager(env, obj, chip_id)?;
+    let result = uci_manager.chip_state()?.set_aoa_filter_window(session_id as u32, window);
+    result
+}
+
+/// Set the precision, in cm, of the distances of the session delivered to the Java side, which are
+/// rounded to the nearest multiple of it. 1 leaves the distances as reported by the UWBS. Return
+/// value defined by uci_packets.pdl
+#[no_mangle]
+pub extern "system" fn Java_com_android_server_uwb_jni_NativeUwbManager_nativeSetTofPrecision(
+    env: JNIEnv,
+    obj: JObject,
+    session_id: jint,
+    precision: jint,
+    chip_id: JString,
+) -> jbyte {
+    debug!("{}: enter", function_name!());
+    byte_result_helper(
+        native_set_tof_precision(env, obj, session_id, precision, chip_id),
+        function_name!(),
+    )
+}
+
+fn native_set_tof_precision(
+    env: JNIEnv,
+    obj: JObject,
+    session_id: jint,
+    precision: jint,
+    chip_id: JString,
+) -> Result<()> {
+    let precision_cm = u16::try_from(precision).map_err(|_| Error::BadParameters)?;
+    let uci_manager = Dispatcher::get_uci_manager(env, obj, chip_id)?;
+    let result = uci_manager.chip_state()?.set_distance_precision(session_id as u32, precision_cm);
+    result
+}
+
+/// Get the precision, in cm, of the distances of the session. Return -1 if failed.
+#[no_mangle]
+pub extern "system" fn Java_com_android_server_uwb_jni_NativeUwbManager_nativeGetTofPrecision(
+    env: JNIEnv,
+    obj: JObject,
+    session_id: jint,
+    chip_id: JString,
+) -> jint {
+    debug!("{}: enter", function_name!());
+    match option_result_helper(
+        native_get_tof_precision(env, obj, session_id, chip_id),
+        function_name!(),
+    ) {
+        Some(precision_cm) => precision_cm.into(),
+        None => -1,
+    }
+}
+
+fn native_get_tof_precision(
+    env: JNIEnv,
+    obj: JObject,
+    session_id: jint,
+    chip_id: JString,
+) -> Result<u16> {
+    let uci_manager = Dispatcher::get_uci_manager(env, obj, chip_id)?;
+    let precision_cm = uci_manager.chip_state()?.distance_precision(session_id as u32);
+    Ok(precision_cm)
+}
+
+/// Set the interval in ms between two one way ranging rounds of an OWR for AoA session, as its
+/// RANGING_DURATION. Return value defined by uci_packets.pdl
+#[no_mangle]
+pub extern "system" fn Java_com_android_server_uwb_jni_NativeUwbManager_nativeSetOwrAoaRangingInterval(
+    env: JNIEnv,
+    obj: JObject,
+    session_id: jint,
+    interval_ms: jint,
+    chip_id: JString,
+) -> jbyte {
+    debug!("{}: enter", function_name!());
+    byte_result_helper(
+        native_set_owr_aoa_ranging_interval(env, obj, session_id, interval_ms, chip_id),
+        function_name!(),
+    )
+}
+
+fn native_set_owr_aoa_ranging_interval(
+    env: JNIEnv,
+    obj: JObject,
+    session_id: jint,
+    interval_ms: jint,
+    chip_id: JString,
+) -> Result<()> {
+    native_set_app_config_tlv(
+        env,
+        obj,
+        session_id,
+        owr_aoa_ranging_interval_tlv(interval_ms)?,
+        chip_id,
+    )
+}
+
+/// Get the interval in ms between two one way ranging rounds of an OWR for AoA session, from its
+/// RANGING_DURATION. Return -1 if failed.
+#[no_mangle]
+pub extern "system" fn Java_com_android_server_uwb_jni_NativeUwbManager_nativeGetOwrAoaRangingInterval(
+    env: JNIEnv,
+    obj: JObject,
+    session_id: jint,
+    chip_id: JString,
+) -> jint {
+    debug!("{}: enter", function_name!());
+    match option_result_helper(
+        native_get_app_config_value(
+            env,
+            obj,
+            session_id,
+            AppConfigTlvType::RangingDuration,
+            chip_id,
+        ),
+        function_name!(),
+    ) {
+        Some(interval_ms) => interval_ms.try_into().unwrap_or(-1),
+        None => -1,
+    }
+}
+
+/// Get the max size, in bytes, of the in-band data payload carried by a single packet of the
+/// session, its MTU_SIZE, separately from the max size of a data message. Return -1 if unknown or
+/// if failed.
+#[no_mangle]
+pub extern "system" fn Java_com_android_server_uwb_jni_NativeUwbManager_nativeGetMaxInbandPayload(
+    env: JNIEnv,
+    obj: JObject,
+    session_id: jint,
+    chip_id: JString,
+) -> jint {
+    debug!("{}: enter", function_name!());
+    match option_result_helper(
+        native_get_max_inband_payload(env, obj, session_id, chip_id),
+        function_name!(),
+    ) {
+        Some(size) => size.into(),
+        None => -1,
+    }
+}
+
+fn native_get_max_inband_payload(
+    env: JNIEnv,
+    obj: JObject,
+    session_id: jint,
+    chip_id: JString,
+) -> Result<u16> {
+    let uci_manager = Dispatcher::get_uci_manager(env, obj, chip_id)?;
+    session_get_max_inband_payload(&*uci_manager, session_id as u32)
+}
+
+/// Set the AoA bounds of the session, in degrees as signed Q9.7 values, outside of which the range
+/// data is not notified when enabled by RANGE_DATA_NTF_CONFIG. Return value defined by
+/// uci_packets.pdl
+#[no_mangle]
+pub extern "system" fn Java_com_android_server_uwb_jni_NativeUwbManager_nativeSetAoaBound(
+    env: JNIEnv,
+    obj: JObject,
+    session_id: jint,
+    azimuth_min: jint,
+    azimuth_max: jint,
+    elevation_min: jint,
+    elevation_max: jint,
+    chip_id: JString,
+) -> jbyte {
+    debug!("{}: enter", function_name!());
+    byte_result_helper(
+        native_set_aoa_bound(
+            env,
+            obj,
+            session_id,
+            [azimuth_min, azimuth_max, elevation_min, elevation_max],
+            chip_id,
+        ),
+        function_name!(),
+    )
+}
+
+fn native_set_aoa_bound(
+    env: JNIEnv,
+    obj: JObject,
+    session_id: jint,
+    bound: [jint; 4],
+    chip_id: JString,
+) -> Result<()> {
+    native_set_app_config_tlv(env, obj, session_id, aoa_bound_tlv(bound)?, chip_id)
+}
+
+/// Get the AoA bounds of the session, as the azimuth min and max followed by the elevation min and
+/// max, in degrees as signed Q9.7 values. Return null if failed.
+#[no_mangle]
+pub extern "system" fn Java_com_android_server_uwb_jni_NativeUwbManager_nativeGetAoaBound(
+    env: JNIEnv,
+    obj: JObject,
+    session_id: jint,
+    chip_id: JString,
+) -> jintArray {
+    debug!("{}: enter", function_name!());
+    match option_result_helper(
+        native_get_aoa_bound(env, obj, session_id, chip_id),
+        function_name!(),
+    ) {
+        Some(bound) => bound,
+        None => *JObject::null(),
+    }
+}
+
+fn native_get_aoa_bound(
+    env: JNIEnv,
+    obj: JObject,
+    session_id: jint,
+    chip_id: JString,
+) -> Result<jintArray> {
+    let uci_manager = Dispatcher::get_uci_manager(env, obj, chip_id)?;
+    let bound = session_get_aoa_bound(&*uci_manager, session_id as u32)?;
+    create_int_array(env, &bound)
+}
+
+/// Get the RANGING_ROUND_USAGE values, e.g. SS-TWR, DS-TWR or OWR for AoA, supported by the UWBS.
+/// Return an empty array if not advertised by the UWBS, and null if failed.
+#[no_mangle]
+pub extern "system" fn Java_com_android_server_uwb_jni_NativeUwbManager_nativeGetSupportedRoundUsageModes(
+    env: JNIEnv,
+    obj: JObject,
+    chip_id: JString,
+) -> jintArray {
+    debug!("{}: enter", function_name!());
+    match option_result_helper(
+        native_get_supported_round_usage_modes(env, obj, chip_id),
+        function_name!(),
+    ) {
+        Some(usages) => usages,
+        None => *JObject::null(),
+    }
+}
+
+fn native_get_supported_round_usage_modes(
+    env: JNIEnv,
+    obj: JObject,
+    chip_id: JString,
+) -> Result<jintArray> {
+    let usages = find_supported_round_usages(&native_get_caps_info(env, obj, chip_id)?)
+        .into_iter()
+        .map(jint::from)
+        .collect::<Vec<_>>();
+    create_int_array(env, &usages)
+}
+
+/// Set the MAC_ADDRESS_MODE of the session: 0 for short addresses, 1 for extended addresses with a
+/// short MAC header and 2 for extended addresses. Return value defined by uci_packets.pdl
+#[no_mangle]
+pub extern "system" fn Java_com_android_server_uwb_jni_NativeUwbManager_nativeSetMacAddressMode(
+    env: JNIEnv,
+    obj: JObject,
+    session_id: jint,
+    mode: jint,
+    chip_id: JString,
+) -> jbyte {
+    debug!("{}: enter", function_name!());
+    byte_result_helper(
+        native_set_mac_address_mode(env, obj, session_id, mode, chip_id),
+        function_name!(),
+    )
+}
+
+fn native_set_mac_address_mode(
+    env: JNIEnv,
+    obj: JObject,
+    session_id: jint,
+    mode: jint,
+    chip_id: JString,
+) -> Result<()> {
+    let tlv = mac_address_mode_tlv(mode)?;
+    let uci_manager = Dispatcher::get_uci_manager(env, obj, chip_id)?;
+    let result = session_set_app_config_tlv(&*uci_manager, session_id as u32, tlv);
+    // The mode is known to be in range once its TLV is built.
+    let mode = result.is_ok().then_some(mode as u8);
+    uci_manager.chip_state()?.on_mac_address_mode_set(session_id as u32, mode);
+    result
+}
+
+/// Get the MAC_ADDRESS_MODE of the session. Return -1 if failed.
+#[no_mangle]
+pub extern "system" fn Java_com_android_server_uwb_jni_NativeUwbManager_nativeGetMacAddressMode(
+    env: JNIEnv,
+    obj: JObject,
+    session_id: jint,
+    chip_id: JString,
+) -> jint {
+    debug!("{}: enter", function_name!());
+    match option_result_helper(
+        fira_cfg_id(MAC_ADDRESS_MODE)
+            .and_then(|cfg_id| native_get_app_config_value(env, obj, session_id, cfg_id, chip_id)),
+        function_name!(),
+    ) {
+        Some(mode) => mode.try_into().unwrap_or(-1),
+        None => -1,
+    }
+}
+
+/// Get the role of the local device in the session, from its DEVICE_TYPE: 0 for a controlee and 1
+/// for a controller. Return -1 if not set or if failed.
+#[no_mangle]
+pub extern "system" fn Java_com_android_server_uwb_jni_NativeUwbManager_nativeGetSessionRole(
+    env: JNIEnv,
+    obj: JObject,
+    session_id: jint,
+    chip_id: JString,
+) -> jint {
+    debug!("{}: enter", function_name!());
+    match option_result_helper(
+        native_get_session_role(env, obj, session_id, chip_id),
+        function_name!(),
+    ) {
+        Some(role) => role.into(),
+        None => -1,
+    }
+}
+
+fn native_get_session_role(
+    env: JNIEnv,
+    obj: JObject,
+    session_id: jint,
+    chip_id: JString,
+) -> Result<u8> {
+    let uci_manager = Dispatcher::get_uci_manager(env, obj, chip_id)?;
+    session_get_role(&*uci_manager, session_id as u32)
+}
+
+/// Set whether the UWBS sends a data transfer status notification for each data message of the
+/// session. Return value defined by uci_packets.pdl
+#[no_mangle]
+pub extern "system" fn Java_com_android_server_uwb_jni_NativeUwbManager_nativeSetDataTransferStatusNtf(
+    env: JNIEnv,
+    obj: JObject,
+    session_id: jint,
+    enable: jboolean,
+    chip_id: JString,
+) -> jbyte {
+    debug!("{}: enter", function_name!());
+    byte_result_helper(
+        native_set_data_transfer_status_ntf(env, obj, session_id, enable != 0, chip_id),
+        function_name!(),
+    )
+}
+
+fn native_set_data_transfer_status_ntf(
+    env: JNIEnv,
+    obj: JObject,
+    session_id: jint,
+    enable: bool,
+    chip_id: JString,
+) -> Result<()> {
+    native_set_app_config_tlv(env, obj, session_id, data_transfer_status_ntf_tlv(enable)?, chip_id)
+}
+
+/// Get whether the UWBS sends a data transfer status notification for each data message of the
+/// session. Return 1 if enabled, 0 if disabled and -1 if failed.
+#[no_mangle]
+pub extern "system" fn Java_com_android_server_uwb_jni_NativeUwbManager_nativeGetDataTransferStatusNtf(
+    env: JNIEnv,
+    obj: JObject,
+    session_id: jint,
+    chip_id: JString,
+) -> jint {
+    debug!("{}: enter", function_name!());
+    match option_result_helper(
+        fira_cfg_id(SESSION_DATA_TRANSFER_STATUS_NTF_CONFIG)
+            .and_then(|cfg_id| native_get_app_config_value(env, obj, session_id, cfg_id, chip_id)),
+        function_name!(),
+    ) {
+        Some(enabled) => (enabled != 0).into(),
+        None => -1,
+    }
+}
+
+/// Set the policy of the retry of the UCI commands answered with UCI_STATUS_COMMAND_RETRY: the
+/// commands are sent up to max_attempts times, waiting backoff_ms before each resend. A
+/// max_attempts of 1 disables the retry. The policy only applies to the CORE_DEVICE_RESET,
+/// CORE_GET_CAPS_INFO, SESSION_INIT, SESSION_DEINIT, SESSION_SET_APP_CONFIG,
+/// SESSION_GET_APP_CONFIG, SESSION_START and SESSION_STOP commands. Return value defined by
+/// uci_packets.pdl
+#[no_mangle]
+pub extern "system" fn Java_com_android_server_uwb_jni_NativeUwbManager_nativeSetCommandRetryPolicy(
+    env: JNIEnv,
+    obj: JObject,
+    max_attempts: jint,
+    backoff_ms: jint,
+    chip_id: JString,
+) -> jbyte {
+    debug!("{}: enter", function_name!());
+    byte_result_helper(
+        native_set_command_retry_policy(env, obj, max_attempts, backoff_ms, chip_id),
+        function_name!(),
+    )
+}
+
+fn native_set_command_retry_policy(
+    env: JNIEnv,
+    obj: JObject,
+    max_attempts: jint,
+    backoff_ms: jint,
+    chip_id: JString,
+) -> Result<()> {
+    let max_attempts = u32::try_from(max_attempts).map_err(|_| Error::BadParameters)?;
+    let backoff_ms = u64::try_from(backoff_ms).map_err(|_| Error::BadParameters)?;
+    let retry_policy = RetryPolicy::new(max_attempts, Duration::from_millis(backoff_ms))?;
+    let uci_manager = Dispatcher::get_uci_manager(env, obj, chip_id)?;
+    uci_manager.chip_state()?.set_retry_policy(retry_policy);
+    Ok(())
+}
+
+/// Restrict the raw vendor commands to the allowlist of (GID, OID) pairs, flattened in
+/// gid_oid_pairs. A null array removes the allowlist, allowing all the commands. Return value
+/// defined by uci_packets.pdl
+#[no_mangle]
+pub extern "system" fn Java_com_android_server_uwb_jni_NativeUwbManager_nativeSetVendorCmdAllowlist(
+    env: JNIEnv,
+    obj: JObject,
+    gid_oid_pairs: jintArray,
+) -> jbyte {
+    debug!("{}: enter", function_name!());
+    byte_result_helper(native_set_vendor_cmd_allowlist(env, obj, gid_oid_pairs), function_name!())
+}
+
+fn native_set_vendor_cmd_allowlist(
+    env: JNIEnv,
+    obj: JObject,
+    gid_oid_pairs: jintArray,
+) -> Result<()> {
+    let gid_oid_pairs =
+        if gid_oid_pairs.is_null() { None } else { Some(get_int_array(env, gid_oid_pairs)?) };
+    let allowlist = VendorCmdAllowlist::new(gid_oid_pairs.as_deref())?;
+    let dispatcher = Dispatcher::get_dispatcher(env, obj)?;
+    dispatcher.set_vendor_cmd_allowlist(allowlist)
+}
+
+/// Get the most recent generic errors notified by the UWBS, from the oldest to the most recent one.
+/// Each error is encoded as the 1 byte status and the 8 bytes timestamp in ms, in little endian.
+/// Return null if failed.
+#[no_mangle]
+pub extern "system" fn Java_com_android_server_uwb_jni_NativeUwbManager_nativeGetErrorHistory(
+    env: JNIEnv,
+    obj: JObject,
+    chip_id: JString,
+) -> jbyteArray {
+    debug!("{}: enter", function_name!());
+    match option_result_helper(native_get_error_history(env, obj, chip_id), function_name!()) {
+        Some(error_history) => env
+            .byte_array_from_slice(&error_history)
+            .map_err(|e| {
+                error!("{} failed with {:?}", function_name!(), &e);
+                e
+            })
+            .unwrap_or(*JObject::null()),
+        None => *JObject::null(),
+    }
+}
+
+fn native_get_error_history(env: JNIEnv, obj: JObject, chip_id: JString) -> Result<Vec<u8>> {
+    let uci_manager = Dispatcher::get_uci_manager(env, obj, chip_id)?;
+    let error_history = uci_manager.chip_state()?.encode_error_history();
+    Ok(error_history)
+}
+
+/// Get the raw packets of the last `count` UCI responses received from the UWB device, whichever
+/// command produced them, from the oldest to the most recent one. The responses are captured from
+/// the UCI log: none are captured while the log mode is Disabled, and they are captured as logged,
+/// possibly redacted, while it is Filtered. The last 16 responses are kept by default, see
+/// nativeSetRawResponseCapacity. Return null if failed.
+#[no_mangle]
+pub extern "system" fn Java_com_android_server_uwb_jni_NativeUwbManager_nativeGetRecentRawResponses(
+    env: JNIEnv,
+    obj: JObject,
+    count: jint,
+    chip_id: JString,
+) -> jobjectArray {
+    debug!("{}: enter", function_name!());
+    match option_result_helper(
+        native_get_recent_raw_responses(env, obj, count, chip_id),
+        function_name!(),
+    ) {
+        Some(raw_responses) => raw_responses,
+        None => *JObject::null(),
+    }
+}
+
+fn native_get_recent_raw_responses(
+    env: JNIEnv,
+    obj: JObject,
+    count: jint,
+    chip_id: JString,
+) -> Result<jobjectArray> {
+    let count = usize::try_from(count).map_err(|_| Error::BadParameters)?;
+    if !captures_raw_responses(&Dispatcher::get_dispatcher(env, obj)?.logger_mode()?) {
+        warn!("UCI JNI: the UCI logging is disabled, no raw response is captured");
+    }
+    let uci_manager = Dispatcher::get_uci_manager(env, obj, chip_id)?;
+    let raw_responses = uci_manager.chip_state()?.last_raw_responses(count);
+
+    let raw_responses_jobjectarray = env
+        .new_object_array(raw_responses.len() as i32, "[B", JObject::null())
+        .map_err(|_| Error::ForeignFunctionInterface)?;
+    for (i, raw_response) in raw_responses.iter().enumerate() {
+        let raw_response =
+            env.byte_array_from_slice(raw_response).map_err(|_| Error::ForeignFunctionInterface)?;
+        // Safety: raw_response is safely instantiated above.
+        let raw_response = unsafe { JObject::from_raw(raw_response) };
+        env.set_object_array_element(raw_responses_jobjectarray, i as i32, raw_response)
+            .map_err(|_| Error::ForeignFunctionInterface)?;
+    }
+    Ok(raw_responses_jobjectarray)
+}
+
+/// Set the number of raw UCI responses kept for nativeGetRecentRawResponses, 16 by default. The
+/// oldest responses beyond it are dropped, and 0 stops the capture. Return value defined by
+/// uci_packets.pdl
+#[no_mangle]
+pub extern "system" fn Java_com_android_server_uwb_jni_NativeUwbManager_nativeSetRawResponseCapacity(
+    env: JNIEnv,
+    obj: JObject,
+    capacity: jint,
+    chip_id: JString,
+) -> jbyte {
+    debug!("{}: enter", function_name!());
+    byte_result_helper(
+        native_set_raw_response_capacity(env, obj, capacity, chip_id),
+        function_name!(),
+    )
+}
+
+fn native_set_raw_response_capacity(
+    env: JNIEnv,
+    obj: JObject,
+    capacity: jint,
+    chip_id: JString,
+) -> Result<()> {
+    let capacity = usize::try_from(capacity).map_err(|_| Error::BadParameters)?;
+    let uci_manager = Dispatcher::get_uci_manager(env, obj, chip_id)?;
+    uci_manager.chip_state()?.set_raw_response_capacity(capacity);
+    Ok(())
+}
+
+/// Get the max number of sessions doing both ranging and data transfers the UWBS runs at the same
+/// time. The UWBS does not advertise a separate limit for them: any ranging session may transfer
+/// data, so this is SUPPORTED_MAX_RANGING_SESSION_NUMBER. Return -1 if not advertised by the UWBS
+/// or if failed.
+#[no_mangle]
+pub extern "system" fn Java_com_android_server_uwb_jni_NativeUwbManager_nativeGetMaxRangingDataSessions(
+    env: JNIEnv,
+    obj: JObject,
+    chip_id: JString,
+) -> jint {
+    debug!("{}: enter", function_name!());
+    cap_count_to_jint(
+        option_result_helper(native_get_caps_info(env, obj, chip_id), function_name!())
+            .and_then(|tlvs| find_max_ranging_sessions(&tlvs)),
+    )
+}
+
+/// Converts a count advertised in the capabilities to a jint, -1 if not advertised or too large.
+fn cap_count_to_jint(count: Option<u32>) -> jint {
+    count.and_then(|count| jint::try_from(count).ok()).unwrap_or(-1)
+}
+
+/// Get the capabilities of a single UWB device, decoded as a UwbDeviceCapabilities carrying the
+/// max number of ranging sessions, -1 if not advertised, the supported ranging round usages and
+/// channels, and the capability TLVs not decoded, in their UCI encoding. Return null if failed.
+#[no_mangle]
+pub extern "system" fn Java_com_android_server_uwb_jni_NativeUwbManager_nativeGetCapabilitiesStructured(
+    env: JNIEnv,
+    obj: JObject,
+    chip_id: JString,
+) -> jobject {
+    debug!("{}: enter", function_name!());
+    match option_result_helper(native_get_caps_info(env, obj, chip_id), function_name!()) {
+        Some(tlvs) => create_device_capabilities(DeviceCapabilities::from_cap_tlvs(&tlvs), env)
+            .map_err(|e| {
+                error!("{} failed with {:?}", function_name!(), &e);
+                e
+            })
+            .unwrap_or(*JObject::null()),
+        None => *JObject::null(),
+    }
+}
+
+fn create_device_capabilities(caps: DeviceCapabilities, env: JNIEnv) -> Result<jobject> {
+    let device_capabilities_class = env
+        .find_class(UWB_DEVICE_CAPABILITIES_CLASS)
+        .map_err(|_| Error::ForeignFunctionInterface)?;
+    let round_usages: Vec<jint> = caps.round_usages.iter().map(|u| *u as jint).collect();
+    let round_usages = create_int_array(env, &round_usages)?;
+    let channels: Vec<jint> = caps.channels.iter().map(|c| *c as jint).collect();
+    let channels = create_int_array(env, &channels)?;
+    let raw_remaining = env
+        .byte_array_from_slice(&encode_cap_tlvs(&caps.raw_remaining))
+        .map_err(|_| Error::ForeignFunctionInterface)?;
+
+    // Safety: round_usages, channels and raw_remaining are safely instantiated above.
+    let round_usages = unsafe { JObject::from_raw(round_usages) };
+    let channels = unsafe { JObject::from_raw(channels) };
+    let raw_remaining = unsafe { JObject::from_raw(raw_remaining) };
+    match env.new_object(
+        device_capabilities_class,
+        "(I[I[I[B)V",
+        &[
+            JValue::Int(cap_count_to_jint(caps.max_ranging_sessions)),
+            JValue::Object(round_usages),
+            JValue::Object(channels),
+            JValue::Object(raw_remaining),
+        ],
+    ) {
+        Ok(o) => Ok(*o),
+        Err(_) => Err(Error::ForeignFunctionInterface),
+    }
+}
+
+/// Set the priority of the session, from 1 to 100, used by the UWBS to schedule the sessions
+/// running concurrently. Return value defined by uci_packets.pdl
+#[no_mangle]
+pub extern "system" fn Java_com_android_server_uwb_jni_NativeUwbManager_nativeSessionSetPriority(
+    env: JNIEnv,
+    obj: JObject,
+    session_id: jint,
+    priority: jint,
+    chip_id: JString,
+) -> jbyte {
+    debug!("{}: enter", function_name!());
+    byte_result_helper(
+        native_session_set_priority(env, obj, session_id, priority, chip_id),
+        function_name!(),
+    )
+}
+
+fn native_session_set_priority(
+    env: JNIEnv,
+    obj: JObject,
+    session_id: jint,
+    priority: jint,
+    chip_id: JString,
+) -> Result<()> {
+    let uci_manager = Dispatcher::get_uci_manager(env, obj, chip_id)?;
+    let priority = u8::try_from(priority).map_err(|_| Error::BadParameters)?;
+    session_set_priority(&*uci_manager, session_id as u32, priority)
+}
+
+/// Get the number of ranging measurements per second of the session over the last 5 seconds.
+/// Return 0 without recent measurement, or if failed.
+#[no_mangle]
+pub extern "system" fn Java_com_android_server_uwb_jni_NativeUwbManager_nativeGetMeasurementRate(
+    env: JNIEnv,
+    obj: JObject,
+    session_id: jint,
+    chip_id: JString,
+) -> jfloat {
+    debug!("{}: enter", function_name!());
+    option_result_helper(
+        native_get_measurement_rate(env, obj, session_id, chip_id),
+        function_name!(),
+    )
+    .unwrap_or(0.0)
+}
+
+fn native_get_measurement_rate(
+    env: JNIEnv,
+    obj: JObject,
+    session_id: jint,
+    chip_id: JString,
+) -> Result<f32> {
+    let uci_manager = Dispatcher::get_uci_manager(env, obj, chip_id)?;
+    let measurement_rate = uci_manager.chip_state()?.measurement_rate(session_id as u32, now_ms());
+    Ok(measurement_rate)
+}
+
+/// Set the MAC mode of the session, as its RANGING_ROUND_USAGE: 0 for two way ranging and 1 for one
+/// way ranging. A round usage already of this mode is kept. Return value defined by uci_packets.pdl
+#[no_mangle]
+pub extern "system" fn Java_com_android_server_uwb_jni_NativeUwbManager_nativeSetMacMode(
+    env: JNIEnv,
+    obj: JObject,
+    session_id: jint,
+    mode: jint,
+    chip_id: JString,
+) -> jbyte {
+    debug!("{}: enter", function_name!());
+    byte_result_helper(native_set_mac_mode(env, obj, session_id, mode, chip_id), function_name!())
+}
+
+fn native_set_mac_mode(
+    env: JNIEnv,
+    obj: JObject,
+    session_id: jint,
+    mode: jint,
+    chip_id: JString,
+) -> Result<()> {
+    let uci_manager = Dispatcher::get_uci_manager(env, obj, chip_id)?;
+    session_set_mac_mode(&*uci_manager, session_id as u32, mode)
+}
+
+/// Get the MAC mode of the session, decoded from its RANGING_ROUND_USAGE. Return -1 if unknown or
+/// if failed.
+#[no_mangle]
+pub extern "system" fn Java_com_android_server_uwb_jni_NativeUwbManager_nativeGetMacMode(
+    env: JNIEnv,
+    obj: JObject,
+    session_id: jint,
+    chip_id: JString,
+) -> jint {
+    debug!("{}: enter", function_name!());
+    match option_result_helper(
+        native_get_app_config_value(
+            env,
+            obj,
+            session_id,
+            AppConfigTlvType::RangingRoundUsage,
+            chip_id,
+        ),
+        function_name!(),
+    ) {
+        Some(round_usage) => decode_mac_mode(round_usage).map_or(-1, jint::from),
+        None => -1,
+    }
+}
+
+/// Get the class loader object. Has to be called from a JNIEnv where the local java classes are
+/// loaded. Results in a global reference to the class loader object that can be used to look for
+/// classes in other native thread.
+fn get_class_loader_obj(env: &JNIEnv) -> Result<GlobalRef> {
+    let ranging_data_class =
+        env.find_class(UWB_RANGING_DATA_CLASS).map_err(|_| Error::ForeignFunctionInterface)?;
+    let ranging_data_class_class =
+        env.get_object_class(ranging_data_class).map_err(|_| Error::ForeignFunctionInterface)?;
+    let get_class_loader_method = env
+        .get_method_id(ranging_data_class_class, "getClassLoader", "()Ljava/lang/ClassLoader;")
+        .map_err(|_| Error::ForeignFunctionInterface)?;
+    let class_loader = env
+        .call_method_unchecked(
+            ranging_data_class,
+            get_class_loader_method,
+            ReturnType::Object,
+            &[jvalue::from(JValue::Void)],
+        )
+        .map_err(|_| Error::ForeignFunctionInterface)?;
+    let class_loader_jobject = class_loader.l().map_err(|_| Error::ForeignFunctionInterface)?;
+    env.new_global_ref(class_loader_jobject).map_err(|_| Error::ForeignFunctionInterface)
+}
+
+/// Create the dispatcher. Returns pointer to Dispatcher casted as jlong that owns the dispatcher.
+#[no_mangle]
+pub extern "system" fn Java_com_android_server_uwb_jni_NativeUwbManager_nativeDispatcherNew(
+    env: JNIEnv,
+    obj: JObject,
+    chip_ids_jarray: jobjectArray,
+) -> jlong {
+    debug!("{}: enter", function_name!());
+    match option_result_helper(native_dispatcher_new(env, obj, chip_ids_jarray), function_name!()) {
+        Some(ptr) => ptr as jlong,
+        None => *JObject::null() as jlong,
+    }
+}
+
+fn native_dispatcher_new(
+    env: JNIEnv,
+    obj: JObject,
+    chip_ids_jarray: jobjectArray,
+) -> Result<*const Dispatcher> {
+    let chip_ids_len: i32 =
+        env.get_array_length(chip_ids_jarray).map_err(|_| Error::ForeignFunctionInterface)?;
+    let chip_ids = (0..chip_ids_len)
         .map(|i| env.get_string(env.get_object_array_element(chip_ids_jarray, i)?.into()))
         .collect::<std::result::Result<Vec<_>, JNIError>>()
         .map_err(|_| Error::ForeignFunctionInterface)?;
@@ -1407,56 +4772,10 @@ fn native_dispatcher_destroy(env: JNIEnv, obj: JObject) -> Result<()> {
 mod tests {
     use super::*;
 
-    use tokio::runtime::Builder;
-    use uwb_core::uci::mock_uci_manager::MockUciManager;
-    use uwb_core::uci::uci_manager_sync::{
-        NotificationManager, NotificationManagerBuilder, UciManagerSync,
-    };
-    use uwb_core::uci::{
-        CoreNotification, DataRcvNotification, RadarDataRcvNotification, SessionNotification,
-    };
-
-    struct NullNotificationManager {}
-    impl NotificationManager for NullNotificationManager {
-        fn on_core_notification(&mut self, _core_notification: CoreNotification) -> Result<()> {
-            Ok(())
-        }
-        fn on_session_notification(
-            &mut self,
-            _session_notification: SessionNotification,
-        ) -> Result<()> {
-            Ok(())
-        }
-        fn on_vendor_notification(&mut self, _vendor_notification: RawUciMessage) -> Result<()> {
-            Ok(())
-        }
-        fn on_data_rcv_notification(&mut self, _data_rcv_notf: DataRcvNotification) -> Result<()> {
-            Ok(())
-        }
-        /// Callback for RadarDataRcvNotification.
-        fn on_radar_data_rcv_notification(
-            &mut self,
-            _radar_data_rcv_notification: RadarDataRcvNotification,
-        ) -> Result<()> {
-            Ok(())
-        }
-    }
-
-    struct NullNotificationManagerBuilder {}
-
-    impl NullNotificationManagerBuilder {
-        fn new() -> Self {
-            Self {}
-        }
-    }
-
-    impl NotificationManagerBuilder for NullNotificationManagerBuilder {
-        type NotificationManager = NullNotificationManager;
+    use crate::caps::tests::cap_tlv;
+    use crate::test_utils::new_mock_uci_manager_sync;
 
-        fn build(self) -> Option<Self::NotificationManager> {
-            Some(NullNotificationManager {})
-        }
-    }
+    use uwb_core::uci::mock_uci_manager::MockUciManager;
 
     /// Checks validity of the function_name! macro.
     #[test]
@@ -1468,7 +4787,6 @@ mod tests {
     #[test]
     fn test_native_set_app_configurations() {
         // Constructs mock UciManagerSync.
-        let test_rt = Builder::new_multi_thread().enable_all().build().unwrap();
         let mut uci_manager_impl = MockUciManager::new();
         uci_manager_impl.expect_session_set_app_config(
             42, // Session id
@@ -1479,18 +4797,283 @@ mod tests {
             vec![],
             Ok(SetAppConfigResponse { status: StatusCode::UciStatusOk, config_status: vec![] }),
         );
-        let uci_manager_sync = UciManagerSync::new_mock(
-            uci_manager_impl,
-            test_rt.handle().to_owned(),
-            NullNotificationManagerBuilder::new(),
-        )
-        .unwrap();
+        let (_test_rt, uci_manager_sync) = new_mock_uci_manager_sync(uci_manager_impl);
 
         let app_config_byte_array: Vec<u8> = vec![
             0, 1, 1, // DeviceType: controller
             1, 1, 1, // RangingRoundUsage: DS_TWR
         ];
-        let tlvs = parse_app_config_tlv_vec(2, &app_config_byte_array).unwrap();
+        let tlvs = parse_app_config_tlv_vec(2, &app_config_byte_array, 0).unwrap();
         assert!(uci_manager_sync.session_set_app_config(42, tlvs).is_ok());
     }
+
+    #[test]
+    fn test_android_get_radar_config() {
+        let cfg_ids = [RadarConfigTlvType::SamplesPerSweep, RadarConfigTlvType::ChannelNumber];
+        let request = vec![42, 0, 0, 0, 2, 0x01, 0x02];
+        let response = |payload: Vec<u8>| {
+            Ok(RawUciMessage { gid: UCI_GID_ANDROID, oid: ANDROID_GET_RADAR_CONFIG_OID, payload })
+        };
+        let mut uci_manager_impl = MockUciManager::new();
+        uci_manager_impl.expect_session_init(42, SessionType::FiraRangingSession, vec![], Ok(()));
+        uci_manager_impl.expect_raw_uci_cmd(
+            UCI_MT_COMMAND,
+            UCI_GID_ANDROID,
+            ANDROID_GET_RADAR_CONFIG_OID,
+            request.clone(),
+            response(vec![0x00, 2, 0x01, 1, 0x40, 0x02, 1, 0x09]),
+        );
+        // Configurations returned in another order.
+        uci_manager_impl.expect_raw_uci_cmd(
+            UCI_MT_COMMAND,
+            UCI_GID_ANDROID,
+            ANDROID_GET_RADAR_CONFIG_OID,
+            request.clone(),
+            response(vec![0x00, 2, 0x02, 1, 0x09, 0x01, 1, 0x40]),
+        );
+        // Command rejected.
+        uci_manager_impl.expect_raw_uci_cmd(
+            UCI_MT_COMMAND,
+            UCI_GID_ANDROID,
+            ANDROID_GET_RADAR_CONFIG_OID,
+            request,
+            response(vec![0x04, 0]),
+        );
+        let (_rt, uci_manager_sync) = new_mock_uci_manager_sync(uci_manager_impl);
+        assert!(uci_manager_sync.session_init(42, SessionType::FiraRangingSession).is_ok());
+
+        assert_eq!(
+            android_get_radar_config(&uci_manager_sync, 42, &cfg_ids).unwrap(),
+            vec![
+                RadarConfigTlv { cfg_id: RadarConfigTlvType::SamplesPerSweep, v: vec![0x40] },
+                RadarConfigTlv { cfg_id: RadarConfigTlvType::ChannelNumber, v: vec![0x09] },
+            ]
+        );
+        assert!(android_get_radar_config(&uci_manager_sync, 42, &cfg_ids).is_err());
+        assert!(android_get_radar_config(&uci_manager_sync, 42, &cfg_ids).is_err());
+    }
+
+    #[test]
+    fn test_parse_controlee_addresses() {
+        let short_addresses = [0x01, 0x02, 0x03, 0x04];
+        assert_eq!(
+            parse_controlee_addresses(&short_addresses, 2, SHORT_ADDRESS_LEN).unwrap(),
+            vec![[0x01, 0x02], [0x03, 0x04]]
+        );
+        // Trailing bytes.
+        assert!(parse_controlee_addresses(&short_addresses[..3], 1, SHORT_ADDRESS_LEN).is_err());
+
+        // Extended addresses holding short addresses.
+        let extended_addresses = [0x01, 0x02, 0, 0, 0, 0, 0, 0, 0x03, 0x04, 0, 0, 0, 0, 0, 0];
+        assert_eq!(
+            parse_controlee_addresses(&extended_addresses, 2, EXTENDED_ADDRESS_LEN).unwrap(),
+            vec![[0x01, 0x02], [0x03, 0x04]]
+        );
+        assert!(parse_controlee_addresses(&extended_addresses, 2, SHORT_ADDRESS_LEN).is_err());
+        // An extended address which can't be carried as a short address is not truncated.
+        let long_address: Vec<u8> = (1..=8).collect();
+        assert!(parse_controlee_addresses(&long_address, 1, EXTENDED_ADDRESS_LEN).is_err());
+
+        // A short address mixed with an extended one.
+        let mixed_addresses = [0x01, 0x02, 0x03, 0x04, 0, 0, 0, 0, 0, 0];
+        assert!(parse_controlee_addresses(&mixed_addresses, 2, SHORT_ADDRESS_LEN).is_err());
+        assert!(parse_controlee_addresses(&mixed_addresses, 2, EXTENDED_ADDRESS_LEN).is_err());
+    }
+
+    #[test]
+    fn test_mac_address_len() {
+        let mut uci_manager_impl = MockUciManager::new();
+        let cfg_id = fira_cfg_id(MAC_ADDRESS_MODE).unwrap();
+        uci_manager_impl.expect_session_get_app_config(
+            42, // Session id
+            vec![cfg_id],
+            Ok(vec![mac_address_mode_tlv(2).unwrap()]),
+        );
+        uci_manager_impl.expect_session_get_app_config(
+            43, // Session id
+            vec![cfg_id],
+            Err(Error::BadParameters),
+        );
+        let (_rt, uci_manager_sync) = new_mock_uci_manager_sync(uci_manager_impl);
+
+        // The cached mode is used without querying the UWBS.
+        assert_eq!(session_mac_address_mode(&uci_manager_sync, 42, Some(0)).unwrap(), 0);
+        // The addresses of the multicast list update of a session in extended mode are extended.
+        let mode = session_mac_address_mode(&uci_manager_sync, 42, None).unwrap();
+        assert_eq!(mac_address_len(mode.into()).unwrap(), EXTENDED_ADDRESS_LEN);
+        // A failure to query the mode is propagated.
+        assert!(matches!(
+            session_mac_address_mode(&uci_manager_sync, 43, None),
+            Err(Error::BadParameters)
+        ));
+
+        assert_eq!(mac_address_len(0).unwrap(), SHORT_ADDRESS_LEN);
+        assert_eq!(mac_address_len(1).unwrap(), EXTENDED_ADDRESS_LEN);
+        assert!(mac_address_len(3).is_err());
+    }
+
+    #[test]
+    fn test_parse_app_config_tlv_vec() {
+        let tlvs = vec![
+            AppConfigTlv::new(AppConfigTlvType::DeviceType, vec![1]),
+            AppConfigTlv::new(AppConfigTlvType::RangingRoundUsage, vec![1]),
+        ];
+        let exact: Vec<u8> = vec![
+            0, 1, 1, // DeviceType: controller
+            1, 1, 1, // RangingRoundUsage: DS_TWR
+        ];
+        assert_eq!(parse_app_config_tlv_vec(2, &exact, 0).unwrap(), tlvs);
+        assert_eq!(parse_app_config_tlv_vec(2, &exact, 2).unwrap(), tlvs);
+
+        let zero_padded = [exact.as_slice(), &[0, 0]].concat();
+        assert!(parse_app_config_tlv_vec(2, &zero_padded, 0).is_err());
+        assert!(parse_app_config_tlv_vec(2, &zero_padded, 1).is_err());
+        assert_eq!(parse_app_config_tlv_vec(2, &zero_padded, 2).unwrap(), tlvs);
+
+        let garbage_padded = [exact.as_slice(), &[0, 7]].concat();
+        assert!(parse_app_config_tlv_vec(2, &garbage_padded, 0).is_err());
+        assert!(parse_app_config_tlv_vec(2, &garbage_padded, 2).is_err());
+
+        assert!(parse_app_config_tlv_vec(3, &exact, 0).is_err());
+    }
+
+    #[test]
+    fn test_validate_app_config_bytes() {
+        let well_formed: Vec<u8> = vec![
+            0, 1, 1, // DeviceType: controller
+            9, 4, 0xc8, 0, 0, 0, // RangingDuration: 200 ms
+        ];
+        assert!(validate_app_config_bytes(2, &well_formed).is_ok());
+        assert!(validate_app_config_bytes(3, &well_formed).is_err());
+
+        let wrong_len: Vec<u8> = vec![
+            0, 1, 1, // DeviceType: controller
+            9, 2, 0xc8, 0, // RangingDuration, 2 bytes short
+        ];
+        assert!(validate_app_config_bytes(2, &wrong_len).is_err());
+
+        let unknown: Vec<u8> = vec![
+            0, 1, 1, // DeviceType: controller
+            0xE3, 3, 1, 2, 3, // NB_OF_RANGE_MEASUREMENTS, size not validated
+        ];
+        assert!(validate_app_config_bytes(2, &unknown).is_ok());
+    }
+
+    #[test]
+    fn test_parse_tlv_vec_error_location() {
+        let truncated: Vec<u8> = vec![
+            0, 1, 1, // DeviceType: controller
+            1, 2, 1, // RangingRoundUsage, missing a byte
+        ];
+        assert_eq!(
+            parse_app_config_tlv_vec(2, &truncated, 0),
+            Err(TlvParseError { index: 1, offset: 3 })
+        );
+        assert_eq!(
+            parse_app_config_tlv_vec(1, &truncated, 0),
+            Err(TlvParseError { index: 1, offset: 3 })
+        );
+
+        let truncated: Vec<u8> = vec![
+            0, 1, 1, // RadarTimingParams
+            1, 2, 0x40, // SamplesPerSweep, missing a byte
+        ];
+        assert_eq!(
+            parse_radar_config_tlv_vec(2, &truncated),
+            Err(TlvParseError { index: 1, offset: 3 })
+        );
+        assert_eq!(
+            parse_radar_config_tlv_vec(1, &truncated),
+            Err(TlvParseError { index: 1, offset: 3 })
+        );
+    }
+
+    #[test]
+    fn test_log_mode_str() {
+        for logger_mode_str in ["Disabled", "Unfiltered", "Filtered"] {
+            let logger_mode = UciLoggerMode::try_from(logger_mode_str.to_owned()).unwrap();
+            assert_eq!(log_mode_str(&logger_mode), logger_mode_str);
+        }
+    }
+
+    #[test]
+    fn test_is_log_mode_supported() {
+        assert!(is_log_mode_supported("Filtered".to_owned()));
+        assert!(!is_log_mode_supported("Verbose".to_owned()));
+    }
+
+    #[test]
+    fn test_parse_country_code() {
+        assert_eq!(parse_country_code("US").unwrap(), CountryCode::new(b"US").unwrap());
+        // World mode.
+        assert_eq!(parse_country_code("00").unwrap(), CountryCode::new(b"00").unwrap());
+        assert!(parse_country_code("0A").is_err());
+        assert!(parse_country_code("us").is_err());
+        assert!(parse_country_code("USA").is_err());
+        assert!(parse_country_code("U").is_err());
+        assert!(parse_country_code("ÜS").is_err());
+    }
+
+    #[test]
+    fn test_parse_reset_config() {
+        assert_eq!(parse_reset_config(0).unwrap(), ResetConfig::UwbsReset);
+        assert!(parse_reset_config(1).is_err());
+        assert!(parse_reset_config(-1).is_err());
+    }
+
+    #[test]
+    fn test_power_stats_counters() {
+        let power_stats = PowerStats {
+            status: StatusCode::UciStatusOk,
+            idle_time_ms: u32::MAX,
+            tx_time_ms: i32::MAX as u32 + 1,
+            rx_time_ms: i32::MAX as u32,
+            total_wake_count: 42,
+        };
+        assert_eq!(
+            power_stats_counters_long(&power_stats),
+            [u32::MAX as jlong, i32::MAX as jlong + 1, i32::MAX as jlong, 42]
+        );
+        // The legacy counters saturate rather than wrap to negative values.
+        assert_eq!(power_stats_counters_int(&power_stats), [jint::MAX, jint::MAX, jint::MAX, 42]);
+    }
+
+    #[test]
+    fn test_hal_state_transitions() {
+        let device_info = GetDeviceInfoResponse {
+            status: StatusCode::UciStatusOk,
+            uci_version: 0x0002,
+            mac_version: 0x0002,
+            phy_version: 0x0002,
+            uci_test_version: 0x0001,
+            vendor_spec_info: vec![],
+        };
+        let mut uci_manager_impl = MockUciManager::new();
+        uci_manager_impl.expect_open_hal(vec![], Err(Error::Timeout));
+        uci_manager_impl.expect_open_hal(vec![], Ok(device_info));
+        uci_manager_impl.expect_close_hal(true, Ok(()));
+        let (_rt, uci_manager_sync) = new_mock_uci_manager_sync(uci_manager_impl);
+
+        let mut hal_states = vec![];
+        assert!(open_hal_with_state(&uci_manager_sync, |state| hal_states.push(state)).is_err());
+        assert_eq!(hal_states, vec![HalState::Opening, HalState::Error]);
+
+        hal_states.clear();
+        assert!(open_hal_with_state(&uci_manager_sync, |state| hal_states.push(state)).is_ok());
+        assert_eq!(hal_states, vec![HalState::Opening, HalState::Open]);
+
+        hal_states.clear();
+        assert!(close_hal_with_state(&uci_manager_sync, |state| hal_states.push(state)).is_ok());
+        assert_eq!(hal_states, vec![HalState::Closed]);
+    }
+
+    #[test]
+    fn test_max_ranging_data_sessions() {
+        let tlvs = vec![cap_tlv(0x02, vec![1, 1]), cap_tlv(0xE9, vec![5, 0, 0, 0])];
+        assert_eq!(cap_count_to_jint(find_max_ranging_sessions(&tlvs)), 5);
+        assert_eq!(cap_count_to_jint(find_max_ranging_sessions(&tlvs[..1])), -1);
+
+        let tlvs = vec![cap_tlv(0xE9, vec![0xFF, 0xFF, 0xFF, 0xFF])];
+        assert_eq!(cap_count_to_jint(find_max_ranging_sessions(&tlvs)), -1);
+    }
 }